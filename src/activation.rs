@@ -0,0 +1,173 @@
+//! Set `LISTEN_PID` for socket activation, which only the child knows.
+//!
+//! `std` replaces the environment after running `pre_exec` hooks, so the last hook `exec`s the
+//! program itself, with an environment prepared before `fork`.
+
+use std::env;
+use std::ffi;
+use std::io;
+use std::os::unix::ffi::OsStrExt as _;
+use std::os::unix::ffi::OsStringExt as _;
+use std::os::unix::fs::PermissionsExt as _;
+use std::path;
+use std::process;
+use std::sync::Mutex;
+
+const LISTEN_PID: &[u8] = b"LISTEN_PID=";
+
+/// Enough for any `pid_t` and the terminating nul.
+const PID_LEN: usize = 21;
+
+#[derive(Debug)]
+pub(crate) struct Activation {
+    prepared: Mutex<Option<Exec>>,
+}
+
+#[derive(Debug)]
+struct Exec {
+    program: ffi::CString,
+    /// Owns what `argv` and `envp` point to.
+    _strings: Vec<ffi::CString>,
+    argv: Vec<*const libc::c_char>,
+    envp: Vec<*const libc::c_char>,
+    /// `LISTEN_PID=`, with room for the pid.
+    listen_pid: Box<[u8]>,
+}
+
+// SAFETY: the pointers are into `Exec`'s own strings, which are never changed once prepared
+unsafe impl Send for Exec {}
+
+impl Activation {
+    pub(crate) fn new() -> Self {
+        Self {
+            prepared: Mutex::new(None),
+        }
+    }
+
+    /// Prepare to `exec` `cmd` with the environment it would get, before spawning it.
+    pub(crate) fn prepare(&self, cmd: &process::Command, env_cleared: bool) -> io::Result<()> {
+        let mut envs: Vec<(ffi::OsString, ffi::OsString)> = if env_cleared {
+            Vec::new()
+        } else {
+            env::vars_os().collect()
+        };
+        for (key, value) in cmd.get_envs() {
+            envs.retain(|(k, _)| k != key);
+            if let Some(value) = value {
+                envs.push((key.to_owned(), value.to_owned()));
+            }
+        }
+        envs.retain(|(key, _)| key != "LISTEN_PID");
+
+        let path = envs
+            .iter()
+            .find(|(key, _)| key == "PATH")
+            .map(|(_, value)| value.as_os_str());
+        let program = ffi::CString::new(
+            resolve(cmd.get_program(), path)?
+                .into_os_string()
+                .into_vec(),
+        )?;
+
+        let mut strings = Vec::new();
+        for arg in std::iter::once(cmd.get_program()).chain(cmd.get_args()) {
+            strings.push(ffi::CString::new(arg.as_bytes())?);
+        }
+        let args = strings.len();
+        for (key, value) in &envs {
+            let mut var = key.as_bytes().to_vec();
+            var.push(b'=');
+            var.extend_from_slice(value.as_bytes());
+            strings.push(ffi::CString::new(var)?);
+        }
+        let argv = strings[..args]
+            .iter()
+            .map(|arg| arg.as_ptr())
+            .chain(std::iter::once(std::ptr::null()))
+            .collect();
+        // The last two entries are filled in by the child
+        let envp = strings[args..]
+            .iter()
+            .map(|var| var.as_ptr())
+            .chain([std::ptr::null(), std::ptr::null()])
+            .collect();
+        let mut listen_pid = vec![0; LISTEN_PID.len() + PID_LEN].into_boxed_slice();
+        listen_pid[..LISTEN_PID.len()].copy_from_slice(LISTEN_PID);
+
+        *self
+            .prepared
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Exec {
+            program,
+            _strings: strings,
+            argv,
+            envp,
+            listen_pid,
+        });
+        Ok(())
+    }
+
+    /// `exec` the prepared program with `LISTEN_PID` set, in the child.
+    ///
+    /// This must only make async-signal-safe calls.
+    pub(crate) fn exec(&self) -> io::Result<()> {
+        let Ok(mut prepared) = self.prepared.try_lock() else {
+            return Err(io::Error::from_raw_os_error(libc::EAGAIN));
+        };
+        let Some(exec) = prepared.as_mut() else {
+            return Ok(());
+        };
+        // SAFETY: `getpid` is async-signal-safe
+        let pid = unsafe { libc::getpid() };
+        let len = format_pid(pid, &mut exec.listen_pid[LISTEN_PID.len()..]);
+        exec.listen_pid[LISTEN_PID.len() + len] = 0;
+        let slot = exec.envp.len() - 2;
+        exec.envp[slot] = exec.listen_pid.as_ptr().cast();
+        // SAFETY: `execve` is async-signal-safe, and `argv` and `envp` are null-terminated arrays
+        // of the nul-terminated strings owned by `exec`
+        unsafe {
+            libc::execve(
+                exec.program.as_ptr(),
+                exec.argv.as_ptr(),
+                exec.envp.as_ptr(),
+            )
+        };
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Find `program` on `path`, like `execvp`.
+fn resolve(program: &ffi::OsStr, path: Option<&ffi::OsStr>) -> io::Result<path::PathBuf> {
+    if program.as_bytes().contains(&b'/') {
+        return Ok(program.into());
+    }
+    let path = path.unwrap_or_else(|| ffi::OsStr::new("/bin:/usr/bin"));
+    env::split_paths(path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| {
+            candidate
+                .metadata()
+                .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))
+}
+
+/// Write `pid` to `buffer` without allocating, returning the length.
+fn format_pid(pid: libc::pid_t, buffer: &mut [u8]) -> usize {
+    let mut digits = [0_u8; PID_LEN];
+    let mut remaining = pid.unsigned_abs();
+    let mut count = 0;
+    loop {
+        digits[count] = b'0' + (remaining % 10) as u8;
+        count += 1;
+        remaining /= 10;
+        if remaining == 0 {
+            break;
+        }
+    }
+    for (i, digit) in digits[..count].iter().rev().enumerate() {
+        buffer[i] = *digit;
+    }
+    count
+}
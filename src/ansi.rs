@@ -0,0 +1,476 @@
+//! Assert on styled output by parsing [SGR] escape sequences.
+//!
+//! Rather than matching raw escape codes, like `\x1b[1;31merror\x1b[0m`, match the text and
+//! describe the style it should have.
+//!
+//! [SGR]: https://en.wikipedia.org/wiki/ANSI_escape_code#SGR_(Select_Graphic_Rendition)_parameters
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::prelude::*;
+//! use assert_cmd::ansi::contains;
+//!
+//! use std::process::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", "\x1b[1;31merror\x1b[0m: no input")
+//!     .assert()
+//!     .stdout_styled(contains("error").in_red().bold())
+//!     .stdout_styled(contains("no input").plain());
+//! ```
+
+use std::fmt;
+
+use anstyle::AnsiColor;
+use anstyle::Color;
+use anstyle::Effects;
+use anstyle::Style;
+use bstr::ByteSlice;
+
+/// Text with the [`Style`] of each byte.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StyledText {
+    text: Vec<u8>,
+    styles: Vec<Style>,
+}
+
+impl StyledText {
+    /// Parse `data`, dropping escape sequences.
+    ///
+    /// Escape sequences besides SGR, like cursor movement or hyperlinks, are dropped without
+    /// affecting the style.
+    pub fn parse(data: &[u8]) -> Self {
+        const ESC: u8 = 0x1b;
+        const BEL: u8 = 0x07;
+
+        let mut styled = Self::default();
+        let mut style = Style::new();
+        let mut bytes = data.iter().copied().peekable();
+        while let Some(byte) = bytes.next() {
+            if byte != ESC {
+                styled.text.push(byte);
+                styled.styles.push(style);
+                continue;
+            }
+            match bytes.peek() {
+                Some(b'[') => {
+                    bytes.next();
+                    let mut params = Vec::new();
+                    let mut final_byte = None;
+                    for byte in bytes.by_ref() {
+                        if (0x40..=0x7e).contains(&byte) {
+                            final_byte = Some(byte);
+                            break;
+                        }
+                        params.push(byte);
+                    }
+                    if final_byte == Some(b'm') {
+                        style = apply_sgr(style, &params);
+                    }
+                }
+                Some(b']') => {
+                    bytes.next();
+                    // Terminated by BEL or ST (`ESC \\`)
+                    while let Some(byte) = bytes.next() {
+                        if byte == BEL {
+                            break;
+                        }
+                        if byte == ESC && bytes.peek() == Some(&b'\\') {
+                            bytes.next();
+                            break;
+                        }
+                    }
+                }
+                Some(_) => {
+                    bytes.next();
+                }
+                None => {}
+            }
+        }
+        styled
+    }
+
+    /// The text without escape sequences.
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// The style of each byte of [`StyledText::text`].
+    pub fn styles(&self) -> &[Style] {
+        &self.styles
+    }
+}
+
+fn apply_sgr(mut style: Style, params: &[u8]) -> Style {
+    // An empty parameter is the same as `0`
+    let mut params = params.split(|b| *b == b';' || *b == b':').map(|param| {
+        param
+            .to_str()
+            .ok()
+            .and_then(|p| p.parse::<u8>().ok())
+            .unwrap_or(0)
+    });
+    while let Some(param) = params.next() {
+        let effects = style.get_effects();
+        style = match param {
+            0 => Style::new(),
+            1 => style.effects(effects.insert(Effects::BOLD)),
+            2 => style.effects(effects.insert(Effects::DIMMED)),
+            3 => style.effects(effects.insert(Effects::ITALIC)),
+            4 => style.effects(effects.insert(Effects::UNDERLINE)),
+            5 | 6 => style.effects(effects.insert(Effects::BLINK)),
+            7 => style.effects(effects.insert(Effects::INVERT)),
+            8 => style.effects(effects.insert(Effects::HIDDEN)),
+            9 => style.effects(effects.insert(Effects::STRIKETHROUGH)),
+            21 => style.effects(effects.insert(Effects::DOUBLE_UNDERLINE)),
+            22 => style.effects(effects.remove(Effects::BOLD).remove(Effects::DIMMED)),
+            23 => style.effects(effects.remove(Effects::ITALIC)),
+            24 => style.effects(
+                effects
+                    .remove(Effects::UNDERLINE)
+                    .remove(Effects::DOUBLE_UNDERLINE),
+            ),
+            25 => style.effects(effects.remove(Effects::BLINK)),
+            27 => style.effects(effects.remove(Effects::INVERT)),
+            28 => style.effects(effects.remove(Effects::HIDDEN)),
+            29 => style.effects(effects.remove(Effects::STRIKETHROUGH)),
+            30..=37 => style.fg_color(Some(ansi_color(param - 30, false))),
+            38 => style.fg_color(extended_color(&mut params)),
+            39 => style.fg_color(None),
+            40..=47 => style.bg_color(Some(ansi_color(param - 40, false))),
+            48 => style.bg_color(extended_color(&mut params)),
+            49 => style.bg_color(None),
+            90..=97 => style.fg_color(Some(ansi_color(param - 90, true))),
+            100..=107 => style.bg_color(Some(ansi_color(param - 100, true))),
+            _ => style,
+        };
+    }
+    style
+}
+
+fn ansi_color(index: u8, bright: bool) -> Color {
+    let color = match index {
+        0 => AnsiColor::Black,
+        1 => AnsiColor::Red,
+        2 => AnsiColor::Green,
+        3 => AnsiColor::Yellow,
+        4 => AnsiColor::Blue,
+        5 => AnsiColor::Magenta,
+        6 => AnsiColor::Cyan,
+        _ => AnsiColor::White,
+    };
+    color.bright(bright).into()
+}
+
+/// Parse the rest of a `38;5;n` or `38;2;r;g;b` color.
+fn extended_color(params: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match params.next()? {
+        5 => Some(anstyle::Ansi256Color(params.next()?).into()),
+        2 => Some(anstyle::RgbColor(params.next()?, params.next()?, params.next()?).into()),
+        _ => None,
+    }
+}
+
+/// Match `text` with a style, see [`StyledPredicate`].
+pub fn contains(text: impl Into<String>) -> StyledPredicate {
+    StyledPredicate {
+        text: text.into(),
+        fg: None,
+        bg: None,
+        effects: Effects::new(),
+        plain: false,
+    }
+}
+
+/// Output contains text with the expected style.
+///
+/// Passes if any occurrence of the text has the style throughout.  Only what is specified is
+/// checked, so `contains("error").in_red()` also matches bold red text, unless
+/// [`StyledPredicate::plain`] is used.
+///
+/// Created with [`contains`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StyledPredicate {
+    text: String,
+    fg: Option<Color>,
+    bg: Option<Color>,
+    effects: Effects,
+    plain: bool,
+}
+
+impl StyledPredicate {
+    /// Expect the foreground `color`.
+    pub fn fg(mut self, color: impl Into<Color>) -> Self {
+        self.fg = Some(color.into());
+        self
+    }
+
+    /// Expect the background `color`.
+    pub fn bg(mut self, color: impl Into<Color>) -> Self {
+        self.bg = Some(color.into());
+        self
+    }
+
+    /// Expect red text.
+    pub fn in_red(self) -> Self {
+        self.fg(AnsiColor::Red)
+    }
+
+    /// Expect green text.
+    pub fn in_green(self) -> Self {
+        self.fg(AnsiColor::Green)
+    }
+
+    /// Expect yellow text.
+    pub fn in_yellow(self) -> Self {
+        self.fg(AnsiColor::Yellow)
+    }
+
+    /// Expect blue text.
+    pub fn in_blue(self) -> Self {
+        self.fg(AnsiColor::Blue)
+    }
+
+    /// Expect bold text.
+    pub fn bold(mut self) -> Self {
+        self.effects = self.effects.insert(Effects::BOLD);
+        self
+    }
+
+    /// Expect dimmed text.
+    pub fn dimmed(mut self) -> Self {
+        self.effects = self.effects.insert(Effects::DIMMED);
+        self
+    }
+
+    /// Expect italic text.
+    pub fn italic(mut self) -> Self {
+        self.effects = self.effects.insert(Effects::ITALIC);
+        self
+    }
+
+    /// Expect underlined text.
+    pub fn underline(mut self) -> Self {
+        self.effects = self.effects.insert(Effects::UNDERLINE);
+        self
+    }
+
+    /// Expect the text to have no other style than what is specified.
+    pub fn plain(mut self) -> Self {
+        self.plain = true;
+        self
+    }
+
+    fn expected(&self) -> Style {
+        Style::new()
+            .fg_color(self.fg)
+            .bg_color(self.bg)
+            .effects(self.effects)
+    }
+
+    fn matches(&self, actual: Style) -> bool {
+        if self.plain {
+            return actual == self.expected();
+        }
+        self.fg
+            .map(|fg| actual.get_fg_color() == Some(fg))
+            .unwrap_or(true)
+            && self
+                .bg
+                .map(|bg| actual.get_bg_color() == Some(bg))
+                .unwrap_or(true)
+            && actual.get_effects().contains(self.effects)
+    }
+
+    /// The styles of each occurrence of the text, or `None` for an occurrence with mixed styles.
+    fn occurrences(&self, styled: &StyledText) -> Vec<Option<Style>> {
+        let len = self.text.len();
+        styled
+            .text()
+            .find_iter(&self.text)
+            .map(|start| {
+                let styles = &styled.styles()[start..start + len];
+                match styles.first() {
+                    Some(first) if styles.iter().all(|s| s == first) => Some(*first),
+                    Some(_) => None,
+                    None => Some(Style::new()),
+                }
+            })
+            .collect()
+    }
+}
+
+impl predicates_core::reflection::PredicateReflection for StyledPredicate {}
+
+impl predicates_core::Predicate<[u8]> for StyledPredicate {
+    fn eval(&self, actual: &[u8]) -> bool {
+        let styled = StyledText::parse(actual);
+        self.occurrences(&styled)
+            .into_iter()
+            .any(|style| style.is_some_and(|style| self.matches(style)))
+    }
+
+    fn find_case<'a>(
+        &'a self,
+        expected: bool,
+        variable: &[u8],
+    ) -> Option<predicates_core::reflection::Case<'a>> {
+        let actual = self.eval(variable);
+        if expected != actual {
+            return None;
+        }
+        let styled = StyledText::parse(variable);
+        let found = self
+            .occurrences(&styled)
+            .into_iter()
+            .map(|style| match style {
+                Some(style) => Describe(style).to_string(),
+                None => "mixed".to_owned(),
+            })
+            .collect::<Vec<_>>();
+        let found = if found.is_empty() {
+            "not found".to_owned()
+        } else {
+            found.join(", ")
+        };
+        Some(
+            predicates_core::reflection::Case::new(Some(self), actual)
+                .add_product(predicates_core::reflection::Product::new("found", found))
+                .add_product(predicates_core::reflection::Product::new(
+                    "var",
+                    styled.text().as_bstr().to_string(),
+                )),
+        )
+    }
+}
+
+impl fmt::Display for StyledPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "var.contains({:?}) styled {}",
+            self.text,
+            Describe(self.expected())
+        )?;
+        if self.plain {
+            write!(f, " only")?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe a [`Style`] in words, like `red on black, bold`.
+struct Describe(Style);
+
+impl fmt::Display for Describe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        match (self.0.get_fg_color(), self.0.get_bg_color()) {
+            (Some(fg), Some(bg)) => {
+                parts.push(format!("{} on {}", color_name(fg), color_name(bg)));
+            }
+            (Some(fg), None) => parts.push(color_name(fg)),
+            (None, Some(bg)) => parts.push(format!("on {}", color_name(bg))),
+            (None, None) => {}
+        }
+        let effects = self.0.get_effects();
+        for (effect, name) in [
+            (Effects::BOLD, "bold"),
+            (Effects::DIMMED, "dimmed"),
+            (Effects::ITALIC, "italic"),
+            (Effects::UNDERLINE, "underline"),
+            (Effects::DOUBLE_UNDERLINE, "double underline"),
+            (Effects::BLINK, "blink"),
+            (Effects::INVERT, "invert"),
+            (Effects::HIDDEN, "hidden"),
+            (Effects::STRIKETHROUGH, "strikethrough"),
+        ] {
+            if effects.contains(effect) {
+                parts.push(name.to_owned());
+            }
+        }
+        if parts.is_empty() {
+            write!(f, "plain")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+fn color_name(color: Color) -> String {
+    match color {
+        Color::Ansi(color) => {
+            let name = match color {
+                AnsiColor::Black | AnsiColor::BrightBlack => "black",
+                AnsiColor::Red | AnsiColor::BrightRed => "red",
+                AnsiColor::Green | AnsiColor::BrightGreen => "green",
+                AnsiColor::Yellow | AnsiColor::BrightYellow => "yellow",
+                AnsiColor::Blue | AnsiColor::BrightBlue => "blue",
+                AnsiColor::Magenta | AnsiColor::BrightMagenta => "magenta",
+                AnsiColor::Cyan | AnsiColor::BrightCyan => "cyan",
+                AnsiColor::White | AnsiColor::BrightWhite => "white",
+            };
+            if color.is_bright() {
+                format!("bright {name}")
+            } else {
+                name.to_owned()
+            }
+        }
+        Color::Ansi256(color) => format!("color {}", color.index()),
+        Color::Rgb(color) => format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use predicates_core::Predicate;
+
+    #[test]
+    fn parse() {
+        let styled = StyledText::parse(b"\x1b[1;31mab\x1b[22mc\x1b[0m\x1b]8;;url\x07d");
+        assert_eq!(styled.text(), b"abcd");
+        let red = Style::new().fg_color(Some(AnsiColor::Red.into()));
+        assert_eq!(styled.styles(), [red.bold(), red.bold(), red, Style::new()]);
+    }
+
+    #[test]
+    fn extended_colors() {
+        let styled = StyledText::parse(b"\x1b[38;5;196;48;2;0;0;255mx");
+        assert_eq!(
+            styled.styles(),
+            [Style::new()
+                .fg_color(Some(anstyle::Ansi256Color(196).into()))
+                .bg_color(Some(anstyle::RgbColor(0, 0, 255).into()))]
+        );
+    }
+
+    #[test]
+    fn predicate() {
+        let output = b"\x1b[1;31merror\x1b[0m: no input";
+        assert!(contains("error").in_red().bold().eval(output));
+        assert!(contains("error").in_red().eval(output));
+        assert!(!contains("error").in_red().plain().eval(output));
+        assert!(!contains("error").in_green().eval(output));
+        assert!(contains("no input").plain().eval(output));
+        assert!(!contains("or: no").eval(output));
+        assert!(!contains("warning").eval(output));
+    }
+
+    #[test]
+    fn describe() {
+        let style = Style::new()
+            .fg_color(Some(AnsiColor::BrightRed.into()))
+            .bg_color(Some(AnsiColor::Black.into()))
+            .bold()
+            .underline();
+        assert_eq!(
+            Describe(style).to_string(),
+            "bright red on black, bold, underline"
+        );
+        assert_eq!(Describe(Style::new()).to_string(), "plain");
+    }
+}
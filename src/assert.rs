@@ -1,16 +1,25 @@
 //! [`std::process::Output`] assertions.
 
 use std::borrow::Cow;
+use std::env;
 use std::error::Error;
+use std::ffi;
 use std::fmt;
+use std::io;
 use std::process;
 use std::str;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 #[cfg(feature = "color")]
 use anstream::panic;
+use bstr::ByteSlice;
 use predicates::str::PredicateStrExt;
 use predicates_tree::CaseTreeExt;
 
+use crate::cmd::Deadline;
+use crate::cmd::ProcessInfo;
 use crate::output::output_fmt;
 use crate::output::DebugBytes;
 
@@ -53,6 +62,8 @@ pub trait OutputAssertExt {
 impl OutputAssertExt for process::Output {
     fn assert(self) -> Assert {
         Assert::new(self)
+            .try_stderr_policy()
+            .unwrap_or_else(AssertError::panic)
     }
 }
 
@@ -60,56 +71,1947 @@ impl OutputAssertExt for &mut process::Command {
     fn assert(self) -> Assert {
         let output = match self.output() {
             Ok(output) => output,
+            Err(err) => AssertError::spawn(SpawnError::new(err).set_cmd(self, false)).panic(),
+        };
+        Assert::new(output)
+            .append_context("command", crate::output::render_command(self, false))
+            .try_stderr_policy()
+            .unwrap_or_else(AssertError::panic)
+    }
+}
+
+/// Assert on the result of [`std::process::Command::output`].
+///
+/// If the command failed to spawn, the panic reports the error and `PATH`, but not the command
+/// line, which an [`io::Error`] doesn't carry.  Call [`OutputAssertExt::assert`] on the command
+/// itself, or use [`Command::assert`][crate::Command::assert], to have it reported.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+///
+/// use std::process::Command;
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .output()
+///     .assert()
+///     .success();
+/// ```
+impl OutputAssertExt for Result<process::Output, io::Error> {
+    fn assert(self) -> Assert {
+        match self {
+            Ok(output) => output.assert(),
+            Err(err) => AssertError::spawn(SpawnError::new(err)).panic(),
+        }
+    }
+}
+
+/// Suite-wide rules checked against `stderr` by every [`OutputAssertExt::assert`].
+///
+/// Each line of `stderr` containing a `deny` pattern fails the assertion unless the line also
+/// contains an `allow` pattern.  This is checked in addition to any per-test predicates.
+///
+/// As tests may run in any order, [`StderrPolicy::install`] from each test (or a shared helper)
+/// that relies on it.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+/// use assert_cmd::assert::StderrPolicy;
+///
+/// use std::process::Command;
+///
+/// StderrPolicy::new()
+///     .deny("panicked at")
+///     .deny("warning:")
+///     .allow("warning: `old-flag` is deprecated")
+///     .install();
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .assert()
+///     .success();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StderrPolicy {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+static STDERR_POLICY: std::sync::Mutex<Option<StderrPolicy>> = std::sync::Mutex::new(None);
+
+impl StderrPolicy {
+    /// Create an empty policy, allowing everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exempt lines containing `pattern` from the `deny` patterns.
+    pub fn allow(mut self, pattern: impl Into<String>) -> Self {
+        self.allow.push(pattern.into());
+        self
+    }
+
+    /// Fail on lines containing `pattern`.
+    pub fn deny(mut self, pattern: impl Into<String>) -> Self {
+        self.deny.push(pattern.into());
+        self
+    }
+
+    /// Apply this policy to all subsequent assertions in this process, replacing any existing
+    /// policy.
+    pub fn install(self) {
+        *STDERR_POLICY
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(self);
+    }
+
+    /// Remove the installed policy, if any.
+    pub fn uninstall() {
+        *STDERR_POLICY
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+
+    fn installed() -> Option<Self> {
+        STDERR_POLICY
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Find the first denied line, returning its 1-based line number, the line, and the pattern
+    /// that matched.
+    fn check(&self, stderr: &[u8]) -> Option<(usize, bstr::BString, String)> {
+        stderr.lines().enumerate().find_map(|(index, line)| {
+            let denied = self.deny.iter().find(|p| line.contains_str(p))?;
+            if self.allow.iter().any(|p| line.contains_str(p)) {
+                return None;
+            }
+            Some((index + 1, line.into(), denied.clone()))
+        })
+    }
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Report failures in a way that holds up under many tests running in parallel.
+///
+/// When enabled:
+/// - Informational notices, like [`Command::locale`][crate::Command::locale] skipping, are not
+///   printed.
+/// - A failure's full report is printed, without styling, to `stderr` in a single write, and the
+///   panic message is only its first line, so reports from concurrent tests can't interleave.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+/// use assert_cmd::assert::set_quiet;
+///
+/// use std::process::Command;
+///
+/// set_quiet(true);
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .assert()
+///     .success();
+/// ```
+pub fn set_quiet(yes: bool) {
+    QUIET.store(yes, Ordering::Relaxed);
+}
+
+/// Whether [`set_quiet`] is enabled.
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Environment variable for [`Strict`] mode, one of `stdout`, `stderr`, or `all`, used when
+/// [`set_strict`] wasn't called.
+pub const STRICT_ENV: &str = "ASSERT_CMD_STRICT";
+
+/// Which output streams must be asserted on when they aren't empty, see [`set_strict`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Strict {
+    /// Output may go unasserted.
+    #[default]
+    Off,
+    /// Non-empty `stdout` must be asserted on.
+    Stdout,
+    /// Non-empty `stderr` must be asserted on.
+    Stderr,
+    /// Both streams must be asserted on when not empty.
+    All,
+}
+
+impl Strict {
+    fn from_env() -> Self {
+        match env::var(STRICT_ENV).as_deref() {
+            Ok("stdout") => Self::Stdout,
+            Ok("stderr") => Self::Stderr,
+            Ok("all" | "1") => Self::All,
+            _ => Self::Off,
+        }
+    }
+
+    fn covers(self, stream: Stream) -> bool {
+        matches!(
+            (self, stream),
+            (Self::All, _) | (Self::Stdout, Stream::Stdout) | (Self::Stderr, Stream::Stderr)
+        )
+    }
+}
+
+static STRICT: std::sync::Mutex<Strict> = std::sync::Mutex::new(Strict::Off);
+
+/// Fail each [`Assert`] that is dropped without asserting on a non-empty output stream.
+///
+/// This catches warnings creeping into the `stderr` of runs that are only checked for success.
+/// Any assertion on a stream counts, as does reading the output with [`Assert::get_output`].
+///
+/// In debug builds, any mode other than [`Strict::Off`] also fails an [`Assert`] dropped without
+/// any assertion at all, which otherwise only prints a warning.
+///
+/// [`Strict::Off`] falls back to [`STRICT_ENV`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+/// use assert_cmd::assert::{set_strict, Strict};
+///
+/// use std::process::Command;
+///
+/// set_strict(Strict::Stderr);
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("stderr", "warning: deprecated")
+///     .assert()
+///     .success()
+///     .stderr("warning: deprecated\n");
+/// ```
+pub fn set_strict(strict: Strict) {
+    *STRICT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = strict;
+}
+
+/// Which output streams must be asserted on, see [`set_strict`].
+pub fn strictness() -> Strict {
+    match *STRICT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+    {
+        Strict::Off => Strict::from_env(),
+        strict => strict,
+    }
+}
+
+type ContextProvider = Arc<dyn Fn() -> Vec<(&'static str, String)> + Send + Sync>;
+
+static CONTEXT_PROVIDERS: std::sync::Mutex<Vec<ContextProvider>> =
+    std::sync::Mutex::new(Vec::new());
+
+/// Add context from `provider` to every assertion failure in this process.
+///
+/// `provider` is called when an assertion fails, so it can report state at the time of failure.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+/// use assert_cmd::assert::add_context_provider;
+///
+/// use std::process::Command;
+///
+/// add_context_provider(|| {
+///     let sha = std::env::var("GITHUB_SHA").unwrap_or_default();
+///     vec![("git", sha)]
+/// });
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .assert()
+///     .success();
+/// ```
+pub fn add_context_provider<F>(provider: F)
+where
+    F: Fn() -> Vec<(&'static str, String)> + Send + Sync + 'static,
+{
+    CONTEXT_PROVIDERS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .push(Arc::new(provider));
+}
+
+/// Remove all providers added with [`add_context_provider`].
+pub fn clear_context_providers() {
+    CONTEXT_PROVIDERS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clear();
+}
+
+fn provided_context() -> Vec<(&'static str, String)> {
+    // Call providers without the lock, in case they run assertions themselves
+    let providers = CONTEXT_PROVIDERS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone();
+    providers.iter().flat_map(|provider| provider()).collect()
+}
+
+/// Failure to launch the command-under-test.
+#[derive(Debug)]
+pub(crate) struct SpawnError {
+    cmd: Option<String>,
+    path: Option<ffi::OsString>,
+    cause: io::Error,
+}
+
+impl SpawnError {
+    pub(crate) fn new(cause: io::Error) -> Self {
+        Self {
+            cmd: None,
+            path: env::var_os("PATH"),
+            cause,
+        }
+    }
+
+    /// Add the command line and the `PATH` it will be searched in.
+    ///
+    /// `cleared` is whether `cmd` starts from an empty environment.
+    pub(crate) fn set_cmd(mut self, cmd: &process::Command, cleared: bool) -> Self {
+        self.cmd = Some(crate::output::render_command(cmd, cleared));
+        if let Some((_, path)) = cmd.get_envs().filter(|(k, _)| *k == "PATH").last() {
+            self.path = path.map(ToOwned::to_owned);
+        }
+        self
+    }
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let palette = crate::Palette::color();
+        writeln!(f, "Failed to spawn command")?;
+        if let Some(cmd) = &self.cmd {
+            writeln!(f, "{:#}=`{:#}`", palette.key("command"), palette.value(cmd))?;
+        }
+        let path = self
+            .path
+            .as_deref()
+            .map(|p| p.to_string_lossy())
+            .unwrap_or_else(|| "<unset>".into());
+        writeln!(f, "{:#}=`{:#}`", palette.key("PATH"), palette.value(path))?;
+        writeln!(
+            f,
+            "{:#}={:#}",
+            palette.key("cause"),
+            palette.value(&self.cause)
+        )
+    }
+}
+
+/// Assert the state of an [`Output`].
+///
+/// Create an `Assert` through the [`OutputAssertExt`] trait.
+///
+/// When created within a test, failures include the test's name, so failures from shared helper
+/// functions identify their caller.
+///
+/// In debug builds, dropping an `Assert` without making any assertion on it prints a warning,
+/// catching a bare `cmd.assert();` that verifies nothing.  It panics instead when
+/// [strict mode][set_strict] is on.  Reading the output, like with [`Assert::get_output`], counts
+/// as an assertion.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+///
+/// use std::process::Command;
+///
+/// let mut cmd = Command::cargo_bin("bin_fixture")
+///     .unwrap();
+/// cmd.assert()
+///     .success();
+/// ```
+///
+/// [`Output`]: std::process::Output
+pub struct Assert {
+    output: Arc<process::Output>,
+    context: Vec<(&'static str, Box<dyn fmt::Display + Send + Sync>)>,
+    deadline: Option<Deadline>,
+    process: Option<ProcessInfo>,
+    attachments: Vec<Attachment>,
+    fds: Option<Vec<i32>>,
+    current_dir: Option<std::path::PathBuf>,
+    name: Option<String>,
+    stdout_checked: AtomicBool,
+    stderr_checked: AtomicBool,
+    asserted: AtomicBool,
+    expected_failure: Option<ExpectedFailure>,
+}
+
+/// Why an [`Assert`] is expected to fail, and whether it has.
+#[derive(Debug)]
+struct ExpectedFailure {
+    reason: String,
+    failed: bool,
+}
+
+impl Assert {
+    /// Create an `Assert` for a given [`Output`].
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn new(output: process::Output) -> Self {
+        Self::from_shared(Arc::new(output))
+    }
+
+    /// Create an `Assert` for an [`Output`] shared with other values.
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn from_shared(output: Arc<process::Output>) -> Self {
+        let mut context: Vec<(&'static str, Box<dyn fmt::Display + Send + Sync>)> = vec![];
+        // The test harness names each test's thread after the test
+        if let Some(name) = std::thread::current().name() {
+            if name != "main" {
+                context.push(("test", Box::new(name.to_owned())));
+            }
+        }
+        Self {
+            output,
+            context,
+            deadline: None,
+            process: None,
+            attachments: Vec::new(),
+            fds: None,
+            current_dir: None,
+            name: None,
+            stdout_checked: AtomicBool::new(false),
+            stderr_checked: AtomicBool::new(false),
+            asserted: AtomicBool::new(false),
+            expected_failure: None,
+        }
+    }
+
+    /// Expect assertions to fail, see [`Command::expected_failure`][crate::Command::expected_failure].
+    pub(crate) fn expect_failure(mut self, reason: String) -> Self {
+        self.expected_failure = Some(ExpectedFailure {
+            reason,
+            failed: false,
+        });
+        self
+    }
+
+    /// Record that an assertion was made, so dropping `self` doesn't fail.
+    fn mark_asserted(&self) {
+        self.asserted.store(true, Ordering::Relaxed);
+    }
+
+    /// The exit status, recording that an assertion was made.
+    fn checked_status(&self) -> process::ExitStatus {
+        self.mark_asserted();
+        self.output.status
+    }
+
+    /// Details of the process, recording that an assertion was made.
+    fn checked_process(&self) -> Option<ProcessInfo> {
+        self.mark_asserted();
+        self.process
+    }
+
+    /// `stdout`, recording that it was asserted on for [`Strict`] mode.
+    fn checked_stdout(&self) -> &[u8] {
+        self.mark_asserted();
+        self.stdout_checked.store(true, Ordering::Relaxed);
+        &self.output.stdout
+    }
+
+    /// `stderr`, recording that it was asserted on for [`Strict`] mode.
+    fn checked_stderr(&self) -> &[u8] {
+        self.mark_asserted();
+        self.stderr_checked.store(true, Ordering::Relaxed);
+        &self.output.stderr
+    }
+
+    /// Record that both streams were dealt with, so [`Strict`] mode doesn't fail on them.
+    fn check_all(&self) {
+        self.mark_asserted();
+        self.stdout_checked.store(true, Ordering::Relaxed);
+        self.stderr_checked.store(true, Ordering::Relaxed);
+    }
+
+    /// Fail with `detail` about `stream`, for output kept outside `self`, like by
+    /// [`FileAssert`][crate::files::FileAssert].
+    pub(crate) fn try_external(self, stream: Stream, detail: Option<String>) -> AssertResult {
+        match stream {
+            Stream::Stdout => self.checked_stdout(),
+            Stream::Stderr => self.checked_stderr(),
+        };
+        match detail {
+            Some(detail) => Err(self.into_error(AssertReason::ExternalStream { stream, detail })),
+            None => Ok(self),
+        }
+    }
+
+    /// Record that the command was killed for exceeding `deadline`.
+    pub(crate) fn set_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Record that the command was stopped at the end of a sampling `window`, rather than for
+    /// timing out.
+    pub(crate) fn set_sampled(mut self, window: std::time::Duration) -> Self {
+        if self.deadline != Some(Deadline::Run(window)) {
+            return self;
+        }
+        self.deadline = None;
+        self.append_context("sampled", format!("stopped after {window:?}"))
+    }
+
+    /// Record which process produced the output.
+    pub(crate) fn set_process(mut self, process: ProcessInfo) -> Self {
+        self.process = Some(process);
+        self
+    }
+
+    /// Record the directory the command ran in.
+    pub(crate) fn set_current_dir(mut self, dir: &std::path::Path) -> Self {
+        self.current_dir = Some(dir.to_owned());
+        self
+    }
+
+    /// Record which file descriptors the command could see.
+    #[cfg(unix)]
+    pub(crate) fn set_fds(mut self, fds: Vec<i32>) -> Self {
+        let list = fds
+            .iter()
+            .map(|fd| fd.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.fds = Some(fds);
+        self.append_context("fds", list)
+    }
+
+    /// Fail, reporting `first`, unless this run was judged the `same` as it.
+    pub(crate) fn try_same_as(self, first: &Self, same: bool) -> AssertResult {
+        if same {
+            return Ok(self);
+        }
+        let first = &first.output;
+        let code = first
+            .status
+            .code()
+            .map(|code| code.to_string())
+            .unwrap_or_else(|| "<interrupted>".to_owned());
+        Err(self
+            .append_context("first code", code)
+            .append_context("first stdout", DebugBytes::new(&first.stdout).to_string())
+            .append_context("first stderr", DebugBytes::new(&first.stderr).to_string())
+            .into_error(AssertReason::NotIdempotent))
+    }
+
+    fn into_error(mut self, reason: AssertReason) -> AssertError {
+        self.check_all();
+        if let Some(expected) = &mut self.expected_failure {
+            expected.failed = true;
+        }
+        for (name, context) in provided_context() {
+            self = self.append_context(name, context);
+        }
+        AssertError {
+            assert: Some(self),
+            reason,
+        }
+    }
+
+    /// Clarify failures with additional context.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .append_context("main", "no args")
+    ///     .success();
+    /// ```
+    pub fn append_context<D>(mut self, name: &'static str, context: D) -> Self
+    where
+        D: fmt::Display + Send + Sync + 'static,
+    {
+        self.context.push((name, Box::new(context)));
+        self
+    }
+
+    /// Name this run, to tell which of several runs in a test failed.
+    ///
+    /// Failures start with the name, like `[second run] Unexpected failure.`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// for run in ["first run", "second run"] {
+    ///     Command::cargo_bin("bin_fixture")
+    ///         .unwrap()
+    ///         .assert()
+    ///         .named(run)
+    ///         .success();
+    /// }
+    /// ```
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Access the contained [`Output`].
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn get_output(&self) -> &process::Output {
+        self.check_all();
+        &self.output
+    }
+
+    /// Share the contained [`Output`] without copying it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::output::OutputError;
+    ///
+    /// use std::process::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert();
+    /// let error = OutputError::from_shared(assert.shared_output());
+    /// ```
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn shared_output(&self) -> Arc<process::Output> {
+        self.check_all();
+        Arc::clone(&self.output)
+    }
+
+    /// The process ID the command ran as.
+    ///
+    /// This is only known when run by [`Command`][crate::Command].  The process has exited, so
+    /// the ID may since have been reused.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .success();
+    /// println!("ran as {}", assert.pid().unwrap());
+    /// ```
+    pub fn pid(&self) -> Option<u32> {
+        self.checked_process().map(|p| p.pid)
+    }
+
+    /// When the command was spawned.
+    ///
+    /// This is only known when run by [`Command`][crate::Command].
+    pub fn started_at(&self) -> Option<std::time::SystemTime> {
+        self.checked_process().map(|p| p.started)
+    }
+
+    /// When the command finished and its output was collected.
+    ///
+    /// This is only known when run by [`Command`][crate::Command].
+    pub fn finished_at(&self) -> Option<std::time::SystemTime> {
+        self.checked_process().map(|p| p.finished)
+    }
+
+    /// How long spawning the command took, like loading it or being scanned by antivirus.
+    ///
+    /// This is only known when run by [`Command`][crate::Command].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .success();
+    /// println!(
+    ///     "spawn {:?}, run {:?}, teardown {:?}",
+    ///     assert.spawn_latency().unwrap(),
+    ///     assert.execution_time().unwrap(),
+    ///     assert.teardown_time().unwrap(),
+    /// );
+    /// ```
+    pub fn spawn_latency(&self) -> Option<std::time::Duration> {
+        self.checked_process().map(|p| p.spawn_latency)
+    }
+
+    /// How long the command ran, from being spawned until it exited.
+    ///
+    /// This is only known when run by [`Command`][crate::Command].
+    pub fn execution_time(&self) -> Option<std::time::Duration> {
+        self.checked_process().map(|p| p.execution_time)
+    }
+
+    /// How long collecting the command's output took after it exited.
+    ///
+    /// This is only known when run by [`Command`][crate::Command].
+    pub fn teardown_time(&self) -> Option<std::time::Duration> {
+        self.checked_process().map(|p| p.teardown_time)
+    }
+
+    /// The directory the command ran in, if it was set with
+    /// [`Command::current_dir`][crate::Command::current_dir].
+    pub fn current_dir(&self) -> Option<&std::path::Path> {
+        self.current_dir.as_deref()
+    }
+
+    /// `path` within the directory the command ran in, to assert on files it wrote.
+    ///
+    /// Relative paths are resolved against [`Assert::current_dir`], falling back to the test's
+    /// own working directory.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use assert_fs::prelude::*;
+    ///
+    /// let (mut cmd, temp) = Command::cargo_bin("my-tool")
+    ///     .unwrap()
+    ///     .with_temp_dir()
+    ///     .unwrap();
+    /// let assert = cmd.arg("init").assert().success();
+    /// assert.child_path("config.toml").assert(predicates::path::exists());
+    /// temp.close().unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn child_path(&self, path: impl AsRef<std::path::Path>) -> assert_fs::fixture::ChildPath {
+        self.mark_asserted();
+        assert_fs::fixture::ChildPath::new(self.resolve(path.as_ref()))
+    }
+
+    /// Resolve `path` against the directory the command ran in.
+    fn resolve(&self, path: &std::path::Path) -> std::path::PathBuf {
+        let dir = match &self.current_dir {
+            Some(dir) if dir.is_absolute() => dir.clone(),
+            Some(dir) => env::current_dir().unwrap_or_default().join(dir),
+            None => env::current_dir().unwrap_or_default(),
+        };
+        dir.join(path)
+    }
+
+    /// Ensure the command left no file at `path`, like a temporary file it should clean up.
+    ///
+    /// Relative paths are resolved against [`Assert::current_dir`], falling back to the test's
+    /// own working directory.  See [`Command::send_signal_after`][crate::Command::send_signal_after]
+    /// for checking cleanup on interruption.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("my-tool")
+    ///     .unwrap()
+    ///     .args(["convert", "in.txt"])
+    ///     .assert()
+    ///     .success()
+    ///     .file_absent("in.txt.tmp");
+    /// ```
+    #[track_caller]
+    pub fn file_absent(self, path: impl AsRef<std::path::Path>) -> Self {
+        self.try_file_absent(path)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::file_absent`] that returns an [`AssertResult`].
+    pub fn try_file_absent(self, path: impl AsRef<std::path::Path>) -> AssertResult {
+        self.mark_asserted();
+        let path = self.resolve(path.as_ref());
+        // A dangling symlink is still left behind
+        if path.symlink_metadata().is_ok() {
+            return Err(self.into_error(AssertReason::LeftoverFile { path }));
+        }
+        Ok(self)
+    }
+
+    /// The file descriptors the command could see, in ascending order.
+    ///
+    /// This is only known when run by [`Command`][crate::Command] with
+    /// [`Command::report_fds`][crate::Command::report_fds].
+    pub fn visible_fds(&self) -> Option<&[i32]> {
+        self.mark_asserted();
+        self.fds.as_deref()
+    }
+
+    /// Ensure the command could only see the file descriptors in `allowed`.
+    ///
+    /// This requires running with [`Command::report_fds`][crate::Command::report_fds].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .close_fds(true)
+    ///     .report_fds(true)
+    ///     .assert()
+    ///     .only_fds(&[0, 1, 2]);
+    /// ```
+    #[track_caller]
+    pub fn only_fds(self, allowed: &[i32]) -> Self {
+        self.try_only_fds(allowed)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::only_fds`] that returns an [`AssertResult`].
+    pub fn try_only_fds(self, allowed: &[i32]) -> AssertResult {
+        self.mark_asserted();
+        let Some(fds) = &self.fds else {
+            return Err(self.into_error(AssertReason::UnreportedFds));
+        };
+        let unexpected: Vec<_> = fds
+            .iter()
+            .copied()
+            .filter(|fd| !allowed.contains(fd))
+            .collect();
+        if unexpected.is_empty() {
+            Ok(self)
+        } else {
+            Err(self.into_error(AssertReason::UnexpectedFds { unexpected }))
+        }
+    }
+
+    /// Snapshot a file the command wrote, like a log file, to include it in failure output.
+    ///
+    /// The file is read now, so later writes by a still-running process are not seen.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("my-daemon")
+    ///     .unwrap()
+    ///     .args(["--log", "target/daemon.log"])
+    ///     .assert()
+    ///     .attach_file("target/daemon.log")
+    ///     .success();
+    /// ```
+    pub fn attach_file(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        let path = path.into();
+        let contents = std::fs::read(&path).map_err(|err| err.to_string());
+        self.attachments.retain(|a| a.path != path);
+        self.attachments.push(Attachment { path, contents });
+        self
+    }
+
+    /// Ensure an [attached][Assert::attach_file] file matches the predicate.
+    ///
+    /// The file is attached first, if it wasn't already.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("my-daemon")
+    ///     .unwrap()
+    ///     .args(["--log", "target/daemon.log"])
+    ///     .assert()
+    ///     .attached_file("target/daemon.log", predicate::str::contains("listening"));
+    /// ```
+    #[track_caller]
+    pub fn attached_file<I, P>(self, path: impl Into<std::path::PathBuf>, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_attached_file(path, pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::attached_file`] that returns an [`AssertResult`].
+    pub fn try_attached_file<I, P>(
+        mut self,
+        path: impl Into<std::path::PathBuf>,
+        pred: I,
+    ) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.mark_asserted();
+        let path = path.into();
+        if !self.attachments.iter().any(|a| a.path == path) {
+            self = self.attach_file(path.clone());
+        }
+        let attachment = self
+            .attachments
+            .iter()
+            .find(|a| a.path == path)
+            .expect("attached above");
+        let reason = match &attachment.contents {
+            Ok(contents) => pred.into_output().find_case(false, contents).map(|case| {
+                AssertReason::UnexpectedFile {
+                    path,
+                    case_tree: CaseTree(case.tree()),
+                }
+            }),
+            Err(cause) => Some(AssertReason::UnreadableFile {
+                path,
+                cause: cause.clone(),
+            }),
+        };
+        match reason {
+            Some(reason) => Err(self.into_error(reason)),
+            None => Ok(self),
+        }
+    }
+
+    /// Extract a value from `stdout` while keeping the assertion chain.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// let (assert, port) = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "listening on 8080")
+    ///     .assert()
+    ///     .map_stdout(|stdout| {
+    ///         String::from_utf8_lossy(stdout)
+    ///             .trim()
+    ///             .rsplit(' ')
+    ///             .next()
+    ///             .and_then(|port| port.parse::<u16>().ok())
+    ///     });
+    /// assert.success();
+    /// assert_eq!(port, Some(8080));
+    /// ```
+    pub fn map_stdout<T, F>(self, f: F) -> (Self, T)
+    where
+        F: FnOnce(&[u8]) -> T,
+    {
+        let value = f(self.checked_stdout());
+        (self, value)
+    }
+
+    /// Extract a value from `stderr` while keeping the assertion chain.
+    ///
+    /// See [`Assert::map_stdout`].
+    pub fn map_stderr<T, F>(self, f: F) -> (Self, T)
+    where
+        F: FnOnce(&[u8]) -> T,
+    {
+        let value = f(self.checked_stderr());
+        (self, value)
+    }
+
+    /// Rewrite paths in the captured `stdout` and `stderr` so a single expectation can pass on
+    /// every platform.
+    ///
+    /// This affects all later assertions and [`Assert::get_output`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::normalize::NormalizePaths;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", r"src\main.rs")
+    ///     .assert()
+    ///     .normalize_paths(NormalizePaths::new())
+    ///     .stdout("src/main.rs\n");
+    /// ```
+    pub fn normalize_paths(mut self, normalize: crate::normalize::NormalizePaths) -> Self {
+        let output = Arc::make_mut(&mut self.output);
+        output.stdout = normalize.apply(&output.stdout);
+        output.stderr = normalize.apply(&output.stderr);
+        self
+    }
+
+    /// Strip byte order marks and unify newlines in the captured `stdout` and `stderr`.
+    ///
+    /// This affects all later assertions and [`Assert::get_output`].  What was changed is noted
+    /// in failure output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::normalize::NormalizeText;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::new("windows-tool")
+    ///     .assert()
+    ///     .normalize_text(NormalizeText::new())
+    ///     .stdout("line 1\nline 2\n");
+    /// ```
+    pub fn normalize_text(mut self, normalize: crate::normalize::NormalizeText) -> Self {
+        let output = Arc::make_mut(&mut self.output);
+        let (stdout, stdout_changes) = normalize.apply(&output.stdout);
+        let (stderr, stderr_changes) = normalize.apply(&output.stderr);
+        output.stdout = stdout;
+        output.stderr = stderr;
+        let notes: Vec<_> = [("stdout", stdout_changes), ("stderr", stderr_changes)]
+            .into_iter()
+            .filter(|(_, changes)| !changes.is_empty())
+            .map(|(stream, changes)| format!("{stream} ({})", changes.join(", ")))
+            .collect();
+        if notes.is_empty() {
+            self
+        } else {
+            self.append_context("normalized", notes.join(", "))
+        }
+    }
+
+    /// Replace the captured `stdout` with what `decoder` makes of it, so predicates see the
+    /// decoded form.
+    ///
+    /// For commands that write encoded payloads, like gzip, base64, or a binary format with a text
+    /// rendering.  This affects all later assertions and [`Assert::get_output`], and is noted in
+    /// failure output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use predicates::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "68656c6c6f")
+    ///     .assert()
+    ///     .decode_stdout(|hex| {
+    ///         let hex = std::str::from_utf8(hex).map_err(|e| e.to_string())?.trim();
+    ///         (0..hex.len())
+    ///             .step_by(2)
+    ///             .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| e.to_string()))
+    ///             .collect::<Result<Vec<u8>, _>>()
+    ///     })
+    ///     .stdout(predicate::str::diff("hello"));
+    /// ```
+    #[track_caller]
+    pub fn decode_stdout<F, E>(self, decoder: F) -> Self
+    where
+        F: FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+        E: fmt::Display,
+    {
+        self.try_decode_stdout(decoder)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::decode_stdout`] that returns an [`AssertResult`].
+    pub fn try_decode_stdout<F, E>(self, decoder: F) -> AssertResult
+    where
+        F: FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+        E: fmt::Display,
+    {
+        self.try_decode(Stream::Stdout, decoder)
+    }
+
+    /// Replace the captured `stderr` with what `decoder` makes of it, so predicates see the
+    /// decoded form.
+    ///
+    /// See [`Assert::decode_stdout`].
+    #[track_caller]
+    pub fn decode_stderr<F, E>(self, decoder: F) -> Self
+    where
+        F: FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+        E: fmt::Display,
+    {
+        self.try_decode_stderr(decoder)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::decode_stderr`] that returns an [`AssertResult`].
+    pub fn try_decode_stderr<F, E>(self, decoder: F) -> AssertResult
+    where
+        F: FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+        E: fmt::Display,
+    {
+        self.try_decode(Stream::Stderr, decoder)
+    }
+
+    fn try_decode<F, E>(mut self, stream: Stream, decoder: F) -> AssertResult
+    where
+        F: FnOnce(&[u8]) -> Result<Vec<u8>, E>,
+        E: fmt::Display,
+    {
+        let output = Arc::make_mut(&mut self.output);
+        let captured = match stream {
+            Stream::Stdout => &mut output.stdout,
+            Stream::Stderr => &mut output.stderr,
+        };
+        match decoder(captured) {
+            Ok(decoded) => {
+                let note = format!("{stream} ({} to {} bytes)", captured.len(), decoded.len());
+                *captured = decoded;
+                Ok(self.append_context("decoded", note))
+            }
             Err(err) => {
-                panic!("Failed to spawn {:?}: {}", self, err);
+                let cause = err.to_string();
+                Err(self.into_error(AssertReason::Undecodable { stream, cause }))
+            }
+        }
+    }
+
+    /// Check `stderr` against the installed [`StderrPolicy`], if any.
+    pub(crate) fn try_stderr_policy(self) -> AssertResult {
+        let violation = StderrPolicy::installed().and_then(|p| p.check(&self.output.stderr));
+        if let Some((line_number, line, pattern)) = violation {
+            return Err(self.into_error(AssertReason::StderrPolicy {
+                line_number,
+                line,
+                pattern,
+            }));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the command succeeded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .success();
+    /// ```
+    #[track_caller]
+    pub fn success(self) -> Self {
+        self.try_success().unwrap_or_else(AssertError::settle)
+    }
+
+    /// `try_` variant of [`Assert::success`].
+    pub fn try_success(self) -> AssertResult {
+        if let Some(deadline) = self.deadline {
+            return Err(self.into_error(AssertReason::Timeout { deadline }));
+        }
+        if !self.checked_status().success() {
+            let actual_code = self.checked_status().code();
+            return Err(self.into_error(AssertReason::UnexpectedFailure { actual_code }));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the command failed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "1")
+    ///     .assert()
+    ///     .failure();
+    /// ```
+    #[track_caller]
+    pub fn failure(self) -> Self {
+        self.try_failure().unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::failure`] that returns an [`AssertResult`].
+    pub fn try_failure(self) -> AssertResult {
+        if self.checked_status().success() {
+            return Err(self.into_error(AssertReason::UnexpectedSuccess));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the command aborted before returning a code.
+    #[track_caller]
+    pub fn interrupted(self) -> Self {
+        self.try_interrupted().unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::interrupted`] that returns an [`AssertResult`].
+    pub fn try_interrupted(self) -> AssertResult {
+        if self.checked_status().code().is_some() {
+            return Err(self.into_error(AssertReason::UnexpectedCompletion));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the command returned the expected code.
+    ///
+    /// This uses [`IntoCodePredicate`] to provide short-hands for common cases.
+    ///
+    /// See [`predicates`] for more predicates.
+    ///
+    /// # Examples
+    ///
+    /// Accepting a predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "42")
+    ///     .assert()
+    ///     .code(predicate::eq(42));
+    /// ```
+    ///
+    /// Accepting an exit code:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "42")
+    ///     .assert()
+    ///     .code(42);
+    /// ```
+    ///
+    /// Accepting multiple exit codes:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("exit", "42")
+    ///     .assert()
+    ///     .code(&[2, 42] as &[i32]);
+    /// ```
+    ///
+    #[track_caller]
+    pub fn code<I, P>(self, pred: I) -> Self
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.try_code(pred).unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::code`] that returns an [`AssertResult`].
+    pub fn try_code<I, P>(self, pred: I) -> AssertResult
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.code_impl(&pred.into_code())
+    }
+
+    fn code_impl(self, pred: &dyn predicates_core::Predicate<i32>) -> AssertResult {
+        if let Some(deadline) = self.deadline {
+            return Err(self.into_error(AssertReason::Timeout { deadline }));
+        }
+        let actual_code = if let Some(actual_code) = self.checked_status().code() {
+            actual_code
+        } else {
+            return Err(self.into_error(AssertReason::CommandInterrupted));
+        };
+        if let Some(case) = pred.find_case(false, &actual_code) {
+            return Err(self.into_error(AssertReason::UnexpectedReturnCode {
+                case_tree: CaseTree(case.tree()),
+            }));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the command exited with the first of `expected` whose platform is current.
+    ///
+    /// This is for tools that legitimately exit differently per platform.  Fails if no platform
+    /// in `expected` is current; end with [`Platform::any`] for a fallback.
+    ///
+    /// [`Platform::any`]: crate::platform::Platform::any
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::platform::Platform;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .code_per_platform(&[(Platform::family("windows"), 3), (Platform::any(), 0)]);
+    /// ```
+    #[track_caller]
+    pub fn code_per_platform(self, expected: &[(crate::platform::Platform, i32)]) -> Self {
+        self.try_code_per_platform(expected)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::code_per_platform`] that returns an [`AssertResult`].
+    pub fn try_code_per_platform(
+        self,
+        expected: &[(crate::platform::Platform, i32)],
+    ) -> AssertResult {
+        match expected.iter().find(|(platform, _)| platform.is_current()) {
+            Some((platform, expected)) => self
+                .append_context("platform", platform.to_string())
+                .try_code(*expected),
+            None => {
+                let platform = crate::platform::Platform::current();
+                Err(self.into_error(AssertReason::NoCodeForPlatform { platform }))
+            }
+        }
+    }
+
+    /// Ensure the command wrote the expected data to `stdout`.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// See [`predicates`] for more predicates.
+    ///
+    /// # Examples
+    ///
+    /// Accepting a bytes predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout(predicate::eq(b"hello\n" as &[u8]));
+    /// ```
+    ///
+    /// Accepting a `str` predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout(predicate::str::diff("hello\n"));
+    /// ```
+    ///
+    /// Accepting bytes:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout(b"hello\n" as &[u8]);
+    /// ```
+    ///
+    /// Accepting a `str`:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stdout("hello\n");
+    /// ```
+    ///
+    #[track_caller]
+    pub fn stdout<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stdout(pred).unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout`] that returns an [`AssertResult`].
+    pub fn try_stdout<I, P>(self, pred: I) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.stdout_impl(&pred.into_output())
+    }
+
+    fn stdout_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> AssertResult {
+        {
+            let actual = self.checked_stdout();
+            if let Some(case) = pred.find_case(false, actual) {
+                return Err(self.into_error(AssertReason::UnexpectedStdout {
+                    case_tree: CaseTree(case.tree()),
+                }));
             }
+        }
+        Ok(self)
+    }
+
+    /// Ensure `stdout`, with ANSI escape sequences stripped, matches the predicate.
+    ///
+    /// This checks the text independently of its styling, while [`Assert::stdout`] keeps checking
+    /// the raw output.  Failures show both the plain and the raw output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "\x1b[1;31merror\x1b[0m: no input")
+    ///     .assert()
+    ///     .stdout_plain(predicate::str::contains("error: no input"))
+    ///     .stdout(predicate::str::contains("\x1b[1;31m"));
+    /// ```
+    #[track_caller]
+    pub fn stdout_plain<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stdout_plain(pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_plain`] that returns an [`AssertResult`].
+    pub fn try_stdout_plain<I, P>(self, pred: I) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.plain_impl(Stream::Stdout, &pred.into_output())
+    }
+
+    fn plain_impl(
+        self,
+        stream: Stream,
+        pred: &dyn predicates_core::Predicate<[u8]>,
+    ) -> AssertResult {
+        let actual = match stream {
+            Stream::Stdout => self.checked_stdout(),
+            Stream::Stderr => self.checked_stderr(),
         };
-        Assert::new(output).append_context("command", format!("{self:?}"))
+        let plain = crate::output::strip_ansi(actual);
+        if let Some(case) = pred.find_case(false, &plain) {
+            let case_tree = CaseTree(case.tree());
+            return Err(self.into_error(AssertReason::UnexpectedPlain {
+                stream,
+                plain,
+                case_tree,
+            }));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the command wrote the first of `expected` whose platform is current to `stdout`.
+    ///
+    /// Fails if no platform in `expected` is current; end with [`Platform::any`] for a fallback.
+    ///
+    /// [`Platform::any`]: crate::platform::Platform::any
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::platform::Platform;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .assert()
+    ///     .stdout_per_platform(&[
+    ///         (Platform::family("windows"), "hello\r\n"),
+    ///         (Platform::any(), "hello\n"),
+    ///     ]);
+    /// ```
+    #[track_caller]
+    pub fn stdout_per_platform(self, expected: &[(crate::platform::Platform, &str)]) -> Self {
+        self.try_stdout_per_platform(expected)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_per_platform`] that returns an [`AssertResult`].
+    pub fn try_stdout_per_platform(
+        self,
+        expected: &[(crate::platform::Platform, &str)],
+    ) -> AssertResult {
+        match expected.iter().find(|(platform, _)| platform.is_current()) {
+            Some((platform, expected)) => self
+                .append_context("platform", platform.to_string())
+                .try_stdout((*expected).to_owned()),
+            None => {
+                let detail = format!(
+                    "no expectation for this platform ({})",
+                    crate::platform::Platform::current()
+                );
+                Err(self.into_error(AssertReason::UnexpectedStdoutMessage { detail }))
+            }
+        }
+    }
+
+    /// Ensure every line the command wrote to `stdout` matches the predicate.
+    ///
+    /// Lines are split on `\n` with any trailing `\r` removed.  On failure, the first offending
+    /// line is reported with its (1-based) line number.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello\nworld")
+    ///     .assert()
+    ///     .stdout_each_line(predicate::function(|line: &str| line.len() <= 120));
+    /// ```
+    #[track_caller]
+    pub fn stdout_each_line<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stdout_each_line(pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_each_line`] that returns an [`AssertResult`].
+    pub fn try_stdout_each_line<I, P>(self, pred: I) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.stdout_each_line_impl(&pred.into_output())
+    }
+
+    fn stdout_each_line_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> AssertResult {
+        let failure = self
+            .checked_stdout()
+            .lines()
+            .enumerate()
+            .find_map(|(index, line)| {
+                pred.find_case(false, line)
+                    .map(|case| AssertReason::UnexpectedStdoutLine {
+                        line_number: index + 1,
+                        line: line.into(),
+                        case_tree: CaseTree(case.tree()),
+                    })
+            });
+        if let Some(reason) = failure {
+            return Err(self.into_error(reason));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the section of `stdout` between two marker lines matches the predicate.
+    ///
+    /// The section runs from the line after the first line containing `start` to the line before
+    /// the next line containing `end`, so output outside of it can't fail the assertion.  For a
+    /// section under a heading, ending at a blank line, see [`pred::under_heading`].
+    ///
+    /// See [`pred::between_markers`].
+    ///
+    /// [`pred::under_heading`]: crate::pred::under_heading
+    /// [`pred::between_markers`]: crate::pred::between_markers
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "noise\n== SUMMARY ==\n3 passed\n== END ==\nmore noise")
+    ///     .assert()
+    ///     .stdout_section("== SUMMARY ==", "== END ==", predicate::str::diff("3 passed"));
+    /// ```
+    #[track_caller]
+    pub fn stdout_section<P>(self, start: &str, end: &str, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.try_stdout_section(start, end, pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_section`] that returns an [`AssertResult`].
+    pub fn try_stdout_section<P>(self, start: &str, end: &str, pred: P) -> AssertResult
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.stdout_impl(&crate::pred::between_markers(start, end, pred))
+    }
+
+    /// Ensure the command wrote the expected data to `stderr`.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// See [`predicates`] for more predicates.
+    ///
+    /// # Examples
+    ///
+    /// Accepting a bytes predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr(predicate::eq(b"world\n" as &[u8]));
+    /// ```
+    ///
+    /// Accepting a `str` predicate:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr(predicate::str::diff("world\n"));
+    /// ```
+    ///
+    /// Accepting bytes:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr(b"world\n" as &[u8]);
+    /// ```
+    ///
+    /// Accepting a `str`:
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "hello")
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr("world\n");
+    /// ```
+    ///
+    #[track_caller]
+    pub fn stderr<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stderr(pred).unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr`] that returns an [`AssertResult`].
+    pub fn try_stderr<I, P>(self, pred: I) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.stderr_impl(&pred.into_output())
+    }
+
+    fn stderr_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> AssertResult {
+        {
+            let actual = self.checked_stderr();
+            if let Some(case) = pred.find_case(false, actual) {
+                return Err(self.into_error(AssertReason::UnexpectedStderr {
+                    case_tree: CaseTree(case.tree()),
+                }));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Ensure `stderr`, with ANSI escape sequences stripped, matches the predicate.
+    ///
+    /// See [`Assert::stdout_plain`].
+    #[track_caller]
+    pub fn stderr_plain<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stderr_plain(pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr_plain`] that returns an [`AssertResult`].
+    pub fn try_stderr_plain<I, P>(self, pred: I) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.plain_impl(Stream::Stderr, &pred.into_output())
+    }
+
+    /// Ensure the section of `stderr` between two marker lines matches the predicate.
+    ///
+    /// See [`Assert::stdout_section`].
+    #[track_caller]
+    pub fn stderr_section<P>(self, start: &str, end: &str, pred: P) -> Self
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.try_stderr_section(start, end, pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr_section`] that returns an [`AssertResult`].
+    pub fn try_stderr_section<P>(self, start: &str, end: &str, pred: P) -> AssertResult
+    where
+        P: predicates_core::Predicate<str>,
+    {
+        self.stderr_impl(&crate::pred::between_markers(start, end, pred))
+    }
+
+    /// Ensure the command wrote text with the expected style to `stdout`.
+    ///
+    /// See [`ansi`][crate::ansi] for how styles are matched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::ansi::contains;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "\x1b[1;31merror\x1b[0m: no input")
+    ///     .assert()
+    ///     .stdout_styled(contains("error").in_red().bold());
+    /// ```
+    #[cfg(feature = "ansi")]
+    #[track_caller]
+    pub fn stdout_styled(self, pred: crate::ansi::StyledPredicate) -> Self {
+        self.try_stdout_styled(pred)
+            .unwrap_or_else(AssertError::settle)
     }
-}
 
-/// Assert the state of an [`Output`].
-///
-/// Create an `Assert` through the [`OutputAssertExt`] trait.
-///
-/// # Examples
-///
-/// ```rust,no_run
-/// use assert_cmd::prelude::*;
-///
-/// use std::process::Command;
-///
-/// let mut cmd = Command::cargo_bin("bin_fixture")
-///     .unwrap();
-/// cmd.assert()
-///     .success();
-/// ```
-///
-/// [`Output`]: std::process::Output
-pub struct Assert {
-    output: process::Output,
-    context: Vec<(&'static str, Box<dyn fmt::Display + Send + Sync>)>,
-}
+    /// Variant of [`Assert::stdout_styled`] that returns an [`AssertResult`].
+    #[cfg(feature = "ansi")]
+    pub fn try_stdout_styled(self, pred: crate::ansi::StyledPredicate) -> AssertResult {
+        self.stdout_impl(&pred)
+    }
 
-impl Assert {
-    /// Create an `Assert` for a given [`Output`].
+    /// Ensure the command wrote text with the expected style to `stderr`.
     ///
-    /// [`Output`]: std::process::Output
-    pub fn new(output: process::Output) -> Self {
-        Self {
-            output,
-            context: vec![],
+    /// See [`ansi`][crate::ansi] for how styles are matched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    /// use assert_cmd::ansi::contains;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "\x1b[33mwarning\x1b[0m: deprecated")
+    ///     .assert()
+    ///     .stderr_styled(contains("warning").in_yellow());
+    /// ```
+    #[cfg(feature = "ansi")]
+    #[track_caller]
+    pub fn stderr_styled(self, pred: crate::ansi::StyledPredicate) -> Self {
+        self.try_stderr_styled(pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr_styled`] that returns an [`AssertResult`].
+    #[cfg(feature = "ansi")]
+    pub fn try_stderr_styled(self, pred: crate::ansi::StyledPredicate) -> AssertResult {
+        self.stderr_impl(&pred)
+    }
+
+    /// Ensure `stdout`, decoded from `encoding`, matches the predicate.
+    ///
+    /// For programs that write in a legacy encoding, like Windows-1252, or in UTF-16.  A byte
+    /// order mark overrides `encoding` and malformed sequences are replaced with `U+FFFD`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::new("legacy-tool")
+    ///     .assert()
+    ///     .stdout_decoded(encoding_rs::WINDOWS_1252, "café\n");
+    /// ```
+    #[cfg(feature = "encoding")]
+    #[track_caller]
+    pub fn stdout_decoded<I, P>(self, encoding: &'static encoding_rs::Encoding, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stdout_decoded(encoding, pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_decoded`] that returns an [`AssertResult`].
+    #[cfg(feature = "encoding")]
+    pub fn try_stdout_decoded<I, P>(
+        self,
+        encoding: &'static encoding_rs::Encoding,
+        pred: I,
+    ) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let (actual, _, _) = encoding.decode(self.checked_stdout());
+        if let Some(case) = pred.into_output().find_case(false, actual.as_bytes()) {
+            let case_tree = CaseTree(case.tree());
+            return Err(self
+                .append_context("encoding", encoding.name())
+                .into_error(AssertReason::UnexpectedStdout { case_tree }));
         }
+        Ok(self)
     }
 
-    fn into_error(self, reason: AssertReason) -> AssertError {
-        AssertError {
-            assert: self,
-            reason,
+    /// Ensure `stderr`, decoded from `encoding`, matches the predicate.
+    ///
+    /// See [`Assert::stdout_decoded`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    /// use predicates::prelude::*;
+    ///
+    /// Command::new("legacy-tool")
+    ///     .assert()
+    ///     .stderr_decoded(encoding_rs::SHIFT_JIS, predicate::str::contains("エラー"));
+    /// ```
+    #[cfg(feature = "encoding")]
+    #[track_caller]
+    pub fn stderr_decoded<I, P>(self, encoding: &'static encoding_rs::Encoding, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stderr_decoded(encoding, pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr_decoded`] that returns an [`AssertResult`].
+    #[cfg(feature = "encoding")]
+    pub fn try_stderr_decoded<I, P>(
+        self,
+        encoding: &'static encoding_rs::Encoding,
+        pred: I,
+    ) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let (actual, _, _) = encoding.decode(self.checked_stderr());
+        if let Some(case) = pred.into_output().find_case(false, actual.as_bytes()) {
+            let case_tree = CaseTree(case.tree());
+            return Err(self
+                .append_context("encoding", encoding.name())
+                .into_error(AssertReason::UnexpectedStderr { case_tree }));
         }
+        Ok(self)
     }
 
-    /// Clarify failures with additional context.
+    /// Ensure `stdout`, `stderr`, and the exit code all match, reporting every mismatch at once.
+    ///
+    /// Unlike chaining [`Assert::stdout`], [`Assert::stderr`], and [`Assert::code`], a failure
+    /// shows how each of the three was evaluated.
     ///
     /// # Examples
     ///
@@ -117,29 +2019,89 @@ impl Assert {
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
+    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
+    ///     .env("stderr", "error: no input")
+    ///     .env("exit", "2")
     ///     .assert()
-    ///     .append_context("main", "no args")
-    ///     .success();
+    ///     .outputs(
+    ///         predicate::str::is_empty(),
+    ///         predicate::str::contains("no input"),
+    ///         2,
+    ///     );
     /// ```
-    pub fn append_context<D>(mut self, name: &'static str, context: D) -> Self
+    #[track_caller]
+    pub fn outputs<IO, PO, IE, PE, IC, PC>(self, stdout: IO, stderr: IE, code: IC) -> Self
     where
-        D: fmt::Display + Send + Sync + 'static,
+        IO: IntoOutputPredicate<PO>,
+        PO: predicates_core::Predicate<[u8]>,
+        IE: IntoOutputPredicate<PE>,
+        PE: predicates_core::Predicate<[u8]>,
+        IC: IntoCodePredicate<PC>,
+        PC: predicates_core::Predicate<i32>,
     {
-        self.context.push((name, Box::new(context)));
-        self
+        self.try_outputs(stdout, stderr, code)
+            .unwrap_or_else(AssertError::settle)
     }
 
-    /// Access the contained [`Output`].
-    ///
-    /// [`Output`]: std::process::Output
-    pub fn get_output(&self) -> &process::Output {
-        &self.output
+    /// Variant of [`Assert::outputs`] that returns an [`AssertResult`].
+    pub fn try_outputs<IO, PO, IE, PE, IC, PC>(
+        self,
+        stdout: IO,
+        stderr: IE,
+        code: IC,
+    ) -> AssertResult
+    where
+        IO: IntoOutputPredicate<PO>,
+        PO: predicates_core::Predicate<[u8]>,
+        IE: IntoOutputPredicate<PE>,
+        PE: predicates_core::Predicate<[u8]>,
+        IC: IntoCodePredicate<PC>,
+        PC: predicates_core::Predicate<i32>,
+    {
+        self.outputs_impl(
+            &stdout.into_output(),
+            &stderr.into_output(),
+            &code.into_code(),
+        )
     }
 
-    /// Ensure the command succeeded.
+    fn outputs_impl(
+        self,
+        stdout: &dyn predicates_core::Predicate<[u8]>,
+        stderr: &dyn predicates_core::Predicate<[u8]>,
+        code: &dyn predicates_core::Predicate<i32>,
+    ) -> AssertResult {
+        if let Some(deadline) = self.deadline {
+            return Err(self.into_error(AssertReason::Timeout { deadline }));
+        }
+        let evaluate = |case: Option<predicates_core::reflection::Case<'_>>| match case {
+            Some(case) => Evaluation::Failed(CaseTree(case.tree())),
+            None => Evaluation::Passed,
+        };
+        let evaluations = Evaluations {
+            stdout: evaluate(stdout.find_case(false, self.checked_stdout())),
+            stderr: evaluate(stderr.find_case(false, self.checked_stderr())),
+            code: match self.checked_status().code() {
+                Some(actual_code) => evaluate(code.find_case(false, &actual_code)),
+                None => Evaluation::Interrupted,
+            },
+        };
+        if evaluations.passed() {
+            Ok(self)
+        } else {
+            Err(self.into_error(AssertReason::UnexpectedOutputs {
+                evaluations: Box::new(evaluations),
+            }))
+        }
+    }
+
+    /// Ensure the command wrote nothing to `stdout`.
+    ///
+    /// On failure, what was written is classified (e.g. a panic or only ANSI escape codes) and
+    /// shown trimmed.
     ///
     /// # Examples
     ///
@@ -150,24 +2112,28 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
+    ///     .env("stderr", "world")
     ///     .assert()
-    ///     .success();
+    ///     .stdout_empty();
     /// ```
     #[track_caller]
-    pub fn success(self) -> Self {
-        self.try_success().unwrap_or_else(AssertError::panic)
+    pub fn stdout_empty(self) -> Self {
+        self.try_stdout_empty().unwrap_or_else(AssertError::settle)
     }
 
-    /// `try_` variant of [`Assert::success`].
-    pub fn try_success(self) -> AssertResult {
-        if !self.output.status.success() {
-            let actual_code = self.output.status.code();
-            return Err(self.into_error(AssertReason::UnexpectedFailure { actual_code }));
+    /// Variant of [`Assert::stdout_empty`] that returns an [`AssertResult`].
+    pub fn try_stdout_empty(self) -> AssertResult {
+        if !self.checked_stdout().is_empty() {
+            let noise = Noise::classify(self.checked_stdout());
+            return Err(self.into_error(AssertReason::NonEmptyStdout { noise }));
         }
         Ok(self)
     }
 
-    /// Ensure the command failed.
+    /// Ensure the command wrote nothing to `stderr`.
+    ///
+    /// On failure, what was written is classified (e.g. a panic or a deprecation warning) and
+    /// shown trimmed.
     ///
     /// # Examples
     ///
@@ -178,60 +2144,152 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "1")
+    ///     .env("stdout", "hello")
     ///     .assert()
-    ///     .failure();
+    ///     .stderr_empty();
     /// ```
     #[track_caller]
-    pub fn failure(self) -> Self {
-        self.try_failure().unwrap_or_else(AssertError::panic)
+    pub fn stderr_empty(self) -> Self {
+        self.try_stderr_empty().unwrap_or_else(AssertError::settle)
     }
 
-    /// Variant of [`Assert::failure`] that returns an [`AssertResult`].
-    pub fn try_failure(self) -> AssertResult {
-        if self.output.status.success() {
-            return Err(self.into_error(AssertReason::UnexpectedSuccess));
+    /// Variant of [`Assert::stderr_empty`] that returns an [`AssertResult`].
+    pub fn try_stderr_empty(self) -> AssertResult {
+        if !self.checked_stderr().is_empty() {
+            let noise = Noise::classify(self.checked_stderr());
+            return Err(self.into_error(AssertReason::NonEmptyStderr { noise }));
         }
         Ok(self)
     }
 
-    /// Ensure the command aborted before returning a code.
+    /// Ensure the command wrote the [`dedent`]ed `expected` text to `stdout`.
+    ///
+    /// Trailing newlines are ignored on both sides, so `expected` can be written as an indented
+    /// raw string literal.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "Usage:\n  bin_fixture [OPTIONS]")
+    ///     .assert()
+    ///     .stdout_indoc(
+    ///         r#"
+    ///         Usage:
+    ///           bin_fixture [OPTIONS]
+    ///         "#,
+    ///     );
+    /// ```
     #[track_caller]
-    pub fn interrupted(self) -> Self {
-        self.try_interrupted().unwrap_or_else(AssertError::panic)
+    pub fn stdout_indoc(self, expected: &str) -> Self {
+        self.try_stdout_indoc(expected)
+            .unwrap_or_else(AssertError::settle)
     }
 
-    /// Variant of [`Assert::interrupted`] that returns an [`AssertResult`].
-    pub fn try_interrupted(self) -> AssertResult {
-        if self.output.status.code().is_some() {
-            return Err(self.into_error(AssertReason::UnexpectedCompletion));
+    /// Variant of [`Assert::stdout_indoc`] that returns an [`AssertResult`].
+    pub fn try_stdout_indoc(self, expected: &str) -> AssertResult {
+        let pred = indoc_predicate(expected);
+        let actual = trim_newlines(self.checked_stdout());
+        if let Some(case) = predicates_core::Predicate::find_case(&pred, false, actual) {
+            return Err(self.into_error(AssertReason::UnexpectedStdout {
+                case_tree: CaseTree(case.tree()),
+            }));
         }
         Ok(self)
     }
 
-    /// Ensure the command returned the expected code.
-    ///
-    /// This uses [`IntoCodePredicate`] to provide short-hands for common cases.
+    /// Ensure the command wrote the [`dedent`]ed `expected` text to `stderr`.
     ///
-    /// See [`predicates`] for more predicates.
+    /// Trailing newlines are ignored on both sides, so `expected` can be written as an indented
+    /// raw string literal.
     ///
     /// # Examples
     ///
-    /// Accepting a predicate:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
-    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "42")
+    ///     .env("stderr", "error: oops\n  try again")
     ///     .assert()
-    ///     .code(predicate::eq(42));
+    ///     .stderr_indoc(
+    ///         r#"
+    ///         error: oops
+    ///           try again
+    ///         "#,
+    ///     );
     /// ```
+    #[track_caller]
+    pub fn stderr_indoc(self, expected: &str) -> Self {
+        self.try_stderr_indoc(expected)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr_indoc`] that returns an [`AssertResult`].
+    pub fn try_stderr_indoc(self, expected: &str) -> AssertResult {
+        let pred = indoc_predicate(expected);
+        let actual = trim_newlines(self.checked_stderr());
+        if let Some(case) = predicates_core::Predicate::find_case(&pred, false, actual) {
+            return Err(self.into_error(AssertReason::UnexpectedStderr {
+                case_tree: CaseTree(case.tree()),
+            }));
+        }
+        Ok(self)
+    }
+
+    /// Ensure one of the [framed][crate::jsonrpc] messages the command wrote to `stdout` matches
+    /// `pred`.
+    ///
+    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    ///
+    /// See the [`jsonrpc` module][crate::jsonrpc] for an example.
+    #[track_caller]
+    pub fn stdout_jsonrpc<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.try_stdout_jsonrpc(pred)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_jsonrpc`] that returns an [`AssertResult`].
+    pub fn try_stdout_jsonrpc<I, P>(self, pred: I) -> AssertResult
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        self.stdout_jsonrpc_impl(&pred.into_output())
+    }
+
+    fn stdout_jsonrpc_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> AssertResult {
+        let detail = match crate::jsonrpc::unframe(self.checked_stdout()) {
+            Ok(messages) if messages.iter().any(|m| pred.eval(m)) => None,
+            Ok(messages) => Some(format!(
+                "none of {} messages matched {pred}",
+                messages.len()
+            )),
+            Err(err) => Some(err.to_string()),
+        };
+        match detail {
+            Some(detail) => Err(self.into_error(AssertReason::UnexpectedStdoutMessage { detail })),
+            None => Ok(self),
+        }
+    }
+
+    /// Ensure the command wrote the JSON document `expected` to `stdout`.
+    ///
+    /// Documents are compared by value, so formatting and the order of fields don't matter.
+    ///
+    /// # Examples
     ///
-    /// Accepting an exit code:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
@@ -239,12 +2297,53 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "42")
+    ///     .env("stdout", r#"{"b": [1, 2], "a": null}"#)
     ///     .assert()
-    ///     .code(42);
+    ///     .stdout_json(r#"{"a": null, "b": [1, 2]}"#);
     /// ```
+    #[cfg(feature = "json")]
+    #[track_caller]
+    pub fn stdout_json(self, expected: &str) -> Self {
+        self.try_stdout_json(expected)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_json`] that returns an [`AssertResult`].
+    #[cfg(feature = "json")]
+    pub fn try_stdout_json(self, expected: &str) -> AssertResult {
+        self.try_stdout_json_with(expected, &crate::json::JsonRules::new())
+    }
+
+    /// Ensure the command wrote the JSON document `expected` to `stdout`, by `rules`.
+    ///
+    /// See the [`json` module][crate::json] for an example.
+    #[cfg(feature = "json")]
+    #[track_caller]
+    pub fn stdout_json_with(self, expected: &str, rules: &crate::json::JsonRules) -> Self {
+        self.try_stdout_json_with(expected, rules)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_json_with`] that returns an [`AssertResult`].
+    #[cfg(feature = "json")]
+    pub fn try_stdout_json_with(
+        self,
+        expected: &str,
+        rules: &crate::json::JsonRules,
+    ) -> AssertResult {
+        match rules.mismatch(expected, self.checked_stdout()) {
+            Some(detail) => Err(self.into_error(AssertReason::UnexpectedStdoutMessage { detail })),
+            None => Ok(self),
+        }
+    }
+
+    /// Ensure the command wrote `expected` to `stdout`, ignoring a single trailing newline on
+    /// either side.
+    ///
+    /// `\n` and `\r\n` both count as a newline.
+    ///
+    /// # Examples
     ///
-    /// Accepting multiple exit codes:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
@@ -252,82 +2351,175 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("exit", "42")
+    ///     .env("stdout", "hello")
     ///     .assert()
-    ///     .code(&[2, 42] as &[i32]);
+    ///     .stdout_eq_trimmed("hello");
     /// ```
-    ///
     #[track_caller]
-    pub fn code<I, P>(self, pred: I) -> Self
-    where
-        I: IntoCodePredicate<P>,
-        P: predicates_core::Predicate<i32>,
-    {
-        self.try_code(pred).unwrap_or_else(AssertError::panic)
+    pub fn stdout_eq_trimmed(self, expected: &str) -> Self {
+        self.try_stdout_eq_trimmed(expected)
+            .unwrap_or_else(AssertError::settle)
     }
 
-    /// Variant of [`Assert::code`] that returns an [`AssertResult`].
-    pub fn try_code<I, P>(self, pred: I) -> AssertResult
-    where
-        I: IntoCodePredicate<P>,
-        P: predicates_core::Predicate<i32>,
-    {
-        self.code_impl(&pred.into_code())
+    /// Variant of [`Assert::stdout_eq_trimmed`] that returns an [`AssertResult`].
+    pub fn try_stdout_eq_trimmed(self, expected: &str) -> AssertResult {
+        let pred = trimmed_predicate(expected);
+        let actual = trim_trailing_newline(self.checked_stdout());
+        if let Some(case) = predicates_core::Predicate::find_case(&pred, false, actual) {
+            return Err(self.into_error(AssertReason::UnexpectedStdout {
+                case_tree: CaseTree(case.tree()),
+            }));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the command wrote `expected` to `stderr`, ignoring a single trailing newline on
+    /// either side.
+    ///
+    /// `\n` and `\r\n` both count as a newline.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::prelude::*;
+    ///
+    /// use std::process::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stderr", "world")
+    ///     .assert()
+    ///     .stderr_eq_trimmed("world");
+    /// ```
+    #[track_caller]
+    pub fn stderr_eq_trimmed(self, expected: &str) -> Self {
+        self.try_stderr_eq_trimmed(expected)
+            .unwrap_or_else(AssertError::settle)
     }
 
-    fn code_impl(self, pred: &dyn predicates_core::Predicate<i32>) -> AssertResult {
-        let actual_code = if let Some(actual_code) = self.output.status.code() {
-            actual_code
-        } else {
-            return Err(self.into_error(AssertReason::CommandInterrupted));
-        };
-        if let Some(case) = pred.find_case(false, &actual_code) {
-            return Err(self.into_error(AssertReason::UnexpectedReturnCode {
+    /// Variant of [`Assert::stderr_eq_trimmed`] that returns an [`AssertResult`].
+    pub fn try_stderr_eq_trimmed(self, expected: &str) -> AssertResult {
+        let pred = trimmed_predicate(expected);
+        let actual = trim_trailing_newline(self.checked_stderr());
+        if let Some(case) = predicates_core::Predicate::find_case(&pred, false, actual) {
+            return Err(self.into_error(AssertReason::UnexpectedStderr {
                 case_tree: CaseTree(case.tree()),
             }));
         }
         Ok(self)
     }
 
-    /// Ensure the command wrote the expected data to `stdout`.
+    /// Ensure the command wrote exactly the contents of the file at `path` to `stdout`.
     ///
-    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    /// On failure, the diff is reported and, if a [patch directory][crate::golden::patch_dir] is
+    /// set, written as a `.patch` file that updates `path`.  A missing file is treated as empty,
+    /// with the patch creating it.
     ///
-    /// See [`predicates`] for more predicates.
+    /// When [blessing][crate::golden::is_blessing], `path` is overwritten instead and this passes.
     ///
     /// # Examples
     ///
-    /// Accepting a bytes predicate:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
-    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
     ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
     ///     .assert()
-    ///     .stdout(predicate::eq(b"hello\n" as &[u8]));
+    ///     .stdout_matches_path("tests/snapshots/hello.txt");
     /// ```
+    #[track_caller]
+    pub fn stdout_matches_path(self, path: impl AsRef<std::path::Path>) -> Self {
+        self.try_stdout_matches_path(path)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_matches_path`] that returns an [`AssertResult`].
+    pub fn try_stdout_matches_path(self, path: impl AsRef<std::path::Path>) -> AssertResult {
+        self.try_matches_path(Stream::Stdout, path.as_ref())
+    }
+
+    /// Ensure the command wrote exactly the contents of the file at `path` to `stderr`.
+    ///
+    /// See [`Assert::stdout_matches_path`].
+    ///
+    /// # Examples
     ///
-    /// Accepting a `str` predicate:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
-    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
     ///     .env("stderr", "world")
     ///     .assert()
-    ///     .stdout(predicate::str::diff("hello\n"));
+    ///     .stderr_matches_path("tests/snapshots/world.txt");
     /// ```
+    #[track_caller]
+    pub fn stderr_matches_path(self, path: impl AsRef<std::path::Path>) -> Self {
+        self.try_stderr_matches_path(path)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr_matches_path`] that returns an [`AssertResult`].
+    pub fn try_stderr_matches_path(self, path: impl AsRef<std::path::Path>) -> AssertResult {
+        self.try_matches_path(Stream::Stderr, path.as_ref())
+    }
+
+    fn try_matches_path(self, stream: Stream, path: &std::path::Path) -> AssertResult {
+        let expected = match std::fs::read(path) {
+            Ok(expected) => Some(expected),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(self.into_error(AssertReason::UnreadableFile {
+                    path: path.to_owned(),
+                    cause: err.to_string(),
+                }));
+            }
+        };
+        let actual = match stream {
+            Stream::Stdout => self.checked_stdout(),
+            Stream::Stderr => self.checked_stderr(),
+        };
+        if expected.as_deref() == Some(actual) {
+            return Ok(self);
+        }
+        if crate::golden::is_blessing() {
+            return match crate::golden::bless(path, actual) {
+                Ok(()) => Ok(self),
+                Err(err) => Err(self.into_error(AssertReason::UnwritableFile {
+                    path: path.to_owned(),
+                    cause: err.to_string(),
+                })),
+            };
+        }
+        let diff = crate::golden::display_diff(expected.as_deref().unwrap_or_default(), actual);
+        let patch = crate::golden::write_patch(path, expected.as_deref(), actual)
+            .map_err(|err| err.to_string())
+            .transpose();
+        Err(self.into_error(AssertReason::UnexpectedGolden {
+            stream,
+            path: path.to_owned(),
+            missing: expected.is_none(),
+            diff,
+            patch,
+        }))
+    }
+
+    /// Ensure no more lines of `stdout` contain `pattern` than the count recorded in `baseline`.
+    ///
+    /// This ratchets a count, like of deprecation warnings, towards zero: it may go down but not
+    /// up.  `baseline` holds just the count.  Running with
+    /// [`ASSERT_CMD_BLESS=1`][crate::golden::BLESS_ENV] records the current count when `baseline`
+    /// doesn't exist yet, and lowers it when the count has gone down, which otherwise only prints
+    /// a note.  Blessing never raises an existing baseline, and without it a missing file counts
+    /// as zero.
+    ///
+    /// # Examples
     ///
-    /// Accepting bytes:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
@@ -335,13 +2527,30 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
     ///     .assert()
-    ///     .stdout(b"hello\n" as &[u8]);
+    ///     .stdout_ratchet("TODO", "tests/baselines/todos.txt");
     /// ```
+    #[track_caller]
+    pub fn stdout_ratchet(self, pattern: &str, baseline: impl AsRef<std::path::Path>) -> Self {
+        self.try_stdout_ratchet(pattern, baseline)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_ratchet`] that returns an [`AssertResult`].
+    pub fn try_stdout_ratchet(
+        self,
+        pattern: &str,
+        baseline: impl AsRef<std::path::Path>,
+    ) -> AssertResult {
+        self.try_ratchet(Stream::Stdout, pattern, baseline.as_ref())
+    }
+
+    /// Ensure no more lines of `stderr` contain `pattern` than the count recorded in `baseline`.
+    ///
+    /// See [`Assert::stdout_ratchet`].
+    ///
+    /// # Examples
     ///
-    /// Accepting a `str`:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
@@ -349,95 +2558,211 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
     ///     .assert()
-    ///     .stdout("hello\n");
+    ///     .stderr_ratchet("deprecated", "tests/baselines/deprecations.txt");
     /// ```
-    ///
     #[track_caller]
-    pub fn stdout<I, P>(self, pred: I) -> Self
-    where
-        I: IntoOutputPredicate<P>,
-        P: predicates_core::Predicate<[u8]>,
-    {
-        self.try_stdout(pred).unwrap_or_else(AssertError::panic)
+    pub fn stderr_ratchet(self, pattern: &str, baseline: impl AsRef<std::path::Path>) -> Self {
+        self.try_stderr_ratchet(pattern, baseline)
+            .unwrap_or_else(AssertError::settle)
     }
 
-    /// Variant of [`Assert::stdout`] that returns an [`AssertResult`].
-    pub fn try_stdout<I, P>(self, pred: I) -> AssertResult
-    where
-        I: IntoOutputPredicate<P>,
-        P: predicates_core::Predicate<[u8]>,
-    {
-        self.stdout_impl(&pred.into_output())
+    /// Variant of [`Assert::stderr_ratchet`] that returns an [`AssertResult`].
+    pub fn try_stderr_ratchet(
+        self,
+        pattern: &str,
+        baseline: impl AsRef<std::path::Path>,
+    ) -> AssertResult {
+        self.try_ratchet(Stream::Stderr, pattern, baseline.as_ref())
     }
 
-    fn stdout_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> AssertResult {
-        {
-            let actual = &self.output.stdout;
-            if let Some(case) = pred.find_case(false, actual) {
-                return Err(self.into_error(AssertReason::UnexpectedStdout {
-                    case_tree: CaseTree(case.tree()),
+    fn try_ratchet(self, stream: Stream, pattern: &str, path: &std::path::Path) -> AssertResult {
+        use std::io::Write as _;
+
+        let baseline = match std::fs::read_to_string(path) {
+            Ok(content) => match content.trim().parse::<usize>() {
+                Ok(baseline) => Some(baseline),
+                Err(_) => {
+                    return Err(self.into_error(AssertReason::UnreadableFile {
+                        path: path.to_owned(),
+                        cause: format!("expected a count, found {:?}", content.trim()),
+                    }));
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(self.into_error(AssertReason::UnreadableFile {
+                    path: path.to_owned(),
+                    cause: err.to_string(),
                 }));
             }
+        };
+        let actual = match stream {
+            Stream::Stdout => self.checked_stdout(),
+            Stream::Stderr => self.checked_stderr(),
+        };
+        let count = actual
+            .lines()
+            .filter(|line| line.contains_str(pattern))
+            .count();
+        let blessing = crate::golden::is_blessing();
+        let recorded = baseline.unwrap_or(0);
+        // Blessing may create a baseline, but not raise one
+        if recorded < count && (baseline.is_some() || !blessing) {
+            return Err(self.into_error(AssertReason::RatchetExceeded {
+                stream,
+                pattern: pattern.to_owned(),
+                path: path.to_owned(),
+                baseline: recorded,
+                count,
+            }));
+        }
+        if baseline == Some(count) {
+            return Ok(self);
+        }
+        if blessing {
+            return match crate::golden::bless(path, format!("{count}\n").as_bytes()) {
+                Ok(()) => Ok(self),
+                Err(err) => Err(self.into_error(AssertReason::UnwritableFile {
+                    path: path.to_owned(),
+                    cause: err.to_string(),
+                })),
+            };
+        }
+        if baseline.is_some() && !is_quiet() {
+            let _ = writeln!(
+                io::stderr(),
+                "note: {count} lines of {stream} contain {pattern:?}, down from {recorded} in {}; \
+                 run with {}=1 to lower the baseline",
+                path.display(),
+                crate::golden::BLESS_ENV,
+            );
         }
         Ok(self)
     }
 
-    /// Ensure the command wrote the expected data to `stderr`.
+    /// Ensure the command wrote `expected` to `stdout`, ignoring case.
     ///
-    /// This uses [`IntoOutputPredicate`] to provide short-hands for common cases.
+    /// Case is folded by Unicode rules, so `Straße` and `STRASSE` differ but `Ärger` and
+    /// `ärger` don't.  See [`NormalizeText::unicode`] to also ignore how characters are
+    /// composed.
     ///
-    /// See [`predicates`] for more predicates.
+    /// [`NormalizeText::unicode`]: crate::normalize::NormalizeText
     ///
     /// # Examples
     ///
-    /// Accepting a bytes predicate:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
-    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .env("stdout", "Hello")
     ///     .assert()
-    ///     .stderr(predicate::eq(b"world\n" as &[u8]));
+    ///     .stdout_eq_ignore_case("HELLO\n");
     /// ```
+    #[track_caller]
+    pub fn stdout_eq_ignore_case(self, expected: &str) -> Self {
+        self.try_stdout_eq_ignore_case(expected)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stdout_eq_ignore_case`] that returns an [`AssertResult`].
+    pub fn try_stdout_eq_ignore_case(self, expected: &str) -> AssertResult {
+        match ignore_case_mismatch(expected, self.checked_stdout()) {
+            Some(case_tree) => Err(self
+                .append_context("compared", "ignoring case")
+                .into_error(AssertReason::UnexpectedStdout { case_tree })),
+            None => Ok(self),
+        }
+    }
+
+    /// Ensure the command wrote `expected` to `stderr`, ignoring case.
+    ///
+    /// See [`Assert::stdout_eq_ignore_case`].
+    #[track_caller]
+    pub fn stderr_eq_ignore_case(self, expected: &str) -> Self {
+        self.try_stderr_eq_ignore_case(expected)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::stderr_eq_ignore_case`] that returns an [`AssertResult`].
+    pub fn try_stderr_eq_ignore_case(self, expected: &str) -> AssertResult {
+        match ignore_case_mismatch(expected, self.checked_stderr()) {
+            Some(case_tree) => Err(self
+                .append_context("compared", "ignoring case")
+                .into_error(AssertReason::UnexpectedStderr { case_tree })),
+            None => Ok(self),
+        }
+    }
+
+    /// Ensure the command's output looks like `--help`, by the default [`HelpRules`].
+    ///
+    /// # Examples
     ///
-    /// Accepting a `str` predicate:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
     /// use std::process::Command;
-    /// use predicates::prelude::*;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .arg("--help")
     ///     .assert()
-    ///     .stderr(predicate::str::diff("world\n"));
+    ///     .help_like();
     /// ```
+    #[track_caller]
+    pub fn help_like(self) -> Self {
+        self.try_help_like().unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::help_like`] that returns an [`AssertResult`].
+    pub fn try_help_like(self) -> AssertResult {
+        self.try_help_like_with(&HelpRules::new())
+    }
+
+    /// Ensure the command's output looks like `--help`, by `rules`.
+    ///
+    /// # Examples
     ///
-    /// Accepting bytes:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
+    /// use assert_cmd::assert::HelpRules;
     ///
     /// use std::process::Command;
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .arg("--help")
     ///     .assert()
-    ///     .stderr(b"world\n" as &[u8]);
+    ///     .help_like_with(&HelpRules::new().bin_name("bin_fixture").max_width(100));
     /// ```
+    #[track_caller]
+    pub fn help_like_with(self, rules: &HelpRules) -> Self {
+        self.try_help_like_with(rules)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::help_like_with`] that returns an [`AssertResult`].
+    pub fn try_help_like_with(self, rules: &HelpRules) -> AssertResult {
+        let assert = self.try_success()?;
+        match rules.check(assert.checked_stdout()) {
+            Some(detail) => {
+                Err(assert.into_error(AssertReason::UnexpectedStdoutMessage { detail }))
+            }
+            None => Ok(assert),
+        }
+    }
+
+    /// Ensure the command succeeded and printed the version of the crate under test, from
+    /// `CARGO_PKG_VERSION`, on the first line of `stdout`.
+    ///
+    /// `CARGO_PKG_VERSION` is read when the assertion runs, so this requires running under
+    /// `cargo test`.
+    ///
+    /// # Examples
     ///
-    /// Accepting a `str`:
     /// ```rust,no_run
     /// use assert_cmd::prelude::*;
     ///
@@ -445,53 +2770,443 @@ impl Assert {
     ///
     /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .env("stdout", "hello")
-    ///     .env("stderr", "world")
+    ///     .arg("--version")
     ///     .assert()
-    ///     .stderr("world\n");
+    ///     .version_matches_crate();
     /// ```
+    #[track_caller]
+    pub fn version_matches_crate(self) -> Self {
+        self.try_version_matches_crate()
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::version_matches_crate`] that returns an [`AssertResult`].
+    pub fn try_version_matches_crate(self) -> AssertResult {
+        match env::var("CARGO_PKG_VERSION") {
+            Ok(version) => self.try_version_matches(&version),
+            Err(_) => {
+                let detail = "`CARGO_PKG_VERSION` is not set, run under `cargo test`".to_owned();
+                Err(self.into_error(AssertReason::UnexpectedStdoutMessage { detail }))
+            }
+        }
+    }
+
+    /// Ensure the command succeeded and printed `version` on the first line of `stdout`.
     ///
+    /// The version must be a whole word, optionally prefixed with `v`, like `tool 1.2.3` or
+    /// `tool v1.2.3`.
     #[track_caller]
-    pub fn stderr<I, P>(self, pred: I) -> Self
-    where
-        I: IntoOutputPredicate<P>,
-        P: predicates_core::Predicate<[u8]>,
-    {
-        self.try_stderr(pred).unwrap_or_else(AssertError::panic)
+    pub fn version_matches(self, version: &str) -> Self {
+        self.try_version_matches(version)
+            .unwrap_or_else(AssertError::settle)
     }
 
-    /// Variant of [`Assert::stderr`] that returns an [`AssertResult`].
-    pub fn try_stderr<I, P>(self, pred: I) -> AssertResult
-    where
-        I: IntoOutputPredicate<P>,
-        P: predicates_core::Predicate<[u8]>,
-    {
-        self.stderr_impl(&pred.into_output())
+    /// Variant of [`Assert::version_matches`] that returns an [`AssertResult`].
+    pub fn try_version_matches(self, version: &str) -> AssertResult {
+        let assert = self.try_success()?;
+        let first = assert
+            .output
+            .stdout
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or_default();
+        let matches = first.fields().any(|word| {
+            let word = word.strip_prefix(b"v").unwrap_or(word);
+            word == version.as_bytes()
+        });
+        if matches {
+            return Ok(assert);
+        }
+        let detail = format!(
+            "expected version {version:?} on the first line, found {:?}",
+            first.as_bstr()
+        );
+        Err(assert.into_error(AssertReason::UnexpectedStdoutMessage { detail }))
     }
 
-    fn stderr_impl(self, pred: &dyn predicates_core::Predicate<[u8]>) -> AssertResult {
-        {
-            let actual = &self.output.stderr;
-            if let Some(case) = pred.find_case(false, actual) {
-                return Err(self.into_error(AssertReason::UnexpectedStderr {
-                    case_tree: CaseTree(case.tree()),
-                }));
+    /// Ensure the command succeeded and printed a completion script for `shell`.
+    ///
+    /// The script is parsed by `shell`, without running it, when `shell` is installed.
+    /// Otherwise, only lightweight checks are made and a message is printed.
+    ///
+    /// See the [`completions` module][crate::completions] for an example.
+    #[track_caller]
+    pub fn completion_script(self, shell: crate::completions::Shell) -> Self {
+        self.try_completion_script(shell)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Assert::completion_script`] that returns an [`AssertResult`].
+    pub fn try_completion_script(self, shell: crate::completions::Shell) -> AssertResult {
+        let assert = self.try_success()?;
+        match crate::completions::check(shell, assert.checked_stdout()) {
+            Some(detail) => {
+                Err(assert.into_error(AssertReason::UnexpectedStdoutMessage { detail }))
             }
+            None => Ok(assert),
         }
-        Ok(self)
+    }
+}
+
+/// What [`Assert::help_like_with`] expects of `--help` output.
+///
+/// By default, `stdout` must be non-empty and have a usage line, starting with `Usage:` in any
+/// case, naming the binary.  The binary is assumed to be named after the crate under test, from
+/// `CARGO_PKG_NAME` when the assertion runs, unless set with [`HelpRules::bin_name`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HelpRules {
+    bin_name: Option<String>,
+    usage_prefix: String,
+    max_width: Option<usize>,
+}
+
+impl HelpRules {
+    /// The default rules.
+    pub fn new() -> Self {
+        Self {
+            bin_name: None,
+            usage_prefix: "usage:".to_owned(),
+            max_width: None,
+        }
+    }
+
+    /// Require the usage line to name `bin_name`.
+    pub fn bin_name(mut self, bin_name: impl Into<String>) -> Self {
+        self.bin_name = Some(bin_name.into());
+        self
+    }
+
+    /// Recognize the usage line by `prefix`, in any case, instead of `Usage:`.
+    pub fn usage_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.usage_prefix = prefix.into().to_lowercase();
+        self
+    }
+
+    /// Require every line to fit in `width` columns.
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Describe the first broken rule, if any.
+    fn check(&self, stdout: &[u8]) -> Option<String> {
+        let stdout = crate::output::strip_ansi(stdout);
+        let stdout = stdout.to_str_lossy();
+        if stdout.trim().is_empty() {
+            return Some("expected help but found nothing".to_owned());
+        }
+        let Some(usage) = stdout.lines().find(|line| {
+            line.trim_start()
+                .to_lowercase()
+                .starts_with(&self.usage_prefix)
+        }) else {
+            return Some(format!(
+                "expected help with a line starting with {:?}",
+                self.usage_prefix
+            ));
+        };
+        let bin_name = self
+            .bin_name
+            .clone()
+            .or_else(|| env::var("CARGO_PKG_NAME").ok());
+        if let Some(bin_name) = bin_name {
+            if !usage.contains(&bin_name) {
+                return Some(format!(
+                    "expected the usage line to name {bin_name:?}, found {:?}",
+                    usage.trim()
+                ));
+            }
+        }
+        if let Some(max_width) = self.max_width {
+            if let Some((index, line)) = stdout
+                .lines()
+                .enumerate()
+                .find(|(_, line)| max_width < line.chars().count())
+            {
+                return Some(format!(
+                    "expected lines of at most {max_width} columns, line {} has {}",
+                    index + 1,
+                    line.chars().count()
+                ));
+            }
+        }
+        None
+    }
+}
+
+impl Default for HelpRules {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compare `actual` to `expected` with both lowercased, describing any difference.
+fn ignore_case_mismatch(expected: &str, actual: &[u8]) -> Option<CaseTree> {
+    let pred = StrContentOutputPredicate::from_string(expected.to_lowercase());
+    let actual = actual.to_str_lossy().to_lowercase();
+    predicates_core::Predicate::find_case(&pred, false, actual.as_bytes())
+        .map(|case| CaseTree(case.tree()))
+}
+
+fn indoc_predicate(expected: &str) -> StrContentOutputPredicate {
+    let expected = dedent(expected);
+    StrContentOutputPredicate::from_string(expected.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+fn trim_newlines(data: &[u8]) -> &[u8] {
+    data.trim_end_with(|c| c == '\r' || c == '\n')
+}
+
+fn trimmed_predicate(expected: &str) -> StrContentOutputPredicate {
+    let expected = trim_trailing_newline(expected.as_bytes());
+    StrContentOutputPredicate::from_string(String::from_utf8_lossy(expected).into_owned())
+}
+
+fn trim_trailing_newline(data: &[u8]) -> &[u8] {
+    data.strip_suffix(b"\r\n")
+        .or_else(|| data.strip_suffix(b"\n"))
+        .unwrap_or(data)
+}
+
+/// Remove the common leading indentation from `text`.
+///
+/// This makes multi-line expected output readable in Rust source:
+/// - A leading newline (right after the opening quote) is dropped
+/// - A trailing line of only whitespace (before the closing quote) is dropped, keeping its newline
+/// - Whitespace-only lines don't count towards the common indentation
+///
+/// # Examples
+///
+/// ```rust
+/// use assert_cmd::assert::dedent;
+///
+/// let expected = dedent(
+///     r#"
+///     Usage:
+///       bin_fixture [OPTIONS]
+///     "#,
+/// );
+/// assert_eq!(expected, "Usage:\n  bin_fixture [OPTIONS]\n");
+/// ```
+pub fn dedent(text: &str) -> String {
+    let text = text.strip_prefix('\n').unwrap_or(text);
+    let text = match text.rfind('\n') {
+        Some(last) if text[last + 1..].trim().is_empty() => &text[..=last],
+        _ => text,
+    };
+
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        // Only ASCII indentation, so slicing it off can't split a character
+        .map(|line| line.len() - line.trim_start_matches([' ', '\t']).len())
+        .min()
+        .unwrap_or(0);
+
+    let mut dedented = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            dedented.push_str(line.trim_start_matches([' ', '\t']));
+        } else {
+            dedented.push_str(&line[indent..]);
+        }
+    }
+    dedented
+}
+
+/// What a command printed when nothing was expected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Noise {
+    Panic,
+    Deprecation,
+    Ansi,
+    Whitespace,
+    Text,
+}
+
+impl Noise {
+    fn classify(data: &[u8]) -> Self {
+        let stripped = crate::output::strip_ansi(data);
+        if data.contains_str("panicked at") {
+            Self::Panic
+        } else if data.to_lowercase().contains_str("deprecat") {
+            Self::Deprecation
+        } else if stripped.trim().is_empty() && stripped.len() != data.len() {
+            Self::Ansi
+        } else if stripped.trim().is_empty() {
+            Self::Whitespace
+        } else {
+            Self::Text
+        }
+    }
+}
+
+impl fmt::Display for Noise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::Panic => "what looks like a panic",
+            Self::Deprecation => "what looks like a deprecation warning",
+            Self::Ansi => "only ANSI escape codes",
+            Self::Whitespace => "only whitespace",
+            Self::Text => "text",
+        };
+        f.write_str(description)
+    }
+}
+
+impl Drop for Assert {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        let nothing_asserted = cfg!(debug_assertions) && !self.asserted.load(Ordering::Relaxed);
+        if nothing_asserted && strictness() == Strict::Off {
+            if !is_quiet() {
+                let command = self
+                    .context
+                    .iter()
+                    .find(|(name, _)| *name == "command")
+                    .map(|(_, value)| value.to_string())
+                    .unwrap_or_default();
+                // `eprintln!` is captured by the test harness, unlike writes to `io::stderr()`
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!(
+                        "warning: nothing was asserted on a run of {command}, \
+                         add an assertion like `.success()`"
+                    );
+                }
+            }
+            return;
+        }
+        let reason = if nothing_asserted {
+            AssertReason::NothingAsserted
+        } else if let Some(expected) = self.expected_failure.take().filter(|e| !e.failed) {
+            AssertReason::UnexpectedPass {
+                reason: expected.reason,
+            }
+        } else {
+            match self.unasserted() {
+                Some(streams) => AssertReason::Unasserted { streams },
+                None => return,
+            }
+        };
+        // Hand the report what was gathered for this run, leaving `self` to drop quietly
+        let assert = Self {
+            output: Arc::clone(&self.output),
+            context: std::mem::take(&mut self.context),
+            deadline: self.deadline.take(),
+            process: self.process.take(),
+            attachments: std::mem::take(&mut self.attachments),
+            fds: self.fds.take(),
+            current_dir: self.current_dir.take(),
+            name: self.name.take(),
+            stdout_checked: AtomicBool::new(true),
+            stderr_checked: AtomicBool::new(true),
+            asserted: AtomicBool::new(true),
+            expected_failure: None,
+        };
+        assert.into_error(reason).panic::<()>();
+    }
+}
+
+impl Assert {
+    /// Non-empty streams that [`Strict`] mode requires an assertion on, but had none.
+    fn unasserted(&self) -> Option<Vec<Stream>> {
+        let strict = strictness();
+        let unasserted: Vec<_> = [
+            (Stream::Stdout, &self.stdout_checked, &self.output.stdout),
+            (Stream::Stderr, &self.stderr_checked, &self.output.stderr),
+        ]
+        .into_iter()
+        .filter(|(stream, checked, content)| {
+            strict.covers(*stream) && !checked.load(Ordering::Relaxed) && !content.is_empty()
+        })
+        .map(|(stream, _, _)| stream)
+        .collect();
+        (!unasserted.is_empty()).then_some(unasserted)
     }
 }
 
 impl fmt::Display for Assert {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::redact::write(f, Report(self))
+    }
+}
+
+/// [`Assert`]'s report, before redaction.
+struct Report<'a>(&'a Assert);
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_report(f)
+    }
+}
+
+impl Assert {
+    fn write_report(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let palette = crate::Palette::color();
         for (name, context) in &self.context {
             writeln!(f, "{:#}=`{:#}`", palette.key(name), palette.value(context))?;
         }
+        for attachment in &self.attachments {
+            let path = attachment.path.display();
+            match &attachment.contents {
+                Ok(contents) => writeln!(
+                    f,
+                    "{:#}={:#}",
+                    palette.key(path),
+                    palette.value(DebugBytes::new(contents))
+                )?,
+                Err(cause) => writeln!(
+                    f,
+                    "{:#}=`{:#}`",
+                    palette.key(path),
+                    palette.value(format_args!("<unreadable: {cause}>"))
+                )?,
+            }
+        }
+        if let Some(process) = self.process {
+            use crate::output::human_duration;
+            use crate::output::timestamp;
+
+            writeln!(
+                f,
+                "{:#}=`{:#}`",
+                palette.key("started"),
+                palette.value(timestamp(process.started))
+            )?;
+            writeln!(
+                f,
+                "{:#}=`{:#}`",
+                palette.key("finished"),
+                palette.value(timestamp(process.finished))
+            )?;
+            let total = process.spawn_latency + process.execution_time + process.teardown_time;
+            writeln!(
+                f,
+                "{:#}=`{:#}`",
+                palette.key("duration"),
+                palette.value(format_args!(
+                    "{} (spawn {}, run {}, teardown {})",
+                    human_duration(total),
+                    human_duration(process.spawn_latency),
+                    human_duration(process.execution_time),
+                    human_duration(process.teardown_time)
+                ))
+            )?;
+        }
         output_fmt(&self.output, f)
     }
 }
 
+/// A file snapshotted by [`Assert::attach_file`].
+#[derive(Clone, Debug)]
+struct Attachment {
+    path: std::path::PathBuf,
+    contents: Result<Vec<u8>, String>,
+}
+
 impl fmt::Debug for Assert {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Assert")
@@ -692,6 +3407,17 @@ impl IntoCodePredicate<InCodePredicate> for &'static [i32] {
     }
 }
 
+/// Match the code of another run's [`ExitStatus`][process::ExitStatus].
+///
+/// A status without a code, like from a signal, matches nothing.
+impl IntoCodePredicate<InCodePredicate> for process::ExitStatus {
+    type Predicate = InCodePredicate;
+
+    fn into_code(self) -> Self::Predicate {
+        Self::Predicate::new(self.code())
+    }
+}
+
 /// Used by [`Assert::stdout`] and [`Assert::stderr`] to convert Self
 /// into the needed [`predicates_core::Predicate<[u8]>`].
 ///
@@ -784,7 +3510,14 @@ impl predicates_core::Predicate<[u8]> for BytesContentOutputPredicate {
     ) -> Option<predicates_core::reflection::Case<'_>> {
         let actual = self.eval(variable);
         if expected == actual {
-            Some(predicates_core::reflection::Case::new(Some(self), actual))
+            Some(
+                predicates_core::reflection::Case::new(Some(self), actual).add_product(
+                    predicates_core::reflection::Product::new(
+                        "var",
+                        DebugBytes::new(variable).to_string(),
+                    ),
+                ),
+            )
         } else {
             None
         }
@@ -793,7 +3526,7 @@ impl predicates_core::Predicate<[u8]> for BytesContentOutputPredicate {
 
 impl fmt::Display for BytesContentOutputPredicate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        predicates::ord::eq(self.0.as_ref()).fmt(f)
+        write!(f, "var == {}", DebugBytes::new(&self.0))
     }
 }
 
@@ -805,6 +3538,14 @@ impl IntoOutputPredicate<BytesContentOutputPredicate> for Vec<u8> {
     }
 }
 
+impl IntoOutputPredicate<BytesContentOutputPredicate> for Cow<'static, [u8]> {
+    type Predicate = BytesContentOutputPredicate;
+
+    fn into_output(self) -> Self::Predicate {
+        BytesContentOutputPredicate(self)
+    }
+}
+
 impl IntoOutputPredicate<BytesContentOutputPredicate> for &'static [u8] {
     type Predicate = BytesContentOutputPredicate;
 
@@ -835,17 +3576,18 @@ impl IntoOutputPredicate<BytesContentOutputPredicate> for &'static [u8] {
 #[derive(Debug, Clone)]
 pub struct StrContentOutputPredicate(
     predicates::str::Utf8Predicate<predicates::str::DifferencePredicate>,
+    Cow<'static, str>,
 );
 
 impl StrContentOutputPredicate {
     pub(crate) fn from_str(value: &'static str) -> Self {
         let pred = predicates::str::diff(value).from_utf8();
-        StrContentOutputPredicate(pred)
+        StrContentOutputPredicate(pred, Cow::from(value))
     }
 
     pub(crate) fn from_string(value: String) -> Self {
-        let pred = predicates::str::diff(value).from_utf8();
-        StrContentOutputPredicate(pred)
+        let pred = predicates::str::diff(value.clone()).from_utf8();
+        StrContentOutputPredicate(pred, Cow::from(value))
     }
 }
 
@@ -874,7 +3616,24 @@ impl predicates_core::Predicate<[u8]> for StrContentOutputPredicate {
         expected: bool,
         variable: &[u8],
     ) -> Option<predicates_core::reflection::Case<'a>> {
-        self.0.find_case(expected, variable)
+        let case = self.0.find_case(expected, variable)?;
+        if expected {
+            return Some(case);
+        }
+        // "The strings look identical" failures are easier to debug with whitespace made visible
+        match str::from_utf8(variable) {
+            Ok(actual) if crate::output::differs_only_in_whitespace(&self.1, actual) => Some(
+                case.add_product(predicates_core::reflection::Product::new(
+                    "expected (visible whitespace)",
+                    crate::output::visible_whitespace(&self.1),
+                ))
+                .add_product(predicates_core::reflection::Product::new(
+                    "actual (visible whitespace)",
+                    crate::output::visible_whitespace(actual),
+                )),
+            ),
+            _ => Some(case),
+        }
     }
 }
 
@@ -900,6 +3659,83 @@ impl IntoOutputPredicate<StrContentOutputPredicate> for &'static str {
     }
 }
 
+impl IntoOutputPredicate<StrContentOutputPredicate> for Cow<'static, str> {
+    type Predicate = StrContentOutputPredicate;
+
+    fn into_output(self) -> Self::Predicate {
+        match self {
+            Cow::Borrowed(value) => Self::Predicate::from_str(value),
+            Cow::Owned(value) => Self::Predicate::from_string(value),
+        }
+    }
+}
+
+/// [`predicates_core::Predicate`] used by [`IntoOutputPredicate`] for a [`regex::Regex`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+///
+/// use std::process::Command;
+///
+/// let version = regex::Regex::new(r"^\d+\.\d+\.\d+$").unwrap();
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("stdout", "1.2.3")
+///     .assert()
+///     .stdout(version);
+/// ```
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub struct RegexOutputPredicate(regex::Regex);
+
+#[cfg(feature = "regex")]
+impl predicates_core::reflection::PredicateReflection for RegexOutputPredicate {}
+
+#[cfg(feature = "regex")]
+impl predicates_core::Predicate<[u8]> for RegexOutputPredicate {
+    fn eval(&self, item: &[u8]) -> bool {
+        str::from_utf8(item).is_ok_and(|item| self.0.is_match(item))
+    }
+
+    fn find_case(
+        &self,
+        expected: bool,
+        variable: &[u8],
+    ) -> Option<predicates_core::reflection::Case<'_>> {
+        let actual = self.eval(variable);
+        if expected == actual {
+            Some(
+                predicates_core::reflection::Case::new(Some(self), actual).add_product(
+                    predicates_core::reflection::Product::new(
+                        "var",
+                        DebugBytes::new(variable).to_string(),
+                    ),
+                ),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl fmt::Display for RegexOutputPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var.is_match({})", self.0)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl IntoOutputPredicate<RegexOutputPredicate> for regex::Regex {
+    type Predicate = RegexOutputPredicate;
+
+    fn into_output(self) -> Self::Predicate {
+        RegexOutputPredicate(self)
+    }
+}
+
 // Keep `predicates` concrete Predicates out of our public API.
 /// [`predicates_core::Predicate`] used by [`IntoOutputPredicate`] for
 /// [`Predicate<str>`][predicates_core::Predicate].
@@ -1012,30 +3848,393 @@ pub type AssertResult = Result<Assert, AssertError>;
 /// [`Assert`] error (see [`AssertResult`]).
 #[derive(Debug)]
 pub struct AssertError {
-    assert: Assert,
+    assert: Option<Assert>,
     reason: AssertReason,
 }
 
+/// The category of an [`AssertError`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::prelude::*;
+/// use assert_cmd::assert::AssertErrorKind;
+///
+/// use std::process::Command;
+///
+/// let err = Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .env("exit", "42")
+///     .assert()
+///     .try_success()
+///     .unwrap_err();
+/// assert_eq!(err.kind(), AssertErrorKind::WrongStatus);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AssertErrorKind {
+    /// The command succeeded or failed when the opposite was expected.
+    WrongStatus,
+    /// The command's exit code did not match, or it had none.
+    WrongCode,
+    /// `stdout` did not satisfy the predicate.
+    StdoutMismatch,
+    /// `stderr` did not satisfy the predicate.
+    StderrMismatch,
+    /// An [attached][Assert::attach_file] file did not satisfy the predicate or couldn't be read,
+    /// or an expectation file couldn't be read or updated.
+    FileMismatch,
+    /// The command could see unexpected file descriptors, see [`Assert::only_fds`].
+    FdMismatch,
+    /// The command could not be launched, or the [files][crate::Command::assert_to_files] to
+    /// capture its output to couldn't be created.
+    Spawn,
+    /// The command was killed after exceeding its timeout, or the [budget][crate::budget] was
+    /// used up.
+    Timeout,
+    /// Running the command again changed its output, see
+    /// [`Command::assert_idempotent`][crate::Command::assert_idempotent].
+    NotIdempotent,
+    /// An [`Assert`] was dropped, in a debug build with [`Strict`] mode on, without any assertion
+    /// being made on it.
+    NothingAsserted,
+    /// Every assertion passed on a command with an
+    /// [expected failure][crate::Command::expected_failure].
+    UnexpectedPass,
+}
+
+/// Which output stream an assertion was made on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdout => f.write_str("stdout"),
+            Self::Stderr => f.write_str("stderr"),
+        }
+    }
+}
+
 #[derive(Debug)]
 enum AssertReason {
-    UnexpectedFailure { actual_code: Option<i32> },
+    UnexpectedFailure {
+        actual_code: Option<i32>,
+    },
     UnexpectedSuccess,
     UnexpectedCompletion,
     CommandInterrupted,
-    UnexpectedReturnCode { case_tree: CaseTree },
-    UnexpectedStdout { case_tree: CaseTree },
-    UnexpectedStderr { case_tree: CaseTree },
+    UnexpectedReturnCode {
+        case_tree: CaseTree,
+    },
+    NoCodeForPlatform {
+        platform: String,
+    },
+    UnexpectedStdout {
+        case_tree: CaseTree,
+    },
+    UnexpectedStdoutLine {
+        line_number: usize,
+        line: bstr::BString,
+        case_tree: CaseTree,
+    },
+    UnexpectedStderr {
+        case_tree: CaseTree,
+    },
+    UnexpectedPlain {
+        stream: Stream,
+        plain: Vec<u8>,
+        case_tree: CaseTree,
+    },
+    UnexpectedStdoutMessage {
+        detail: String,
+    },
+    ExternalStream {
+        stream: Stream,
+        detail: String,
+    },
+    UnexpectedOutputs {
+        evaluations: Box<Evaluations>,
+    },
+    UnexpectedFile {
+        path: std::path::PathBuf,
+        case_tree: CaseTree,
+    },
+    UnreadableFile {
+        path: std::path::PathBuf,
+        cause: String,
+    },
+    UnwritableFile {
+        path: std::path::PathBuf,
+        cause: String,
+    },
+    /// Where the command's output would have been written, before it ran.
+    UncreatableFile {
+        path: std::path::PathBuf,
+        cause: String,
+    },
+    LeftoverFile {
+        path: std::path::PathBuf,
+    },
+    Undecodable {
+        stream: Stream,
+        cause: String,
+    },
+    UnexpectedGolden {
+        stream: Stream,
+        path: std::path::PathBuf,
+        missing: bool,
+        diff: String,
+        patch: Option<Result<std::path::PathBuf, String>>,
+    },
+    RatchetExceeded {
+        stream: Stream,
+        pattern: String,
+        path: std::path::PathBuf,
+        baseline: usize,
+        count: usize,
+    },
+    UnexpectedFds {
+        unexpected: Vec<i32>,
+    },
+    UnreportedFds,
+    NonEmptyStdout {
+        noise: Noise,
+    },
+    NonEmptyStderr {
+        noise: Noise,
+    },
+    StderrPolicy {
+        line_number: usize,
+        line: bstr::BString,
+        pattern: String,
+    },
+    Spawn {
+        cause: SpawnError,
+    },
+    Timeout {
+        deadline: Deadline,
+    },
+    NotIdempotent,
+    Unasserted {
+        streams: Vec<Stream>,
+    },
+    NothingAsserted,
+    UnexpectedPass {
+        reason: String,
+    },
 }
 
 impl AssertError {
+    pub(crate) fn spawn(cause: SpawnError) -> Self {
+        Self {
+            assert: None,
+            reason: AssertReason::Spawn { cause },
+        }
+    }
+
+    /// `path`, for the command's output, couldn't be created, so the command didn't run.
+    pub(crate) fn uncreatable(path: std::path::PathBuf, err: &io::Error) -> Self {
+        Self {
+            assert: None,
+            reason: AssertReason::UncreatableFile {
+                path,
+                cause: err.to_string(),
+            },
+        }
+    }
+
+    pub(crate) fn deadline(deadline: Deadline) -> Self {
+        Self {
+            assert: None,
+            reason: AssertReason::Timeout { deadline },
+        }
+    }
+
+    /// See [`Assert::append_context`]; ignored if the command failed to spawn.
+    pub(crate) fn append_context<D>(mut self, name: &'static str, context: D) -> Self
+    where
+        D: fmt::Display + Send + Sync + 'static,
+    {
+        self.assert = self.assert.map(|a| a.append_context(name, context));
+        self
+    }
+
+    #[track_caller]
+    pub(crate) fn panic<T>(self) -> T {
+        if is_quiet() {
+            // Printed plain as `stderr` may be captured by the test harness, bypassing `anstream`
+            let report = self.to_string();
+            let report = crate::output::strip_ansi(report.as_bytes());
+            let report = report.to_str_lossy();
+            // A single `eprint!` holds the `stderr` lock for the whole report
+            #[allow(clippy::print_stderr)]
+            {
+                eprint!("{report}");
+            }
+            let summary = report.lines().next().unwrap_or_default();
+            panic!("{}", summary)
+        } else {
+            panic!("{}", self)
+        }
+    }
+
+    /// Panic, unless the failure was [expected][crate::Command::expected_failure], in which case
+    /// it is noted and the chain continues.
     #[track_caller]
-    fn panic<T>(self) -> T {
-        panic!("{}", self)
+    pub(crate) fn settle(mut self) -> Assert {
+        let expected = self
+            .assert
+            .as_ref()
+            .is_some_and(|assert| assert.expected_failure.is_some());
+        if !expected {
+            return self.panic();
+        }
+        let report = self.to_string();
+        let assert = self.assert.take().expect("checked above");
+        if let (Some(expected), false) = (&assert.expected_failure, is_quiet()) {
+            let summary = crate::output::strip_ansi(report.as_bytes());
+            let summary = summary.to_str_lossy();
+            let summary = summary.lines().next().unwrap_or_default();
+            // `eprintln!` is captured by the test harness, unlike writes to `io::stderr()`
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!("expected failure ({}): {summary}", expected.reason);
+            }
+        }
+        assert
+    }
+
+    /// The category of failure, for harnesses that need to branch on it.
+    pub fn kind(&self) -> AssertErrorKind {
+        match &self.reason {
+            AssertReason::UnexpectedFailure { .. }
+            | AssertReason::UnexpectedSuccess
+            | AssertReason::UnexpectedCompletion => AssertErrorKind::WrongStatus,
+            AssertReason::CommandInterrupted
+            | AssertReason::UnexpectedReturnCode { .. }
+            | AssertReason::NoCodeForPlatform { .. } => AssertErrorKind::WrongCode,
+            AssertReason::UnexpectedStdout { .. }
+            | AssertReason::UnexpectedStdoutLine { .. }
+            | AssertReason::UnexpectedStdoutMessage { .. }
+            | AssertReason::NonEmptyStdout { .. } => AssertErrorKind::StdoutMismatch,
+            AssertReason::UnexpectedStderr { .. }
+            | AssertReason::NonEmptyStderr { .. }
+            | AssertReason::StderrPolicy { .. } => AssertErrorKind::StderrMismatch,
+            AssertReason::UnexpectedOutputs { evaluations } => evaluations.kind(),
+            AssertReason::UnexpectedFile { .. }
+            | AssertReason::UnreadableFile { .. }
+            | AssertReason::UnwritableFile { .. }
+            | AssertReason::LeftoverFile { .. } => AssertErrorKind::FileMismatch,
+            AssertReason::UnexpectedGolden {
+                stream: Stream::Stdout,
+                ..
+            }
+            | AssertReason::Undecodable {
+                stream: Stream::Stdout,
+                ..
+            }
+            | AssertReason::UnexpectedPlain {
+                stream: Stream::Stdout,
+                ..
+            }
+            | AssertReason::RatchetExceeded {
+                stream: Stream::Stdout,
+                ..
+            }
+            | AssertReason::ExternalStream {
+                stream: Stream::Stdout,
+                ..
+            } => AssertErrorKind::StdoutMismatch,
+            AssertReason::UnexpectedGolden {
+                stream: Stream::Stderr,
+                ..
+            }
+            | AssertReason::Undecodable {
+                stream: Stream::Stderr,
+                ..
+            }
+            | AssertReason::UnexpectedPlain {
+                stream: Stream::Stderr,
+                ..
+            }
+            | AssertReason::RatchetExceeded {
+                stream: Stream::Stderr,
+                ..
+            }
+            | AssertReason::ExternalStream {
+                stream: Stream::Stderr,
+                ..
+            } => AssertErrorKind::StderrMismatch,
+            AssertReason::UnexpectedFds { .. } | AssertReason::UnreportedFds => {
+                AssertErrorKind::FdMismatch
+            }
+            AssertReason::Spawn { .. } | AssertReason::UncreatableFile { .. } => {
+                AssertErrorKind::Spawn
+            }
+            AssertReason::Timeout { .. } => AssertErrorKind::Timeout,
+            AssertReason::NotIdempotent => AssertErrorKind::NotIdempotent,
+            AssertReason::NothingAsserted => AssertErrorKind::NothingAsserted,
+            AssertReason::UnexpectedPass { .. } => AssertErrorKind::UnexpectedPass,
+            AssertReason::Unasserted { streams } => {
+                if streams.contains(&Stream::Stderr) {
+                    AssertErrorKind::StderrMismatch
+                } else {
+                    AssertErrorKind::StdoutMismatch
+                }
+            }
+        }
+    }
+
+    /// Access the [`Output`] that failed the assertion.
+    ///
+    /// Returns [`None`] if the command failed to spawn.
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn output(&self) -> Option<&process::Output> {
+        self.assert.as_ref().map(Assert::get_output)
+    }
+
+    /// Share the [`Output`] that failed the assertion without copying it.
+    ///
+    /// Returns [`None`] if the command failed to spawn.
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn shared_output(&self) -> Option<Arc<process::Output>> {
+        self.assert.as_ref().map(Assert::shared_output)
+    }
+
+    /// Describe the predicate that failed, if any.
+    pub fn predicate(&self) -> Option<String> {
+        match &self.reason {
+            AssertReason::UnexpectedReturnCode { case_tree }
+            | AssertReason::UnexpectedStdout { case_tree }
+            | AssertReason::UnexpectedStdoutLine { case_tree, .. }
+            | AssertReason::UnexpectedStderr { case_tree }
+            | AssertReason::UnexpectedPlain { case_tree, .. }
+            | AssertReason::UnexpectedFile { case_tree, .. } => Some(case_tree.to_string()),
+            AssertReason::UnexpectedOutputs { evaluations } => {
+                evaluations
+                    .iter()
+                    .find_map(|(_, evaluation)| match evaluation {
+                        Evaluation::Failed(case_tree) => Some(case_tree.to_string()),
+                        _ => None,
+                    })
+            }
+            _ => None,
+        }
     }
 
     /// Returns the [`Assert`] wrapped into the [`Result`] produced by
     /// the `try_` variants of the [`Assert`] methods.
     ///
+    /// # Panics
+    ///
+    /// If the command failed to spawn, see [`AssertErrorKind::Spawn`].
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -1056,8 +4255,12 @@ impl AssertError {
     ///         }
     ///     }
     /// ```
+    #[track_caller]
     pub fn assert(self) -> Assert {
-        self.assert
+        match self.assert {
+            Some(assert) => assert,
+            None => panic!("{}", self),
+        }
     }
 }
 
@@ -1065,6 +4268,24 @@ impl Error for AssertError {}
 
 impl fmt::Display for AssertError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::redact::write(f, ErrorReport(self))
+    }
+}
+
+/// [`AssertError`]'s report, before redaction.
+struct ErrorReport<'a>(&'a AssertError);
+
+impl fmt::Display for ErrorReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_report(f)
+    }
+}
+
+impl AssertError {
+    fn write_report(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = self.assert.as_ref().and_then(|a| a.name.as_deref()) {
+            write!(f, "[{name}] ")?;
+        }
         match &self.reason {
             AssertReason::UnexpectedFailure { actual_code } => writeln!(
                 f,
@@ -1072,7 +4293,11 @@ impl fmt::Display for AssertError {
                 actual_code
                     .map(|actual_code| actual_code.to_string())
                     .unwrap_or_else(|| "<interrupted>".to_owned()),
-                DebugBytes::new(&self.assert.output.stderr),
+                DebugBytes::new(
+                    self.output()
+                        .map(|o| o.stderr.as_slice())
+                        .unwrap_or_default()
+                ),
             ),
             AssertReason::UnexpectedSuccess => {
                 writeln!(f, "Unexpected success")
@@ -1086,14 +4311,210 @@ impl fmt::Display for AssertError {
             AssertReason::UnexpectedReturnCode { case_tree } => {
                 writeln!(f, "Unexpected return code, failed {case_tree}")
             }
+            AssertReason::NoCodeForPlatform { platform } => {
+                writeln!(
+                    f,
+                    "Unexpected return code, no expectation for this platform ({platform})"
+                )
+            }
             AssertReason::UnexpectedStdout { case_tree } => {
                 writeln!(f, "Unexpected stdout, failed {case_tree}")
             }
+            AssertReason::UnexpectedPlain {
+                stream,
+                plain,
+                case_tree,
+            } => {
+                writeln!(
+                    f,
+                    "Unexpected {stream} with ANSI escapes stripped, failed {case_tree}"
+                )?;
+                writeln!(f, "plain {stream}={}", DebugBytes::new(plain))
+            }
+            AssertReason::UnexpectedStdoutLine {
+                line_number,
+                line,
+                case_tree,
+            } => {
+                writeln!(
+                    f,
+                    "Unexpected stdout on line {line_number}: {line:?}, failed {case_tree}"
+                )
+            }
             AssertReason::UnexpectedStderr { case_tree } => {
                 writeln!(f, "Unexpected stderr, failed {case_tree}")
             }
+            AssertReason::UnexpectedStdoutMessage { detail } => {
+                writeln!(f, "Unexpected stdout, {detail}")
+            }
+            AssertReason::ExternalStream { detail, .. } => writeln!(f, "{detail}"),
+            AssertReason::UnexpectedOutputs { evaluations } => {
+                writeln!(f, "Unexpected output")?;
+                for (name, evaluation) in evaluations.iter() {
+                    writeln!(f, "{name}: {evaluation}")?;
+                }
+                Ok(())
+            }
+            AssertReason::UnexpectedFile { path, case_tree } => {
+                writeln!(f, "Unexpected {}, failed {case_tree}", path.display())
+            }
+            AssertReason::UnreadableFile { path, cause } => {
+                writeln!(f, "Failed to read {}: {cause}", path.display())
+            }
+            AssertReason::UnwritableFile { path, cause } => {
+                writeln!(f, "Failed to update {}: {cause}", path.display())
+            }
+            AssertReason::UncreatableFile { path, cause } => {
+                writeln!(f, "Failed to create {}: {cause}", path.display())
+            }
+            AssertReason::LeftoverFile { path } => {
+                writeln!(f, "Unexpected {}, expected it to be absent", path.display())
+            }
+            AssertReason::Undecodable { stream, cause } => {
+                writeln!(f, "Failed to decode {stream}: {cause}")
+            }
+            AssertReason::RatchetExceeded {
+                stream,
+                pattern,
+                path,
+                baseline,
+                count,
+            } => {
+                writeln!(
+                    f,
+                    "Unexpected {stream}, {count} lines contain {pattern:?}, up from {baseline} in {}",
+                    path.display()
+                )
+            }
+            AssertReason::UnexpectedGolden {
+                stream,
+                path,
+                missing,
+                diff,
+                patch,
+            } => {
+                if *missing {
+                    writeln!(f, "Unexpected {stream}, {} doesn't exist", path.display())?;
+                } else {
+                    writeln!(f, "Unexpected {stream}, differs from {}", path.display())?;
+                }
+                write!(f, "{diff}")?;
+                match patch {
+                    Some(Ok(patch)) => writeln!(f, "patch: {}", patch.display()),
+                    Some(Err(err)) => writeln!(f, "failed to write patch: {err}"),
+                    None => Ok(()),
+                }
+            }
+            AssertReason::UnexpectedFds { unexpected } => {
+                writeln!(f, "Unexpected file descriptors: {unexpected:?}")
+            }
+            AssertReason::UnreportedFds => {
+                writeln!(
+                    f,
+                    "File descriptors weren't reported, run with `Command::report_fds`"
+                )
+            }
+            AssertReason::NonEmptyStdout { noise } => {
+                let stdout = self.output().map(|o| o.stdout.trim()).unwrap_or_default();
+                writeln!(
+                    f,
+                    "Unexpected stdout, expected nothing but found {noise}: {}",
+                    DebugBytes::new(stdout)
+                )
+            }
+            AssertReason::NonEmptyStderr { noise } => {
+                let stderr = self.output().map(|o| o.stderr.trim()).unwrap_or_default();
+                writeln!(
+                    f,
+                    "Unexpected stderr, expected nothing but found {noise}: {}",
+                    DebugBytes::new(stderr)
+                )
+            }
+            AssertReason::StderrPolicy {
+                line_number,
+                line,
+                pattern,
+            } => {
+                writeln!(
+                    f,
+                    "Unexpected stderr on line {line_number}: {line:?}, denied by {pattern:?}"
+                )
+            }
+            AssertReason::Spawn { cause } => write!(f, "{cause}"),
+            AssertReason::Timeout { deadline } => writeln!(f, "{deadline}"),
+            AssertReason::NotIdempotent => writeln!(f, "Unexpected change when run again"),
+            AssertReason::Unasserted { streams } => {
+                let streams = streams
+                    .iter()
+                    .map(|stream| stream.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" and ");
+                writeln!(f, "Unasserted {streams}, required by strict mode")
+            }
+            AssertReason::NothingAsserted => writeln!(
+                f,
+                "Nothing was asserted on this run, add an assertion like `.success()`"
+            ),
+            AssertReason::UnexpectedPass { reason } => {
+                writeln!(f, "Unexpectedly passed, expected to fail: {reason}")
+            }
         }?;
-        write!(f, "{}", self.assert)
+        if let Some(assert) = &self.assert {
+            assert.write_report(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// How each part of the output was evaluated by [`Assert::outputs`].
+#[derive(Debug)]
+struct Evaluations {
+    stdout: Evaluation,
+    stderr: Evaluation,
+    code: Evaluation,
+}
+
+impl Evaluations {
+    fn iter(&self) -> impl Iterator<Item = (&'static str, &Evaluation)> {
+        [
+            ("stdout", &self.stdout),
+            ("stderr", &self.stderr),
+            ("code", &self.code),
+        ]
+        .into_iter()
+    }
+
+    fn passed(&self) -> bool {
+        self.iter()
+            .all(|(_, evaluation)| matches!(evaluation, Evaluation::Passed))
+    }
+
+    /// The kind of the first mismatch, in argument order.
+    fn kind(&self) -> AssertErrorKind {
+        if !matches!(self.stdout, Evaluation::Passed) {
+            AssertErrorKind::StdoutMismatch
+        } else if !matches!(self.stderr, Evaluation::Passed) {
+            AssertErrorKind::StderrMismatch
+        } else {
+            AssertErrorKind::WrongCode
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Evaluation {
+    Passed,
+    Failed(CaseTree),
+    Interrupted,
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Passed => write!(f, "passed"),
+            Self::Failed(case_tree) => write!(f, "failed {case_tree}"),
+            Self::Interrupted => write!(f, "command interrupted"),
+        }
     }
 }
 
@@ -1101,7 +4522,13 @@ struct CaseTree(predicates_tree::CaseTree);
 
 impl fmt::Display for CaseTree {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        <predicates_tree::CaseTree as fmt::Display>::fmt(&self.0, f)
+        if crate::color::Theme::installed().predicate_color_enabled() {
+            <predicates_tree::CaseTree as fmt::Display>::fmt(&self.0, f)
+        } else {
+            let styled = self.0.to_string();
+            let plain = crate::output::strip_ansi(styled.as_bytes());
+            write!(f, "{}", plain.as_bstr())
+        }
     }
 }
 
@@ -1185,4 +4612,96 @@ mod test {
         let pred = convert_output("Hello");
         assert!(pred.eval(b"Hello" as &[u8]));
     }
+
+    #[test]
+    fn into_output_from_cow() {
+        let pred = convert_output(Cow::<'static, str>::Owned("Hello".to_owned()));
+        assert!(pred.eval(b"Hello" as &[u8]));
+        let pred = convert_output(Cow::<'static, [u8]>::Borrowed(b"Hello"));
+        assert!(pred.eval(b"Hello" as &[u8]));
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn into_output_from_regex() {
+        let pred = convert_output(regex::Regex::new("^H.*o$").unwrap());
+        assert!(pred.eval(b"Hello" as &[u8]));
+        assert!(!pred.eval(b"World" as &[u8]));
+    }
+
+    #[test]
+    fn trailing_newline() {
+        assert_eq!(trim_trailing_newline(b"a\n"), b"a");
+        assert_eq!(trim_trailing_newline(b"a\r\n"), b"a");
+        assert_eq!(trim_trailing_newline(b"a\n\n"), b"a\n");
+        assert_eq!(trim_trailing_newline(b"a"), b"a");
+    }
+
+    #[test]
+    fn dedent_literal() {
+        let actual = dedent(
+            r#"
+            first
+              indented
+
+            last
+            "#,
+        );
+        assert_eq!(actual, "first\n  indented\n\nlast\n");
+    }
+
+    #[test]
+    fn dedent_no_trailing_newline() {
+        assert_eq!(dedent("  a\n    b"), "a\n  b");
+    }
+
+    #[test]
+    fn dedent_unicode_whitespace() {
+        assert_eq!(dedent("\u{a0}a\n b"), "\u{a0}a\n b");
+        assert_eq!(dedent("  \u{3000}a\n   b"), "\u{3000}a\n b");
+    }
+
+    #[test]
+    fn noise_classify() {
+        assert_eq!(
+            Noise::classify(b"thread 'main' panicked at src/main.rs:1:1:\n"),
+            Noise::Panic
+        );
+        assert_eq!(
+            Noise::classify(b"Warning: `--foo` is Deprecated\n"),
+            Noise::Deprecation
+        );
+        assert_eq!(Noise::classify(b"\x1b[0m\x1b[1;31m\n"), Noise::Ansi);
+        assert_eq!(Noise::classify(b" \n"), Noise::Whitespace);
+        assert_eq!(Noise::classify(b"hello\n"), Noise::Text);
+    }
+
+    #[test]
+    fn stderr_policy_check() {
+        let policy = StderrPolicy::new()
+            .deny("panicked at")
+            .deny("warning:")
+            .allow("deprecated");
+
+        assert_eq!(policy.check(b"all good\nwarning: deprecated flag\n"), None);
+        assert_eq!(
+            policy.check(b"all good\nwarning: unused\n"),
+            Some((2, "warning: unused".into(), "warning:".to_owned()))
+        );
+    }
+
+    #[test]
+    fn uncreatable_output_file() {
+        let err = AssertError::uncreatable(
+            "/no/such/dir/stdout".into(),
+            &io::Error::from(io::ErrorKind::NotFound),
+        );
+        assert_eq!(err.kind(), AssertErrorKind::Spawn);
+        let message = err.to_string();
+        assert!(
+            message.starts_with("Failed to create /no/such/dir/stdout: "),
+            "{message}"
+        );
+        assert!(!message.contains("command="), "{message}");
+    }
 }
@@ -0,0 +1,79 @@
+//! Cap the total time commands may run in a test process.
+//!
+//! Once commands have run for longer than the budget, summed across all of them, later
+//! assertions fail immediately with a "budget exceeded" error, instead of the CI job hitting its
+//! own, less clear, timeout.  A running command is also killed when it uses up what remains, so
+//! [`Command::output`][crate::Command::output] returns the output of a killed command.
+//!
+//! The budget may be [set][set_budget] or, for a whole test run, given in seconds with the
+//! `ASSERT_CMD_BUDGET` environment variable.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! assert_cmd::budget::set_budget(Some(std::time::Duration::from_secs(600)));
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .assert()
+//!     .success();
+//! ```
+
+use std::env;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+/// Environment variable for the budget in seconds, used when no budget is [set][set_budget].
+pub const BUDGET_ENV: &str = "ASSERT_CMD_BUDGET";
+
+struct State {
+    budget: Option<Duration>,
+    spent: Duration,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    budget: None,
+    spent: Duration::ZERO,
+});
+
+/// Limit the total time commands may run in this process.
+///
+/// [`None`] falls back to [`BUDGET_ENV`].  Time already spent still counts.
+pub fn set_budget(budget: Option<Duration>) {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner).budget = budget;
+}
+
+/// The total time commands may run in this process, if limited.
+pub fn budget() -> Option<Duration> {
+    let budget = STATE.lock().unwrap_or_else(PoisonError::into_inner).budget;
+    budget.or_else(|| {
+        env::var(BUDGET_ENV)
+            .ok()
+            .and_then(|secs| secs.trim().parse::<f64>().ok())
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+    })
+}
+
+/// How long commands have run in this process.
+pub fn spent() -> Duration {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner).spent
+}
+
+/// How much of the budget is left, if limited.
+pub fn remaining() -> Option<Duration> {
+    budget().map(|budget| budget.saturating_sub(spent()))
+}
+
+/// Count `elapsed` against the budget.
+pub(crate) fn record(elapsed: Duration) {
+    let mut state = STATE.lock().unwrap_or_else(PoisonError::into_inner);
+    state.spent = state.spent.saturating_add(elapsed);
+}
+
+/// The budget, if it has been used up.
+pub(crate) fn exceeded() -> Option<Duration> {
+    budget().filter(|budget| *budget <= spent())
+}
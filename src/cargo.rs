@@ -136,18 +136,140 @@ impl CommandCargoExt for process::Command {
 }
 
 pub(crate) fn cargo_bin_cmd<S: AsRef<str>>(name: S) -> Result<process::Command, CargoError> {
-    let path = cargo_bin(name);
-    if path.is_file() {
-        if let Some(runner) = cargo_runner() {
-            let mut cmd = process::Command::new(&runner[0]);
-            cmd.args(&runner[1..]).arg(path);
-            Ok(cmd)
-        } else {
-            Ok(process::Command::new(path))
-        }
+    cargo_bin_path(name.as_ref()).map(|path| bin_cmd(&path))
+}
+
+/// The first of the binary's candidate paths that exists.
+pub(crate) fn cargo_bin_path(name: &str) -> Result<path::PathBuf, CargoError> {
+    let tried = bin_candidates(name);
+    match tried.iter().position(|path| path.is_file()) {
+        Some(found) => Ok(tried.into_iter().nth(found).expect("found above")),
+        None => Err(CargoError::with_cause(NotFoundError { tried })),
+    }
+}
+
+/// A command running the binary at `path`, through the configured runner if any.
+pub(crate) fn bin_cmd(path: &path::Path) -> process::Command {
+    if let Some(runner) = cargo_runner() {
+        let mut cmd = process::Command::new(&runner[0]);
+        cmd.args(&runner[1..]).arg(path);
+        cmd
     } else {
-        Err(CargoError::with_cause(NotFoundError { path }))
+        process::Command::new(path)
+    }
+}
+
+/// Which build of the binary at `path` is about to run: its size, modification time, and, with
+/// the `sha2` feature, its SHA-256.
+///
+/// Hashes are cached by path, size, and modification time, so a binary is only read again once
+/// it is rebuilt.
+pub(crate) fn binary_info(path: &path::Path) -> String {
+    let metadata = match path.metadata() {
+        Ok(metadata) => metadata,
+        Err(err) => return format!("unreadable: {err}"),
+    };
+    let modified = metadata.modified().ok();
+    let mut rendered = String::new();
+    #[cfg(feature = "sha2")]
+    match file_hash(path, metadata.len(), modified) {
+        Ok(hash) => {
+            rendered.push_str("sha256 ");
+            rendered.push_str(&hash);
+            rendered.push_str(", ");
+        }
+        Err(err) => return format!("unreadable: {err}"),
     }
+    rendered.push_str(&format!("{} bytes", metadata.len()));
+    if let Some(modified) = modified {
+        rendered.push_str(", modified ");
+        rendered.push_str(&crate::output::timestamp(modified));
+    }
+    rendered
+}
+
+#[cfg(feature = "sha2")]
+type HashKey = (path::PathBuf, u64, Option<std::time::SystemTime>);
+
+#[cfg(feature = "sha2")]
+static HASHES: std::sync::Mutex<Vec<(HashKey, String)>> = std::sync::Mutex::new(Vec::new());
+
+/// The SHA-256 of the file at `path`, as lowercase hex.
+#[cfg(feature = "sha2")]
+fn file_hash(
+    path: &path::Path,
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+) -> std::io::Result<String> {
+    use sha2::Digest as _;
+
+    let key = (path.to_owned(), len, modified);
+    let mut hashes = HASHES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some((_, hash)) = hashes.iter().find(|(k, _)| *k == key) {
+        return Ok(hash.clone());
+    }
+    let mut hasher = sha2::Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    hashes.retain(|(k, _)| k.0 != key.0);
+    hashes.push((key, hash.clone()));
+    Ok(hash)
+}
+
+type ExitCodeMap = fn(i32) -> i32;
+
+static RUNNER_EXIT_CODES: std::sync::Mutex<Vec<(String, ExitCodeMap)>> =
+    std::sync::Mutex::new(Vec::new());
+
+/// Translate the exit code of `runner`, like `valgrind` or `qemu-aarch64`, to the binary's.
+///
+/// When [`Command::cargo_bin`][crate::Command::cargo_bin] runs a binary through a runner from
+/// `CARGO_TARGET_<TRIPLET>_RUNNER` whose program is named `runner`, `map` is applied as by
+/// [`Command::wrapper_exit_code`][crate::Command::wrapper_exit_code].  This applies to commands
+/// created after registering, so register before creating any, like in a shared test helper.
+///
+/// # Examples
+///
+/// With `CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUNNER="valgrind --error-exitcode=99"`, treat
+/// memory errors as a crash rather than an ordinary failure:
+/// ```rust,no_run
+/// use assert_cmd::Command;
+///
+/// assert_cmd::cargo::runner_exit_code("valgrind", |code| if code == 99 { 134 } else { code });
+///
+/// Command::cargo_bin("bin_fixture")
+///     .unwrap()
+///     .assert()
+///     .success();
+/// ```
+pub fn runner_exit_code(runner: impl Into<String>, map: fn(i32) -> i32) {
+    let runner = runner.into();
+    let mut registered = RUNNER_EXIT_CODES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    registered.retain(|(name, _)| *name != runner);
+    registered.push((runner, map));
+}
+
+/// The translation registered for the configured runner, if any.
+pub(crate) fn cargo_runner_exit_code() -> Option<ExitCodeMap> {
+    runner_exit_code_for(&cargo_runner()?)
+}
+
+fn runner_exit_code_for(runner: &[String]) -> Option<ExitCodeMap> {
+    let program = path::Path::new(runner.first()?).file_stem()?;
+    RUNNER_EXIT_CODES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .iter()
+        .find(|(name, _)| program == name.as_str())
+        .map(|(_, map)| *map)
 }
 
 pub(crate) fn cargo_runner() -> Option<Vec<String>> {
@@ -156,7 +278,7 @@ pub(crate) fn cargo_runner() -> Option<Vec<String>> {
         CURRENT_TARGET.replace('-', "_").to_uppercase()
     );
     let runner = env::var(runner_env).ok()?;
-    Some(runner.split(' ').map(str::to_string).collect())
+    Some(runner.split(' ').map(str::to_owned).collect())
 }
 
 /// Error when finding crate binary.
@@ -176,7 +298,13 @@ impl CargoError {
     }
 }
 
-impl Error for CargoError {}
+impl Error for CargoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause
+            .as_ref()
+            .map(|cause| cause.as_ref() as &(dyn Error + 'static))
+    }
+}
 
 impl fmt::Display for CargoError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -187,33 +315,99 @@ impl fmt::Display for CargoError {
     }
 }
 
-/// Error when finding crate binary.
+/// A crate binary wasn't found, the [source][Error::source] of a [`CargoError`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::Command;
+/// use assert_cmd::cargo::NotFoundError;
+/// use std::error::Error as _;
+///
+/// let err = Command::cargo_bin("missing").unwrap_err();
+/// let not_found = err.source().unwrap().downcast_ref::<NotFoundError>().unwrap();
+/// println!("looked in {:?}", not_found.tried());
+/// ```
 #[derive(Debug)]
-struct NotFoundError {
-    path: path::PathBuf,
+pub struct NotFoundError {
+    tried: Vec<path::PathBuf>,
+}
+
+impl NotFoundError {
+    /// Where the binary was looked for, most likely first.
+    pub fn tried(&self) -> &[path::PathBuf] {
+        &self.tried
+    }
 }
 
 impl Error for NotFoundError {}
 
 impl fmt::Display for NotFoundError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Cargo command not found: {}", self.path.display())
+        let mut tried = self
+            .tried
+            .iter()
+            .map(|path| crate::output::display_path(path));
+        if let Some(path) = tried.next() {
+            writeln!(f, "Cargo command not found: {}", path.display())?;
+        }
+        for path in tried {
+            writeln!(f, "  also tried: {}", path.display())?;
+        }
+        Ok(())
     }
 }
 
-// Adapted from
-// https://github.com/rust-lang/cargo/blob/485670b3983b52289a2f353d589c57fae2f60f82/tests/testsuite/support/mod.rs#L507
-fn target_dir() -> path::PathBuf {
-    env::current_exe()
-        .ok()
-        .map(|mut path| {
+/// Directories the binaries for the tests' profile may be in, most likely first.
+///
+/// The test binary's directory comes first, with symlinks resolved, as `target/` may link to a
+/// shared build cache.  Then that directory as reached, and the same profile under
+/// `CARGO_TARGET_DIR` and the package's own `target/`.
+fn target_dirs() -> Vec<path::PathBuf> {
+    // Adapted from
+    // https://github.com/rust-lang/cargo/blob/485670b3983b52289a2f353d589c57fae2f60f82/tests/testsuite/support/mod.rs#L507
+    let profile_dir = |mut path: path::PathBuf| {
+        path.pop();
+        if path.ends_with("deps") {
             path.pop();
-            if path.ends_with("deps") {
-                path.pop();
-            }
-            path
-        })
-        .expect("this should only be used where a `current_exe` can be set")
+        }
+        path
+    };
+    let exe =
+        env::current_exe().expect("this should only be used where a `current_exe` can be set");
+    let mut dirs = Vec::new();
+    if let Ok(canonical) = exe.canonicalize() {
+        dirs.push(profile_dir(canonical));
+    }
+    dirs.push(profile_dir(exe));
+
+    // Relative to the target directory, like `debug` or `<TRIPLET>/debug`
+    let profile = dirs[0].file_name().map(path::PathBuf::from);
+    let profile = profile.map(|profile| match dirs[0].parent() {
+        Some(parent) if parent.ends_with(CURRENT_TARGET) => {
+            path::Path::new(CURRENT_TARGET).join(profile)
+        }
+        _ => profile,
+    });
+    if let Some(profile) = profile {
+        let roots = [
+            env::var_os("CARGO_TARGET_DIR").map(path::PathBuf::from),
+            env::var_os("CARGO_MANIFEST_DIR").map(|dir| path::Path::new(&dir).join("target")),
+        ];
+        dirs.extend(roots.into_iter().flatten().map(|root| root.join(&profile)));
+    }
+    dedup(dirs)
+}
+
+/// Remove later duplicates, keeping the order.
+fn dedup(paths: Vec<path::PathBuf>) -> Vec<path::PathBuf> {
+    let mut unique: Vec<path::PathBuf> = Vec::with_capacity(paths.len());
+    for path in paths {
+        if !unique.contains(&path) {
+            unique.push(path);
+        }
+    }
+    unique
 }
 
 /// Look up the path to a cargo-built binary within an integration test.
@@ -221,12 +415,116 @@ pub fn cargo_bin<S: AsRef<str>>(name: S) -> path::PathBuf {
     cargo_bin_str(name.as_ref())
 }
 
+/// Look up the path to a binary cross-compiled for `target`, like `aarch64-unknown-linux-gnu`.
+///
+/// This searches `target/<TRIPLET>/<PROFILE>/` for the profile the tests were built with, so build
+/// the binary first, like with `cargo build --target <TRIPLET>`.  Run it through an emulator or
+/// with [`Command::wrapped_by`][crate::Command::wrapped_by].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::Command;
+///
+/// let bin = assert_cmd::cargo::bin_for_target("aarch64-unknown-linux-gnu", "mytool").unwrap();
+/// Command::new("qemu-aarch64")
+///     .arg(bin)
+///     .arg("--version")
+///     .assert()
+///     .success();
+/// ```
+pub fn bin_for_target(target: &str, name: &str) -> Result<path::PathBuf, CargoError> {
+    let tried = dedup(
+        target_dirs()
+            .iter()
+            .map(|dir| bin_for_target_in(dir, target, name))
+            .collect(),
+    );
+    match tried.iter().find(|path| path.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(CargoError::with_cause(NotFoundError { tried })),
+    }
+}
+
+/// Where `name` would be built for `target`, alongside `profile_dir`, like `target/debug`.
+fn bin_for_target_in(profile_dir: &path::Path, target: &str, name: &str) -> path::PathBuf {
+    let profile = profile_dir.file_name().unwrap_or_default();
+    let mut root = profile_dir.parent().unwrap_or(profile_dir);
+    // Tests built with `--target` are under their own triplet's directory
+    if root.ends_with(CURRENT_TARGET) {
+        root = root.parent().unwrap_or(root);
+    }
+    let suffix = if target.contains("windows") {
+        ".exe"
+    } else if target.starts_with("wasm") {
+        ".wasm"
+    } else {
+        ""
+    };
+    root.join(target)
+        .join(profile)
+        .join(format!("{name}{suffix}"))
+}
+
 fn cargo_bin_str(name: &str) -> path::PathBuf {
+    let mut candidates = bin_candidates(name);
+    match candidates.iter().position(|path| path.is_file()) {
+        Some(found) => candidates.swap_remove(found),
+        None => candidates.swap_remove(0),
+    }
+}
+
+/// Where `name` may be, most likely first.
+fn bin_candidates(name: &str) -> Vec<path::PathBuf> {
     let env_var = format!("CARGO_BIN_EXE_{name}");
-    env::var_os(env_var)
-        .map(|p| p.into())
-        .unwrap_or_else(|| target_dir().join(format!("{}{}", name, env::consts::EXE_SUFFIX)))
+    let paths = match env::var_os(env_var) {
+        Some(path) => vec![path.into()],
+        None => {
+            let file = format!("{}{}", name, env::consts::EXE_SUFFIX);
+            target_dirs().iter().map(|dir| dir.join(&file)).collect()
+        }
+    };
+    // Deep workspaces can put the binary past what Windows runs without the prefix
+    paths
+        .into_iter()
+        .map(crate::output::extended_length)
+        .collect()
 }
 
 /// The current process' target triplet.
 const CURRENT_TARGET: &str = include_str!(concat!(env!("OUT_DIR"), "/current_target.txt"));
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bin_for_target_paths() {
+        let expected = path::Path::new("target")
+            .join("aarch64-unknown-linux-gnu")
+            .join("debug")
+            .join("mytool");
+        let host = path::Path::new("target").join("debug");
+        assert_eq!(
+            bin_for_target_in(&host, "aarch64-unknown-linux-gnu", "mytool"),
+            expected
+        );
+        let host = path::Path::new("target").join(CURRENT_TARGET).join("debug");
+        assert_eq!(
+            bin_for_target_in(&host, "aarch64-unknown-linux-gnu", "mytool"),
+            expected
+        );
+        assert!(
+            bin_for_target_in(&host, "x86_64-pc-windows-msvc", "mytool").ends_with("mytool.exe")
+        );
+    }
+
+    #[test]
+    fn runner_exit_codes() {
+        runner_exit_code("test-runner", |code| code - 100);
+        let runner = ["/usr/bin/test-runner".to_owned(), "--flag".to_owned()];
+        let map = runner_exit_code_for(&runner).unwrap();
+        assert_eq!(map(103), 3);
+        assert!(runner_exit_code_for(&["other-runner".to_owned()]).is_none());
+    }
+}
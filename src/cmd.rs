@@ -1,26 +1,89 @@
 //! [`std::process::Command`] customized for testing.
 
 use std::ffi;
+use std::fs;
 use std::io;
-use std::io::{Read, Write};
-use std::ops::Deref;
 use std::path;
 use std::process;
+use std::sync::Arc;
 
 use crate::assert::Assert;
+use crate::assert::AssertError;
+use crate::assert::AssertResult;
 use crate::assert::OutputAssertExt;
+use crate::assert::SpawnError;
 use crate::output::DebugBuffer;
 use crate::output::DebugBytes;
 use crate::output::OutputError;
 use crate::output::OutputOkExt;
 use crate::output::OutputResult;
+use crate::pump::Input;
+use crate::pump::Pump;
+
+/// Environment variable holding the seed set by [`Command::deterministic`].
+///
+/// Programs that use randomness can seed their generator from it to be reproducible under test.
+pub const SEED_ENV: &str = "ASSERT_CMD_SEED";
+
+/// `SOURCE_DATE_EPOCH` set by [`Command::deterministic`]: 2000-01-01T00:00:00Z.
+pub const SOURCE_DATE_EPOCH: u64 = 946_684_800;
 
 /// [`std::process::Command`] customized for testing.
 #[derive(Debug)]
 pub struct Command {
     cmd: process::Command,
-    stdin: Option<bstr::BString>,
+    stdin: Option<Arc<bstr::BString>>,
+    stdin_pauses: Vec<(usize, std::time::Duration)>,
+    /// Seed of [`Command::write_stdin_random`] or [`Command::write_stdin_random_lines`].
+    stdin_seed: Option<u64>,
+    stdin_limit: Option<usize>,
+    stdout_limit: Option<usize>,
     timeout: Option<std::time::Duration>,
+    stdin_timeout: Option<std::time::Duration>,
+    kill_timeout: Option<std::time::Duration>,
+    warn_after: Option<std::time::Duration>,
+    /// Signal sent by [`Command::send_signal_after`], and how long after spawning.
+    signal: Option<(i32, std::time::Duration)>,
+    expected_failure: Option<String>,
+    /// Whether [`Command::env_clear`] was called, which `self.cmd` doesn't report.
+    env_cleared: bool,
+    wrapper_exit_code: Option<fn(i32) -> i32>,
+    /// Binary found by [`Command::cargo_bin`], identified in failures.
+    binary: Option<path::PathBuf>,
+    /// CPU cores to pin the process to once spawned, see [`Command::cpu_affinity`].
+    #[cfg(windows)]
+    affinity: Option<usize>,
+    /// Settings `self.cmd` doesn't report, kept to copy them.
+    #[cfg(unix)]
+    uid: Option<u32>,
+    #[cfg(unix)]
+    gid: Option<u32>,
+    #[cfg(unix)]
+    arg0: Option<ffi::OsString>,
+    #[cfg(unix)]
+    process_group: Option<i32>,
+    #[cfg(windows)]
+    creation_flags: Option<u32>,
+    hooks: crate::hooks::Hooks,
+    #[cfg(unix)]
+    listeners: Vec<Arc<std::os::unix::io::OwnedFd>>,
+    /// Sets `LISTEN_PID` for `listeners`.
+    #[cfg(unix)]
+    activation: Arc<crate::activation::Activation>,
+    #[cfg(unix)]
+    fds: Arc<crate::fds::FdPolicy>,
+    #[cfg(unix)]
+    pre_exec: Arc<crate::pre_exec::PreExec>,
+    /// Whether `self.cmd` has the `pre_exec` hook running `pre_exec`, `fds`, and `activation`.
+    #[cfg(unix)]
+    exec_hook: bool,
+    #[cfg(unix)]
+    report_fds: bool,
+    #[cfg(unix)]
+    inherited: Vec<Arc<std::os::unix::io::OwnedFd>>,
+    /// Descriptors reported by the last child spawned.
+    #[cfg(unix)]
+    visible_fds: Option<Vec<i32>>,
 }
 
 impl Command {
@@ -29,7 +92,48 @@ impl Command {
         Self {
             cmd,
             stdin: None,
+            stdin_pauses: Vec::new(),
+            stdin_seed: None,
+            stdin_limit: None,
+            stdout_limit: None,
             timeout: None,
+            stdin_timeout: None,
+            kill_timeout: None,
+            warn_after: None,
+            signal: None,
+            expected_failure: None,
+            env_cleared: false,
+            wrapper_exit_code: None,
+            binary: None,
+            #[cfg(windows)]
+            affinity: None,
+            #[cfg(unix)]
+            uid: None,
+            #[cfg(unix)]
+            gid: None,
+            #[cfg(unix)]
+            arg0: None,
+            #[cfg(unix)]
+            process_group: None,
+            #[cfg(windows)]
+            creation_flags: None,
+            hooks: crate::hooks::Hooks::new(),
+            #[cfg(unix)]
+            listeners: Vec::new(),
+            #[cfg(unix)]
+            activation: Arc::new(crate::activation::Activation::new()),
+            #[cfg(unix)]
+            fds: Arc::new(crate::fds::FdPolicy::new()),
+            #[cfg(unix)]
+            pre_exec: Arc::new(crate::pre_exec::PreExec::new()),
+            #[cfg(unix)]
+            exec_hook: false,
+            #[cfg(unix)]
+            report_fds: false,
+            #[cfg(unix)]
+            inherited: Vec::new(),
+            #[cfg(unix)]
+            visible_fds: None,
         }
     }
 
@@ -58,351 +162,1800 @@ impl Command {
     /// ```
     ///
     pub fn cargo_bin<S: AsRef<str>>(name: S) -> Result<Self, crate::cargo::CargoError> {
-        let cmd = crate::cargo::cargo_bin_cmd(name)?;
-        Ok(Self::from_std(cmd))
+        let binary = crate::cargo::cargo_bin_path(name.as_ref())?;
+        let mut cmd = Self::from_std(crate::cargo::bin_cmd(&binary));
+        cmd.binary = Some(binary);
+        if let Some(map) = crate::cargo::cargo_runner_exit_code() {
+            cmd.wrapper_exit_code(map);
+        }
+        Ok(cmd)
     }
 
-    /// Write `buffer` to `stdin` when the `Command` is run.
+    /// Create a [`Command`] that runs `script` with the platform's shell.
+    ///
+    /// This is `sh -c <script>` on Unix and `cmd /C <script>` on Windows.  `script` is passed to
+    /// the shell verbatim, so it is subject to the shell's own parsing (pipes, globbing,
+    /// variable expansion, etc).
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let mut cmd = Command::new("cat")
-    ///     .arg("-et")
-    ///     .write_stdin("42")
+    /// Command::shell("echo hello | tr a-z A-Z")
     ///     .assert()
-    ///     .stdout("42");
+    ///     .stdout("HELLO\n");
     /// ```
-    pub fn write_stdin<S>(&mut self, buffer: S) -> &mut Self
-    where
-        S: Into<Vec<u8>>,
-    {
-        self.stdin = Some(bstr::BString::from(buffer.into()));
+    pub fn shell<S: AsRef<ffi::OsStr>>(script: S) -> Self {
+        #[cfg(windows)]
+        let cmd = {
+            use std::os::windows::process::CommandExt;
+
+            let mut cmd = process::Command::new("cmd");
+            // `cmd` doesn't follow the quoting conventions `Command::arg` assumes
+            cmd.arg("/C").raw_arg(script);
+            cmd
+        };
+        #[cfg(not(windows))]
+        let cmd = {
+            let mut cmd = process::Command::new("sh");
+            cmd.arg("-c").arg(script);
+            cmd
+        };
+        Self::from_std(cmd)
+    }
+
+    /// Run this command under `wrapper`, like `strace`, `time`, or `firejail`.
+    ///
+    /// The program and arguments of this command are appended to `wrapper`'s arguments.
+    /// Environment variables from both are applied, with this command's taking precedence, and
+    /// the environment is cleared if either [cleared it][Command::env_clear].  This command's
+    /// working directory is used if it has one.  `stdin` and timeouts are unaffected.
+    ///
+    /// `std` can't read back how the process is started, so this command's `pre_exec` hooks, and
+    /// settings made with them like `cpu_affinity` on Linux, are lost, as are its `uid`, `gid`,
+    /// `process_group`, and `arg0`.  Set those after wrapping to apply them to the wrapper, whose
+    /// children inherit all but `arg0`.
+    ///
+    /// See [`Command::wrapper_exit_code`] for wrappers that change the exit code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let mut wrapper = Command::new("strace");
+    /// wrapper.args(["-f", "-o", "/dev/null"]);
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .wrapped_by(wrapper)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn wrapped_by(&mut self, wrapper: Command) -> &mut Self {
+        let inner = &self.cmd;
+        let mut cmd = wrapper.cmd;
+        cmd.arg(inner.get_program()).args(inner.get_args());
+        if self.env_cleared && !wrapper.env_cleared {
+            // `env_clear` also forgets variables set so far
+            let envs: Vec<_> = cmd
+                .get_envs()
+                .map(|(key, value)| (key.to_owned(), value.map(ToOwned::to_owned)))
+                .collect();
+            cmd.env_clear();
+            for (key, value) in envs {
+                if let Some(value) = value {
+                    cmd.env(key, value);
+                }
+            }
+        }
+        for (key, value) in inner.get_envs() {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+        if let Some(dir) = inner.get_current_dir() {
+            cmd.current_dir(dir);
+        }
+        self.cmd = cmd;
+        self.env_cleared |= wrapper.env_cleared;
+        #[cfg(unix)]
+        {
+            self.pre_exec = Arc::new(crate::pre_exec::PreExec::new());
+            self.exec_hook = false;
+            self.uid = wrapper.uid;
+            self.gid = wrapper.gid;
+            self.arg0 = wrapper.arg0;
+            self.process_group = wrapper.process_group;
+        }
+        #[cfg(windows)]
+        {
+            self.creation_flags = wrapper.creation_flags;
+        }
         self
     }
 
-    /// Error out if a timeout is reached
+    /// Translate the wrapper's exit code to the wrapped command's, before asserting.
+    ///
+    /// `map` is only called when the wrapper exited with a code.  For the runner
+    /// [`Command::cargo_bin`] may use, see [`cargo::runner_exit_code`][crate::cargo::runner_exit_code].
+    ///
+    /// # Examples
     ///
+    /// For a sandbox that reports the command's exit code offset by 100:
     /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let assert = Command::cargo_bin("bin_fixture")
+    /// let wrapper = Command::new("my-sandbox");
+    ///
+    /// Command::cargo_bin("bin_fixture")
     ///     .unwrap()
-    ///     .timeout(std::time::Duration::from_secs(1))
-    ///     .env("sleep", "100")
-    ///     .assert();
-    /// assert.failure();
+    ///     .env("exit", "3")
+    ///     .wrapped_by(wrapper)
+    ///     .wrapper_exit_code(|code| code - 100)
+    ///     .assert()
+    ///     .code(3);
     /// ```
-    pub fn timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
-        self.timeout = Some(timeout);
+    pub fn wrapper_exit_code(&mut self, map: fn(i32) -> i32) -> &mut Self {
+        self.wrapper_exit_code = Some(map);
         self
     }
 
-    /// Write `path`s content to `stdin` when the `Command` is run.
+    /// Pass `listener` to the command using the `systemd` socket activation protocol.
     ///
-    /// Paths are relative to the [`env::current_dir`][env_current_dir] and not
-    /// [`Command::current_dir`][Command_current_dir].
+    /// Listeners are passed as file descriptors `3`, `4`, etc, in the order they were added, with
+    /// `LISTEN_FDS` and `LISTEN_PID` set accordingly.  As the tests bind the listener, there is no
+    /// race for the port with other tests.
     ///
-    /// [env_current_dir]: std::env::current_dir()
-    /// [Command_current_dir]: std::process::Command::current_dir()
-    pub fn pipe_stdin<P>(&mut self, file: P) -> io::Result<&mut Self>
-    where
-        P: AsRef<path::Path>,
-    {
-        let buffer = std::fs::read(file)?;
-        Ok(self.write_stdin(buffer))
+    /// Only the child knows its `LISTEN_PID`, so it runs the program itself, after every
+    /// [`pre_exec`][Command::pre_exec] hook, and [`Command::arg0`] is ignored.  Passing another
+    /// descriptor as one the listeners use, with [`Command::inherit_fd`], is an error.
+    ///
+    /// This isn't available on Windows, which has no equivalent protocol: sockets are shared with
+    /// `WSADuplicateSocket` for a known process, rather than inherited.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::net::TcpListener;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let addr = listener.local_addr().unwrap();
+    /// Command::cargo_bin("my-daemon")
+    ///     .unwrap()
+    ///     .inherit_listener(listener)
+    ///     .unwrap()
+    ///     .arg("--exit-after-first-request")
+    ///     .assert()
+    ///     .success();
+    /// ```
+    #[cfg(unix)]
+    pub fn inherit_listener(&mut self, listener: std::net::TcpListener) -> io::Result<&mut Self> {
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::io::FromRawFd;
+
+        // Keep sources above every target descriptor so `dup2`ing one can't clobber another
+        const MAX_LISTENERS: usize = 64;
+
+        let index = self.listeners.len();
+        if MAX_LISTENERS <= index {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("at most {MAX_LISTENERS} listeners can be inherited"),
+            ));
+        }
+        let target = libc::c_int::try_from(FIRST_LISTENER_FD + index).unwrap_or(libc::c_int::MAX);
+        if self.inherited.iter().any(|fd| fd.as_raw_fd() == target) {
+            return Err(listener_conflict(target));
+        }
+        let min_fd =
+            libc::c_int::try_from(FIRST_LISTENER_FD + MAX_LISTENERS).unwrap_or(libc::c_int::MAX);
+        // SAFETY: `listener` is an open descriptor for the duration of the call
+        let fd = unsafe { libc::fcntl(listener.as_raw_fd(), libc::F_DUPFD_CLOEXEC, min_fd) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` was just created and is owned by nothing else
+        let fd = unsafe { std::os::unix::io::OwnedFd::from_raw_fd(fd) };
+        let source = fd.as_raw_fd();
+        self.fds.keep(target)?;
+        self.listeners.push(Arc::new(fd));
+
+        // SAFETY: `dup2` is async-signal-safe and `source` is kept open by `self.listeners`
+        unsafe {
+            self.pre_exec.push(move || {
+                // `dup2` clears close-on-exec for `target`
+                if libc::dup2(source, target) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        self.env("LISTEN_FDS", self.listeners.len().to_string());
+        Ok(self)
     }
 
-    /// Run a `Command`, returning an [`OutputResult`].
+    /// Close every file descriptor except `stdin`, `stdout`, `stderr`, and those passed on
+    /// purpose, before running the command.
+    ///
+    /// This ensures the command doesn't depend on descriptors it happened to inherit, like from
+    /// the test harness.  Descriptors passed by [`Command::inherit_fd`] and
+    /// [`Command::inherit_listener`] are kept.
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let result = Command::new("echo")
-    ///     .args(&["42"])
-    ///     .ok();
-    /// assert!(result.is_ok());
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .close_fds(true)
+    ///     .report_fds(true)
+    ///     .assert()
+    ///     .only_fds(&[0, 1, 2]);
     /// ```
+    #[cfg(unix)]
+    pub fn close_fds(&mut self, yes: bool) -> &mut Self {
+        self.fds.set_close(yes);
+        self
+    }
+
+    /// Pass `fd` to the command, as the same descriptor number.
     ///
-    pub fn ok(&mut self) -> OutputResult {
-        OutputOkExt::ok(self)
+    /// The descriptor is kept open until the `Command` is dropped.  Only descriptors below `128`
+    /// can be passed, and not those used by [`Command::inherit_listener`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::os::unix::io::AsRawFd;
+    ///
+    /// let log = std::fs::File::create("target/fd.log").unwrap();
+    /// let fd = log.as_raw_fd();
+    /// Command::cargo_bin("my-tool")
+    ///     .unwrap()
+    ///     .arg(format!("--log-fd={fd}"))
+    ///     .close_fds(true)
+    ///     .inherit_fd(log)
+    ///     .unwrap()
+    ///     .assert()
+    ///     .success();
+    /// ```
+    #[cfg(unix)]
+    pub fn inherit_fd(
+        &mut self,
+        fd: impl Into<std::os::unix::io::OwnedFd>,
+    ) -> io::Result<&mut Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let fd = fd.into();
+        let slots = FIRST_LISTENER_FD..FIRST_LISTENER_FD + self.listeners.len();
+        if usize::try_from(fd.as_raw_fd()).is_ok_and(|fd| slots.contains(&fd)) {
+            return Err(listener_conflict(fd.as_raw_fd()));
+        }
+        self.fds.keep(fd.as_raw_fd())?;
+        self.inherited.push(Arc::new(fd));
+        Ok(self)
     }
 
-    /// Run a `Command`, unwrapping the [`OutputResult`].
+    /// Record which file descriptors the command could see, for [`Assert::only_fds`] and
+    /// [`Assert::visible_fds`].
+    ///
+    /// The descriptors are listed just before the command is executed, so this also reports what
+    /// a [wrapper][Command::wrapped_by] could see.
+    #[cfg(unix)]
+    pub fn report_fds(&mut self, yes: bool) -> &mut Self {
+        self.report_fds = yes;
+        self
+    }
+
+    /// Write `buffer` to `stdin` when the `Command` is run.
     ///
     /// # Examples
     ///
     /// ```rust
     /// use assert_cmd::Command;
     ///
-    /// let output = Command::new("echo")
-    ///     .args(&["42"])
-    ///     .unwrap();
+    /// let mut cmd = Command::new("cat")
+    ///     .arg("-et")
+    ///     .write_stdin("42")
+    ///     .assert()
+    ///     .stdout("42");
     /// ```
-    ///
-    pub fn unwrap(&mut self) -> process::Output {
-        OutputOkExt::unwrap(self)
+    pub fn write_stdin<S>(&mut self, buffer: S) -> &mut Self
+    where
+        S: Into<Vec<u8>>,
+    {
+        self.stdin = Some(Arc::new(bstr::BString::from(buffer.into())));
+        self.stdin_pauses.clear();
+        self.stdin_seed = None;
+        self
     }
 
-    /// Run a `Command`, unwrapping the error in the [`OutputResult`].
+    /// Write `len` pseudo-random bytes, generated from `seed`, to `stdin` when the `Command` is
+    /// run.
+    ///
+    /// This feeds garbage to parsers to check they fail gracefully.  The same `seed` always
+    /// produces the same bytes, and is included in failure output so a failing run can be
+    /// reproduced.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
     /// use assert_cmd::Command;
-    ///
-    /// let err = Command::new("a-command")
-    ///     .args(&["--will-fail"])
-    ///     .unwrap_err();
+    /// use predicates::prelude::*;
+    ///
+    /// for seed in 0..20 {
+    ///     Command::cargo_bin("my-parser")
+    ///         .unwrap()
+    ///         .write_stdin_random(4096, seed)
+    ///         .assert()
+    ///         .stderr(predicate::str::contains("panicked").not());
+    /// }
     /// ```
+    pub fn write_stdin_random(&mut self, len: usize, seed: u64) -> &mut Self {
+        let mut rng = SplitMix64(seed);
+        let buffer: Vec<u8> = std::iter::repeat_with(|| rng.next().to_le_bytes())
+            .flatten()
+            .take(len)
+            .collect();
+        self.write_stdin(buffer);
+        self.stdin_seed = Some(seed);
+        self
+    }
+
+    /// Write `lines` lines of pseudo-random printable ASCII, generated from `seed`, to `stdin`
+    /// when the `Command` is run.
     ///
-    /// [Output]: std::process::Output
-    pub fn unwrap_err(&mut self) -> OutputError {
-        OutputOkExt::unwrap_err(self)
+    /// Lines are up to 80 characters long, including empty ones.  See
+    /// [`Command::write_stdin_random`].
+    pub fn write_stdin_random_lines(&mut self, lines: usize, seed: u64) -> &mut Self {
+        const MAX_LINE: u64 = 80;
+
+        let mut rng = SplitMix64(seed);
+        let mut buffer = Vec::new();
+        for _ in 0..lines {
+            let len = rng.next() % (MAX_LINE + 1);
+            buffer.extend((0..len).map(|_| b' ' + (rng.next() % 95) as u8));
+            buffer.push(b'\n');
+        }
+        self.write_stdin(buffer);
+        self.stdin_seed = Some(seed);
+        self
     }
 
-    /// Run a `Command` and make assertions on the [`Output`].
+    /// Write each chunk to `stdin` when the `Command` is run, pausing after it for the given
+    /// duration.
+    ///
+    /// This exercises programs sensitive to when input arrives, like ones that debounce input.
+    /// `stdin` is closed after the last pause.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// let mut cmd = Command::cargo_bin("bin_fixture")
-    ///     .unwrap()
+    /// use std::time::Duration;
+    ///
+    /// Command::new("cat")
+    ///     .write_stdin_chunks([
+    ///         ("line1\n", Duration::from_millis(100)),
+    ///         ("line2\n", Duration::ZERO),
+    ///     ])
     ///     .assert()
-    ///     .success();
+    ///     .stdout("line1\nline2\n");
     /// ```
-    ///
-    /// [`Output`]: std::process::Output
-    pub fn assert(&mut self) -> Assert {
-        OutputAssertExt::assert(self)
+    pub fn write_stdin_chunks<I, S>(&mut self, chunks: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (S, std::time::Duration)>,
+        S: AsRef<[u8]>,
+    {
+        let mut buffer = Vec::new();
+        let mut pauses = Vec::new();
+        for (chunk, pause) in chunks {
+            buffer.extend_from_slice(chunk.as_ref());
+            if !pause.is_zero() {
+                pauses.push((buffer.len(), pause));
+            }
+        }
+        self.write_stdin(buffer);
+        self.stdin_pauses = pauses;
+        self
     }
-}
 
-/// Mirror [`std::process::Command`]'s API
-impl Command {
-    /// Constructs a new `Command` for launching the program at
-    /// path `program`, with the following default configuration:
+    /// Close `stdin` after writing `len` bytes of what was given to [`Command::write_stdin`],
+    /// so the command sees an early EOF.
     ///
-    /// * No arguments to the program
-    /// * Inherit the current process's environment
-    /// * Inherit the current process's working directory
-    /// * Inherit stdin/stdout/stderr for `spawn` or `status`, but create pipes for `output`
+    /// # Examples
     ///
-    /// Builder methods are provided to change these defaults and
-    /// otherwise configure the process.
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
     ///
-    /// If `program` is not an absolute path, the `PATH` will be searched in
-    /// an OS-defined way.
+    /// Command::new("cat")
+    ///     .write_stdin("truncated")
+    ///     .close_stdin_after(5)
+    ///     .assert()
+    ///     .stdout("trunc");
+    /// ```
+    pub fn close_stdin_after(&mut self, len: usize) -> &mut Self {
+        self.stdin_limit = Some(len);
+        self
+    }
+
+    /// Run the command with `stdin` closed, so reading from it fails with `EBADF`.
     ///
-    /// The search path to be used may be controlled by setting the
-    /// `PATH` environment variable on the Command,
-    /// but this has some implementation limitations on Windows
-    /// (see issue #37519).
+    /// Like other `pre_exec` hooks, this is lost if the command is later
+    /// [wrapped][Command::wrapped_by].
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
-    /// ```no_run
+    /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// Command::new("sh").unwrap();
+    /// Command::cargo_bin("my-filter")
+    ///     .unwrap()
+    ///     .stdin_closed()
+    ///     .assert()
+    ///     .failure();
     /// ```
-    pub fn new<S: AsRef<ffi::OsStr>>(program: S) -> Self {
-        let cmd = process::Command::new(program);
-        Self::from_std(cmd)
+    #[cfg(unix)]
+    pub fn stdin_closed(&mut self) -> &mut Self {
+        // SAFETY: `close` is async-signal-safe
+        unsafe {
+            self.pre_exec.push(|| {
+                libc::close(libc::STDIN_FILENO);
+                Ok(())
+            });
+        }
+        self
     }
 
-    /// Adds an argument to pass to the program.
+    /// Run the command with a `stdin` that fails every read, with `EISDIR`.
     ///
-    /// Only one argument can be passed per use. So instead of:
+    /// Like other `pre_exec` hooks, this is lost if the command is later
+    /// [wrapped][Command::wrapped_by].
     ///
-    /// ```no_run
-    /// # assert_cmd::Command::new("sh")
-    /// .arg("-C /path/to/repo")
-    /// # ;
-    /// ```
+    /// # Examples
     ///
-    /// usage would be:
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
     ///
-    /// ```no_run
-    /// # assert_cmd::Command::new("sh")
-    /// .arg("-C")
-    /// .arg("/path/to/repo")
-    /// # ;
+    /// Command::cargo_bin("my-filter")
+    ///     .unwrap()
+    ///     .stdin_read_error()
+    ///     .assert()
+    ///     .failure();
     /// ```
+    #[cfg(unix)]
+    pub fn stdin_read_error(&mut self) -> &mut Self {
+        // Reading a directory fails, unlike reading a closed pipe which is only EOF
+        const ROOT: &[u8] = b"/\0";
+        // SAFETY: `open`, `dup2`, and `close` are async-signal-safe, and `ROOT` is nul-terminated
+        unsafe {
+            self.pre_exec.push(|| {
+                let fd = libc::open(ROOT.as_ptr().cast(), libc::O_RDONLY);
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if libc::dup2(fd, libc::STDIN_FILENO) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                libc::close(fd);
+                Ok(())
+            });
+        }
+        self
+    }
+
+    /// Stop reading `stdout` after `len` bytes and close it, so the command's later writes
+    /// fail with `EPIPE` or it is killed by `SIGPIPE`, like the producer in `yes | head`.
     ///
-    /// To pass multiple arguments see [`args`].
-    ///
-    /// [`args`]: Command::args()
+    /// Only the first `len` bytes of `stdout` are captured.
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
-    /// ```no_run
+    /// ```rust,no_run
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .arg("-l")
-    ///         .arg("-a")
-    ///         .unwrap();
+    /// Command::new("yes")
+    ///     .close_stdout_after(4)
+    ///     .assert()
+    ///     .stdout("y\ny\n");
     /// ```
-    pub fn arg<S: AsRef<ffi::OsStr>>(&mut self, arg: S) -> &mut Self {
-        self.cmd.arg(arg);
+    pub fn close_stdout_after(&mut self, len: usize) -> &mut Self {
+        self.stdout_limit = Some(len);
         self
     }
 
-    /// Adds multiple arguments to pass to the program.
+    /// Call `hook` with this command before each time it is spawned.
     ///
-    /// To pass a single argument see [`arg`].
+    /// See the [`hooks` module][crate::hooks] for hooks shared by every command.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .before_spawn(|cmd| {
+    ///         cmd.env("RUST_LOG", "debug");
+    ///     })
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn before_spawn<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&mut Command) + Send + Sync + 'static,
+    {
+        self.hooks = std::mem::take(&mut self.hooks).before_spawn(hook);
+        self
+    }
+
+    /// Pass each [`Assert`] this command produces through `hook` before any assertions are
+    /// made on it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .after_wait(|assert| assert.append_context("fixture", "default"))
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn after_wait<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(Assert) -> Assert + Send + Sync + 'static,
+    {
+        self.hooks = std::mem::take(&mut self.hooks).after_wait(hook);
+        self
+    }
+
+    /// Write each of `messages`, [framed][crate::jsonrpc::frame] with a `Content-Length`
+    /// header, to `stdin` when the `Command` is run.
+    ///
+    /// See the [`jsonrpc` module][crate::jsonrpc] for an example.
+    pub fn write_stdin_jsonrpc<I>(&mut self, messages: I) -> &mut Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let buffer: Vec<u8> = messages
+            .into_iter()
+            .flat_map(crate::jsonrpc::frame)
+            .collect();
+        self.write_stdin(buffer)
+    }
+
+    /// Error out if a timeout is reached
+    ///
+    /// The timeout covers the whole run, including writing to `stdin`.
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .timeout(std::time::Duration::from_secs(1))
+    ///     .env("sleep", "100")
+    ///     .assert();
+    /// assert.failure();
+    /// ```
+    pub fn timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Error out if writing to `stdin` doesn't finish within `timeout`
+    ///
+    /// This distinguishes a command that is slow to consume its input from one that hangs after
+    /// reading it.
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let assert = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .stdin_timeout(std::time::Duration::from_secs(1))
+    ///     .write_stdin(vec![b'a'; 10_000_000])
+    ///     .assert();
+    /// assert.success();
+    /// ```
+    pub fn stdin_timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
+        self.stdin_timeout = Some(timeout);
+        self
+    }
+
+    /// Error out if the command doesn't exit within `timeout` of being killed for exceeding
+    /// [`Command::timeout`] or [`Command::stdin_timeout`]
+    ///
+    /// By default, this waits indefinitely.
+    pub fn kill_timeout(&mut self, timeout: std::time::Duration) -> &mut Self {
+        self.kill_timeout = Some(timeout);
+        self
+    }
+
+    /// Send `signal` to the command once it has run for `after`, like a user pressing Ctrl-C.
+    ///
+    /// This is for checking how the command cleans up when interrupted.  The signal is only sent
+    /// while the command is running, and the command is always waited on, so its cleanup is done
+    /// by the time the [`Assert`] is returned and files can be checked with
+    /// [`Assert::file_absent`].  Only the command itself is signalled, not any children it spawned.
+    ///
+    /// `signal` is a [`Signal`], or a signal number like `libc::SIGINT`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::cmd::Signal;
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("my-downloader")
+    ///     .unwrap()
+    ///     .args(["--out", "target/download"])
+    ///     .send_signal_after(Signal::INT, std::time::Duration::from_millis(500))
+    ///     .timeout(std::time::Duration::from_secs(10))
+    ///     .assert()
+    ///     .code(130)
+    ///     .file_absent("target/download.partial");
+    /// ```
+    #[cfg(unix)]
+    pub fn send_signal_after(
+        &mut self,
+        signal: impl Into<Signal>,
+        after: std::time::Duration,
+    ) -> &mut Self {
+        self.signal = Some((signal.into().as_raw(), after));
+        self
+    }
+
+    /// Warn if the command takes longer than `threshold`, without failing.
+    ///
+    /// Set this below [`Command::timeout`] to notice a command slowing down before it starts
+    /// timing out.  A warning is printed, unless [quiet][crate::assert::set_quiet], and slow runs
+    /// are annotated in assertion failures.
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .warn_after(std::time::Duration::from_secs(2))
+    ///     .timeout(std::time::Duration::from_secs(10))
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn warn_after(&mut self, threshold: std::time::Duration) -> &mut Self {
+        self.warn_after = Some(threshold);
+        self
+    }
+
+    /// Expect the assertions on this command to fail, because of a known bug tracked by `reason`.
+    ///
+    /// Like `xfail`, this keeps a test for a known bug without failing the suite: a failing
+    /// assertion prints a notice, unless [quiet][crate::assert::set_quiet], and the chain
+    /// continues.  Once the bug is fixed and every assertion passes, the [`Assert`] fails as
+    /// unexpectedly passing, as a reminder to remove this.
+    ///
+    /// Only the panicking assertions are affected; `try_` variants still return the failure.
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .arg("--verbose")
+    ///     .expected_failure("tracking issue #123")
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn expected_failure(&mut self, reason: impl Into<String>) -> &mut Self {
+        self.expected_failure = Some(reason.into());
+        self
+    }
+
+    /// Run the `Command` under `locale`, setting `LANG`, `LC_ALL`, and `LANGUAGE` coherently.
+    ///
+    /// If `locale` isn't installed on this host, a message is printed and [`None`] is returned so
+    /// the test can be skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    /// let Some(cmd) = cmd.locale("de_DE.UTF-8") else {
+    ///     return;
+    /// };
+    /// cmd.assert().success();
+    /// ```
+    pub fn locale(&mut self, locale: &str) -> Option<&mut Self> {
+        if !is_locale_installed(locale) {
+            crate::skip::report(format_args!(
+                "locale `{locale}` is not installed (see `locale -a`)"
+            ));
+            return None;
+        }
+        let language = locale.split(['.', '@']).next().unwrap_or(locale);
+        self.env("LANG", locale)
+            .env("LC_ALL", locale)
+            .env("LANGUAGE", language);
+        Some(self)
+    }
+
+    /// Write `path`s content to `stdin` when the `Command` is run.
+    ///
+    /// Paths are relative to the [`env::current_dir`][env_current_dir] and not
+    /// [`Command::current_dir`][Command_current_dir].
+    ///
+    /// [env_current_dir]: std::env::current_dir()
+    /// [Command_current_dir]: std::process::Command::current_dir()
+    pub fn pipe_stdin<P>(&mut self, file: P) -> io::Result<&mut Self>
+    where
+        P: AsRef<path::Path>,
+    {
+        let buffer = fs::read(file)?;
+        Ok(self.write_stdin(buffer))
+    }
+
+    /// Run a `Command`, returning an [`OutputResult`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// let result = Command::new("echo")
+    ///     .args(&["42"])
+    ///     .ok();
+    /// assert!(result.is_ok());
+    /// ```
+    ///
+    pub fn ok(&mut self) -> OutputResult {
+        OutputOkExt::ok(self)
+    }
+
+    /// Run a `Command`, unwrapping the [`OutputResult`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    ///
+    /// let output = Command::new("echo")
+    ///     .args(&["42"])
+    ///     .unwrap();
+    /// ```
+    ///
+    pub fn unwrap(&mut self) -> process::Output {
+        OutputOkExt::unwrap(self)
+    }
+
+    /// Run a `Command`, unwrapping the error in the [`OutputResult`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let err = Command::new("a-command")
+    ///     .args(&["--will-fail"])
+    ///     .unwrap_err();
+    /// ```
+    ///
+    /// [Output]: std::process::Output
+    pub fn unwrap_err(&mut self) -> OutputError {
+        OutputOkExt::unwrap_err(self)
+    }
+
+    /// Run a `Command` and make assertions on the [`Output`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// let mut cmd = Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .assert()
+    ///     .success();
+    /// ```
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn assert(&mut self) -> Assert {
+        OutputAssertExt::assert(self)
+    }
+
+    /// Variant of [`Command::assert`] that returns an [`AssertResult`] rather than panicking
+    /// when the command fails to spawn.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use assert_cmd::Command;
+    /// use assert_cmd::assert::AssertErrorKind;
+    ///
+    /// let err = Command::new("non-existent-command")
+    ///     .try_assert()
+    ///     .unwrap_err();
+    /// assert_eq!(err.kind(), AssertErrorKind::Spawn);
+    /// ```
+    pub fn try_assert(&mut self) -> AssertResult {
+        self.try_assert_run(Capture::Output)
+    }
+
+    /// Run a `Command` without capturing its output and make assertions on its exit status.
+    ///
+    /// `stdout` and `stderr` are discarded, avoiding the cost of capturing a large amount of
+    /// output, so assertions on them will only see empty output.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "a lot of output")
+    ///     .assert_status()
+    ///     .success();
+    /// ```
+    #[track_caller]
+    pub fn assert_status(&mut self) -> Assert {
+        self.try_assert_status().unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Command::assert_status`] that returns an [`AssertResult`] rather than
+    /// panicking when the command fails to spawn.
+    pub fn try_assert_status(&mut self) -> AssertResult {
+        self.try_assert_run(Capture::Status)
+    }
+
+    /// Run a `Command` with `stdout` and `stderr` written to files rather than memory, and
+    /// make assertions on them.
+    ///
+    /// See [`files`][crate::files].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .env("stdout", "a lot of output")
+    ///     .assert_to_files()
+    ///     .success()
+    ///     .stdout_contains("a lot");
+    /// ```
+    #[track_caller]
+    pub fn assert_to_files(&mut self) -> crate::files::FileAssert {
+        self.try_assert_to_files()
+            .unwrap_or_else(AssertError::panic)
+    }
+
+    /// Variant of [`Command::assert_to_files`] that returns an [`AssertError`] rather than
+    /// panicking when the command fails to spawn, or the files for its output can't be created.
+    pub fn try_assert_to_files(&mut self) -> Result<crate::files::FileAssert, AssertError> {
+        let files = crate::files::OutputFiles::new()
+            .map_err(|err| AssertError::uncreatable(std::env::temp_dir(), &err))?;
+        let create = |path: path::PathBuf| {
+            fs::File::create(&path).map_err(|err| AssertError::uncreatable(path, &err))
+        };
+        let stdout = create(files.stdout())?;
+        let stderr = create(files.stderr())?;
+        self.cmd.stdout(stdout);
+        self.cmd.stderr(stderr);
+        let assert = self.try_assert_run(Capture::Redirected)?;
+        Ok(crate::files::FileAssert::new(assert, files))
+    }
+
+    fn try_assert_run(&mut self, capture: Capture) -> AssertResult {
+        // Read before running, so a rebuild while reporting doesn't name the wrong build
+        let binary = self.binary.as_deref().map(crate::cargo::binary_info);
+        if let Some(budget) = crate::budget::exceeded() {
+            return Err(AssertError::deadline(Deadline::Budget(budget)));
+        }
+        let (output, deadline, process) = self.run(capture).map_err(|err| match err {
+            RunError::Io(err) => {
+                AssertError::spawn(SpawnError::new(err).set_cmd(&self.cmd, self.env_cleared))
+            }
+            RunError::Deadline(deadline) => AssertError::deadline(deadline),
+        })?;
+        let assert = Assert::new(output)
+            .set_process(process)
+            .append_context("command", self.render());
+        let assert = match self.cmd.get_current_dir() {
+            Some(dir) => assert.set_current_dir(dir),
+            None => assert,
+        };
+        let assert = match crate::output::env_diff(&self.cmd, self.env_cleared) {
+            Some(diff) => assert.append_context("env changes", diff),
+            None => assert,
+        };
+        let assert = match capture {
+            // Redirected output is described by whoever redirected it
+            Capture::Output | Capture::Redirected => assert,
+            Capture::Status => assert.append_context("output", "not captured"),
+        };
+        let assert = if let Some(stdin) = self.stdin.as_ref() {
+            assert.append_context("stdin", DebugBuffer::new(Arc::clone(stdin)))
+        } else {
+            assert
+        };
+        let assert = match self.stdin_seed {
+            Some(seed) => assert.append_context("stdin seed", seed),
+            None => assert,
+        };
+        let assert = match binary {
+            Some(binary) => assert.append_context("binary", binary),
+            None => assert,
+        };
+        let seed = self
+            .cmd
+            .get_envs()
+            .find(|(key, _)| crate::output::same_env_name(key, SEED_ENV.as_ref()))
+            .and_then(|(_, value)| value);
+        let assert = match seed {
+            Some(seed) => assert.append_context("seed", seed.to_string_lossy().into_owned()),
+            None => assert,
+        };
+        let assert = match deadline {
+            Some(deadline @ Deadline::Kill(_)) => assert
+                .append_context("still running", format!("pid {}", process.pid))
+                .set_deadline(deadline),
+            Some(deadline) => assert.set_deadline(deadline),
+            None => assert,
+        };
+        let elapsed = process.spawn_latency + process.execution_time + process.teardown_time;
+        let assert = match self.warn_after.filter(|t| *t < elapsed) {
+            Some(threshold) => {
+                assert.append_context("slow", format!("took {elapsed:?}, over {threshold:?}"))
+            }
+            None => assert,
+        };
+        #[cfg(unix)]
+        let assert = match self.visible_fds.take() {
+            Some(fds) => assert.set_fds(fds),
+            None => assert,
+        };
+        let assert = match self.signal {
+            Some((signal, after)) => {
+                assert.append_context("signal", format!("{signal} after {after:?}"))
+            }
+            None => assert,
+        };
+        let assert = match &self.expected_failure {
+            Some(reason) => assert.expect_failure(reason.clone()),
+            None => assert,
+        };
+        let assert = crate::hooks::after_wait(assert, &self.hooks);
+        assert.try_stderr_policy()
+    }
+
+    /// Run the command repeatedly until `check` passes, failing with the last attempt once
+    /// `timeout` has elapsed.
+    ///
+    /// This is for commands that observe eventually-consistent state, like a server that is
+    /// still starting up.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// Command::new("curl")
+    ///     .arg("http://localhost:8080/health")
+    ///     .assert_within(Duration::from_secs(5), |assert| {
+    ///         assert.try_success()?.try_stdout("ok")
+    ///     });
+    /// ```
+    #[track_caller]
+    pub fn assert_within<F>(&mut self, timeout: std::time::Duration, check: F) -> Assert
+    where
+        F: FnMut(Assert) -> AssertResult,
+    {
+        self.try_assert_within(timeout, check)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Command::assert_within`] that returns an [`AssertResult`].
+    pub fn try_assert_within<F>(
+        &mut self,
+        timeout: std::time::Duration,
+        mut check: F,
+    ) -> AssertResult
+    where
+        F: FnMut(Assert) -> AssertResult,
+    {
+        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+        let start = std::time::Instant::now();
+        let mut attempts = 0_usize;
+        loop {
+            attempts += 1;
+            let err = match self.try_assert().and_then(&mut check) {
+                Ok(assert) => return Ok(assert),
+                Err(err) => err,
+            };
+            let remaining = timeout.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                return Err(err.append_context("attempts", attempts));
+            }
+            std::thread::sleep(POLL_INTERVAL.min(remaining));
+        }
+    }
+
+    /// Run a command that doesn't exit on its own, like `tail -f`, for `window`, then stop it and
+    /// make assertions on what it wrote meanwhile.
+    ///
+    /// `window` replaces any [`Command::timeout`] for this run.  Reaching it isn't a failure, but
+    /// as the command is killed, its exit status says so; assert on its output instead.  Use
+    /// [`Command::send_signal_after`] to have it stop more gracefully first.  A command that
+    /// exits before `window` is asserted on as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// use std::time::Duration;
+    ///
+    /// Command::new("tail")
+    ///     .args(["-f", "server.log"])
+    ///     .assert_stream_for(Duration::from_secs(2))
+    ///     .stdout(predicates::str::contains("listening"));
+    /// ```
+    #[track_caller]
+    pub fn assert_stream_for(&mut self, window: std::time::Duration) -> Assert {
+        self.try_assert_stream_for(window)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Command::assert_stream_for`] that returns an [`AssertResult`].
+    pub fn try_assert_stream_for(&mut self, window: std::time::Duration) -> AssertResult {
+        let timeout = self.timeout.replace(window);
+        let result = self.try_assert_run(Capture::Output);
+        self.timeout = timeout;
+        result.map(|assert| assert.set_sampled(window))
+    }
+
+    /// Run the command twice and ensure the second run's output is identical to the first's,
+    /// returning the second run.
+    ///
+    /// This is for commands meant to converge, like formatters or migrations, run against the
+    /// same working directory.  See [`Command::assert_idempotent_by`] to compare differently.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("my-fmt")
+    ///     .unwrap()
+    ///     .current_dir("tests/fixtures/project")
+    ///     .assert_idempotent()
+    ///     .success();
+    /// ```
+    #[track_caller]
+    pub fn assert_idempotent(&mut self) -> Assert {
+        self.try_assert_idempotent()
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Command::assert_idempotent`] that returns an [`AssertResult`].
+    pub fn try_assert_idempotent(&mut self) -> AssertResult {
+        self.try_assert_idempotent_by(|first, second| first == second)
+    }
+
+    /// Run the command twice and ensure `same` considers the runs' outputs equivalent, returning
+    /// the second run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// // The first run may reformat files, the second must not
+    /// Command::cargo_bin("my-fmt")
+    ///     .unwrap()
+    ///     .current_dir("tests/fixtures/project")
+    ///     .assert_idempotent_by(|_first, second| second.stdout.is_empty())
+    ///     .success();
+    /// ```
+    #[track_caller]
+    pub fn assert_idempotent_by<F>(&mut self, same: F) -> Assert
+    where
+        F: FnOnce(&process::Output, &process::Output) -> bool,
+    {
+        self.try_assert_idempotent_by(same)
+            .unwrap_or_else(AssertError::settle)
+    }
+
+    /// Variant of [`Command::assert_idempotent_by`] that returns an [`AssertResult`].
+    pub fn try_assert_idempotent_by<F>(&mut self, same: F) -> AssertResult
+    where
+        F: FnOnce(&process::Output, &process::Output) -> bool,
+    {
+        let first = self.try_assert()?;
+        let second = self.try_assert()?;
+        let same = same(first.get_output(), second.get_output());
+        second.try_same_as(&first, same)
+    }
+
+    /// Run the command `iterations` times, up to `parallelism` at a time, ensuring every run
+    /// succeeds.
+    ///
+    /// Failures are aggregated, counting each distinct cause and showing the first few in full.
+    ///
+    /// Each run gets a copy of the command's program, arguments, environment, working directory,
+    /// `stdin`, limits, and process settings, like `uid`, `arg0`, and CPU affinity, and runs its
+    /// `pre_exec` hooks.  Inherited descriptors and listeners are shared by every run.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("my-tool")
+    ///     .unwrap()
+    ///     .args(["append", "--lock", "target/shared.lock"])
+    ///     .stress(8, 100);
+    /// ```
+    #[track_caller]
+    pub fn stress(&self, parallelism: usize, iterations: usize) {
+        self.try_stress(parallelism, iterations)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    /// `try_` variant of [`Command::stress`].
+    pub fn try_stress(
+        &self,
+        parallelism: usize,
+        iterations: usize,
+    ) -> Result<(), crate::stress::StressError> {
+        self.try_stress_by(parallelism, iterations, Assert::try_success)
+    }
+
+    /// Run the command `iterations` times, up to `parallelism` at a time, passing each run to
+    /// `check`.
+    ///
+    /// See [`Command::stress`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("my-server")
+    ///     .unwrap()
+    ///     .args(["--port", "0", "--exit-after-bind"])
+    ///     .stress_by(4, 50, |assert| assert.try_success()?.try_stderr(""));
+    /// ```
+    #[track_caller]
+    pub fn stress_by<F>(&self, parallelism: usize, iterations: usize, check: F)
+    where
+        F: Fn(Assert) -> AssertResult + Sync,
+    {
+        self.try_stress_by(parallelism, iterations, check)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    /// `try_` variant of [`Command::stress_by`].
+    pub fn try_stress_by<F>(
+        &self,
+        parallelism: usize,
+        iterations: usize,
+        check: F,
+    ) -> Result<(), crate::stress::StressError>
+    where
+        F: Fn(Assert) -> AssertResult + Sync,
+    {
+        let runs = (0..iterations).map(|_| self.replicate()).collect();
+        crate::stress::run(runs, parallelism, check)
+    }
+
+    /// Copy what can be copied of this command, to run it concurrently.
+    fn replicate(&self) -> Self {
+        let mut cmd = process::Command::new(self.cmd.get_program());
+        cmd.args(self.cmd.get_args());
+        if self.env_cleared {
+            cmd.env_clear();
+        }
+        for (key, value) in self.cmd.get_envs() {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+        if let Some(dir) = self.cmd.get_current_dir() {
+            cmd.current_dir(dir);
+        }
+        let mut replica = Self::from_std(cmd);
+        replica.env_cleared = self.env_cleared;
+        replica.stdin = self.stdin.clone();
+        replica.stdin_pauses = self.stdin_pauses.clone();
+        replica.stdin_seed = self.stdin_seed;
+        replica.stdin_limit = self.stdin_limit;
+        replica.stdout_limit = self.stdout_limit;
+        replica.timeout = self.timeout;
+        replica.stdin_timeout = self.stdin_timeout;
+        replica.kill_timeout = self.kill_timeout;
+        replica.warn_after = self.warn_after;
+        replica.signal = self.signal;
+        replica.expected_failure.clone_from(&self.expected_failure);
+        replica.wrapper_exit_code = self.wrapper_exit_code;
+        replica.binary.clone_from(&self.binary);
+        replica.hooks = self.hooks.clone();
+        #[cfg(windows)]
+        {
+            replica.affinity = self.affinity;
+            if let Some(flags) = self.creation_flags {
+                replica.creation_flags(flags);
+            }
+        }
+        #[cfg(unix)]
+        {
+            if let Some(id) = self.uid {
+                replica.uid(id);
+            }
+            if let Some(id) = self.gid {
+                replica.gid(id);
+            }
+            if let Some(arg) = &self.arg0 {
+                replica.arg0(arg);
+            }
+            if let Some(pgroup) = self.process_group {
+                replica.process_group(pgroup);
+            }
+            // `self` isn't changed while the replicas run, as it is borrowed
+            replica.pre_exec =
+                Arc::new(crate::pre_exec::PreExec::after(Arc::clone(&self.pre_exec)));
+            replica.fds = Arc::new(self.fds.copy());
+            replica.report_fds = self.report_fds;
+            replica.listeners.clone_from(&self.listeners);
+            replica.inherited.clone_from(&self.inherited);
+        }
+        replica
+    }
+}
+
+/// Mirror [`std::process::Command`]'s API
+impl Command {
+    /// Constructs a new `Command` for launching the program at
+    /// path `program`, with the following default configuration:
+    ///
+    /// * No arguments to the program
+    /// * Inherit the current process's environment
+    /// * Inherit the current process's working directory
+    /// * Inherit stdin/stdout/stderr for `spawn` or `status`, but create pipes for `output`
+    ///
+    /// Builder methods are provided to change these defaults and
+    /// otherwise configure the process.
+    ///
+    /// If `program` is not an absolute path, the `PATH` will be searched in
+    /// an OS-defined way.
+    ///
+    /// The search path to be used may be controlled by setting the
+    /// `PATH` environment variable on the Command,
+    /// but this has some implementation limitations on Windows
+    /// (see issue #37519).
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("sh").unwrap();
+    /// ```
+    pub fn new<S: AsRef<ffi::OsStr>>(program: S) -> Self {
+        let cmd = process::Command::new(program);
+        Self::from_std(cmd)
+    }
+
+    /// Adds an argument to pass to the program.
+    ///
+    /// Only one argument can be passed per use. So instead of:
+    ///
+    /// ```no_run
+    /// # assert_cmd::Command::new("sh")
+    /// .arg("-C /path/to/repo")
+    /// # ;
+    /// ```
+    ///
+    /// usage would be:
+    ///
+    /// ```no_run
+    /// # assert_cmd::Command::new("sh")
+    /// .arg("-C")
+    /// .arg("/path/to/repo")
+    /// # ;
+    /// ```
+    ///
+    /// To pass multiple arguments see [`args`].
+    ///
+    /// [`args`]: Command::args()
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .arg("-l")
+    ///         .arg("-a")
+    ///         .unwrap();
+    /// ```
+    pub fn arg<S: AsRef<ffi::OsStr>>(&mut self, arg: S) -> &mut Self {
+        self.cmd.arg(arg);
+        self
+    }
+
+    /// Adds multiple arguments to pass to the program.
+    ///
+    /// To pass a single argument see [`arg`].
+    ///
+    /// [`arg`]: Command::arg()
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .args(&["-l", "-a"])
+    ///         .unwrap();
+    /// ```
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<ffi::OsStr>,
+    {
+        self.cmd.args(args);
+        self
+    }
+
+    /// Inserts or updates an environment variable mapping.
+    ///
+    /// Note that environment variable names are case-insensitive (but case-preserving) on Windows,
+    /// and case-sensitive on all other platforms.  A warning is printed, unless
+    /// [quiet][crate::assert::set_quiet], for a name that differs only by case from one already
+    /// set or inherited, as a test relying on either behavior won't pass everywhere.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .env("PATH", "/bin")
+    ///         .unwrap_err();
+    /// ```
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    where
+        K: AsRef<ffi::OsStr>,
+        V: AsRef<ffi::OsStr>,
+    {
+        self.warn_env_case(key.as_ref());
+        self.cmd.env(key, val);
+        self
+    }
+
+    /// Adds or updates multiple environment variable mappings.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    /// use std::process::Stdio;
+    /// use std::env;
+    /// use std::collections::HashMap;
+    ///
+    /// let filtered_env : HashMap<String, String> =
+    ///     env::vars().filter(|&(ref k, _)|
+    ///         k == "TERM" || k == "TZ" || k == "LANG" || k == "PATH"
+    ///     ).collect();
+    ///
+    /// Command::new("printenv")
+    ///         .env_clear()
+    ///         .envs(&filtered_env)
+    ///         .unwrap();
+    /// ```
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<ffi::OsStr>,
+        V: AsRef<ffi::OsStr>,
+    {
+        for (key, val) in vars {
+            self.env(key, val);
+        }
+        self
+    }
+
+    /// Removes an environment variable mapping.
+    ///
+    /// Like [`Command::env`], names are case-insensitive on Windows, so removing `Path` also
+    /// removes `PATH` there, and a warning is printed when the two could be confused.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .env_remove("PATH")
+    ///         .unwrap_err();
+    /// ```
+    pub fn env_remove<K: AsRef<ffi::OsStr>>(&mut self, key: K) -> &mut Self {
+        self.warn_env_case(key.as_ref());
+        self.cmd.env_remove(key);
+        self
+    }
+
+    /// Render the command line for reports, see [`crate::output::render_command`].
+    fn render(&self) -> String {
+        crate::output::render_command(&self.cmd, self.env_cleared)
+    }
+
+    /// Warn if `key` differs only by case from a variable already set or inherited, as whether
+    /// they are the same variable depends on the platform.
+    fn warn_env_case(&self, key: &ffi::OsStr) {
+        if crate::assert::is_quiet() {
+            return;
+        }
+        if let Some(other) = self.env_case_collision(key) {
+            // `eprintln!` is captured by the test harness, unlike writes to `io::stderr()`
+            #[allow(clippy::print_stderr)]
+            {
+                eprintln!(
+                    "warning: environment variable `{}` differs only by case from `{}`, \
+                     which is the same variable on Windows but not elsewhere",
+                    key.to_string_lossy(),
+                    other.to_string_lossy()
+                );
+            }
+        }
+    }
+
+    /// A variable already set, or inherited, whose name differs from `key` only by case.
+    ///
+    /// Only the upper and lower case spellings of `key` are looked up in this process's
+    /// environment, to avoid copying all of it on each call.
+    fn env_case_collision(&self, key: &ffi::OsStr) -> Option<ffi::OsString> {
+        if let Some((name, _)) = self
+            .cmd
+            .get_envs()
+            .find(|(name, _)| *name != key && name.eq_ignore_ascii_case(key))
+        {
+            return Some(name.to_owned());
+        }
+        if self.env_cleared {
+            return None;
+        }
+        [key.to_ascii_uppercase(), key.to_ascii_lowercase()]
+            .into_iter()
+            .find(|name| name != key && std::env::var_os(name).is_some())
+    }
+
+    /// Clears the entire environment map for the child process.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .env_clear()
+    ///         .unwrap_err();
+    /// ```
+    pub fn env_clear(&mut self) -> &mut Self {
+        self.cmd.env_clear();
+        self.env_cleared = true;
+        self
+    }
+
+    /// Set conventional environment variables so programs that honor them produce
+    /// reproducible output:
+    /// - `SOURCE_DATE_EPOCH` to [`SOURCE_DATE_EPOCH`], freezing the time
+    /// - [`SEED_ENV`] to `seed`
+    /// - `TZ` to `UTC`
+    ///
+    /// The seed is included in failure output so a failing run can be reproduced.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .deterministic(42)
+    ///     .assert()
+    ///     .success();
+    /// ```
+    pub fn deterministic(&mut self, seed: u64) -> &mut Self {
+        self.cmd
+            .env("SOURCE_DATE_EPOCH", SOURCE_DATE_EPOCH.to_string())
+            .env(SEED_ENV, seed.to_string())
+            .env("TZ", "UTC");
+        self
+    }
+
+    /// Sets the working directory for the child process.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// If the program path is relative (e.g., `"./script.sh"`), it's ambiguous
+    /// whether it should be interpreted relative to the parent's working
+    /// directory or relative to `current_dir`. The behavior in this case is
+    /// platform specific and unstable, and it's recommended to use
+    /// [`canonicalize`] to get an absolute program path instead.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```no_run
+    /// use assert_cmd::Command;
+    ///
+    /// Command::new("ls")
+    ///         .current_dir("/bin")
+    ///         .unwrap();
+    /// ```
+    ///
+    /// [`canonicalize`]: std::fs::canonicalize()
+    pub fn current_dir<P: AsRef<path::Path>>(&mut self, dir: P) -> &mut Self {
+        self.cmd.current_dir(dir);
+        self
+    }
+
+    /// Run the command in a new temporary directory, returned alongside it.
+    ///
+    /// The directory is deleted when the [`TempDir`][assert_fs::TempDir] is dropped, so keep it
+    /// until done asserting on the files the command wrote, see [`Assert::child_path`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use assert_cmd::Command;
+    /// use assert_fs::prelude::*;
+    ///
+    /// let (mut cmd, temp) = Command::cargo_bin("my-tool")
+    ///     .unwrap()
+    ///     .with_temp_dir()
+    ///     .unwrap();
+    /// temp.child("input.txt").write_str("hello").unwrap();
+    /// cmd.arg("input.txt").assert().success();
+    /// temp.child("input.txt.bak").assert("hello");
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn with_temp_dir(
+        mut self,
+    ) -> Result<(Self, assert_fs::TempDir), assert_fs::fixture::FixtureError> {
+        let temp = assert_fs::TempDir::new()?;
+        self.current_dir(temp.path());
+        Ok((self, temp))
+    }
+
+    /// Sets the child process's user ID.
+    ///
+    /// See [`std::os::unix::process::CommandExt::uid`].
+    #[cfg(unix)]
+    pub fn uid(&mut self, id: u32) -> &mut Self {
+        use std::os::unix::process::CommandExt;
+        self.cmd.uid(id);
+        self.uid = Some(id);
+        self
+    }
+
+    /// Similar to `uid`, but sets the group ID of the child process.
+    ///
+    /// See [`std::os::unix::process::CommandExt::gid`].
+    #[cfg(unix)]
+    pub fn gid(&mut self, id: u32) -> &mut Self {
+        use std::os::unix::process::CommandExt;
+        self.cmd.gid(id);
+        self.gid = Some(id);
+        self
+    }
+
+    /// Set the first process argument, `argv[0]`, to something other than the default
+    /// executable path.
     ///
-    /// [`arg`]: Command::arg()
+    /// See [`std::os::unix::process::CommandExt::arg0`].
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
     /// ```no_run
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .args(&["-l", "-a"])
-    ///         .unwrap();
+    /// Command::new("busybox")
+    ///     .arg0("ls")
+    ///     .assert()
+    ///     .success();
     /// ```
-    pub fn args<I, S>(&mut self, args: I) -> &mut Self
-    where
-        I: IntoIterator<Item = S>,
-        S: AsRef<ffi::OsStr>,
-    {
-        self.cmd.args(args);
+    #[cfg(unix)]
+    pub fn arg0<S: AsRef<ffi::OsStr>>(&mut self, arg: S) -> &mut Self {
+        use std::os::unix::process::CommandExt;
+        self.cmd.arg0(&arg);
+        self.arg0 = Some(arg.as_ref().to_owned());
         self
     }
 
-    /// Inserts or updates an environment variable mapping.
-    ///
-    /// Note that environment variable names are case-insensitive (but case-preserving) on Windows,
-    /// and case-sensitive on all other platforms.
+    /// Schedules a closure to be run just before the `exec` function is invoked.
     ///
-    /// # Examples
+    /// Hooks are lost if the command is later [wrapped][Command::wrapped_by].
     ///
-    /// Basic usage:
+    /// See [`std::os::unix::process::CommandExt::pre_exec`].
     ///
-    /// ```no_run
-    /// use assert_cmd::Command;
+    /// # Safety
     ///
-    /// Command::new("ls")
-    ///         .env("PATH", "/bin")
-    ///         .unwrap_err();
-    /// ```
-    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Self
+    /// See [`std::os::unix::process::CommandExt::pre_exec`].
+    #[cfg(unix)]
+    pub unsafe fn pre_exec<F>(&mut self, f: F) -> &mut Self
     where
-        K: AsRef<ffi::OsStr>,
-        V: AsRef<ffi::OsStr>,
+        F: FnMut() -> io::Result<()> + Send + Sync + 'static,
     {
-        self.cmd.env(key, val);
+        // SAFETY: the caller upholds `pre_exec`'s requirements
+        unsafe {
+            self.pre_exec.push(f);
+        }
         self
     }
 
-    /// Adds or updates multiple environment variable mappings.
+    /// Sets the process group ID (PGID) of the child process.
     ///
-    /// # Examples
+    /// See [`std::os::unix::process::CommandExt::process_group`].
     ///
-    /// Basic usage:
+    /// # Examples
     ///
     /// ```no_run
     /// use assert_cmd::Command;
-    /// use std::process::Stdio;
-    /// use std::env;
-    /// use std::collections::HashMap;
-    ///
-    /// let filtered_env : HashMap<String, String> =
-    ///     env::vars().filter(|&(ref k, _)|
-    ///         k == "TERM" || k == "TZ" || k == "LANG" || k == "PATH"
-    ///     ).collect();
     ///
-    /// Command::new("printenv")
-    ///         .env_clear()
-    ///         .envs(&filtered_env)
-    ///         .unwrap();
+    /// // Run in a new process group, away from the test harness's signals
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .process_group(0)
+    ///     .assert()
+    ///     .success();
     /// ```
-    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Self
-    where
-        I: IntoIterator<Item = (K, V)>,
-        K: AsRef<ffi::OsStr>,
-        V: AsRef<ffi::OsStr>,
-    {
-        self.cmd.envs(vars);
+    #[cfg(unix)]
+    pub fn process_group(&mut self, pgroup: i32) -> &mut Self {
+        use std::os::unix::process::CommandExt;
+        self.cmd.process_group(pgroup);
+        self.process_group = Some(pgroup);
         self
     }
 
-    /// Removes an environment variable mapping.
+    /// Sets the [process creation flags][1] to be passed to `CreateProcess`.
     ///
-    /// # Examples
-    ///
-    /// Basic usage:
-    ///
-    /// ```no_run
-    /// use assert_cmd::Command;
+    /// See [`std::os::windows::process::CommandExt::creation_flags`].
     ///
-    /// Command::new("ls")
-    ///         .env_remove("PATH")
-    ///         .unwrap_err();
-    /// ```
-    pub fn env_remove<K: AsRef<ffi::OsStr>>(&mut self, key: K) -> &mut Self {
-        self.cmd.env_remove(key);
+    /// [1]: https://docs.microsoft.com/en-us/windows/win32/procthread/process-creation-flags
+    #[cfg(windows)]
+    pub fn creation_flags(&mut self, flags: u32) -> &mut Self {
+        use std::os::windows::process::CommandExt;
+        self.cmd.creation_flags(flags);
+        self.creation_flags = Some(flags);
         self
     }
 
-    /// Clears the entire environment map for the child process.
+    /// Pin the command to the CPU cores `cpus`, so timing is less affected by other load.
     ///
-    /// # Examples
+    /// Running the command fails with [`io::ErrorKind::InvalidInput`] if `cpus` is empty or
+    /// names a core the system can't have.
     ///
-    /// Basic usage:
+    /// Like other `pre_exec` hooks, this is lost if the command is later
+    /// [wrapped][Command::wrapped_by].
+    ///
+    /// # Examples
     ///
     /// ```no_run
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .env_clear()
-    ///         .unwrap_err();
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .cpu_affinity([0])
+    ///     .assert()
+    ///     .success();
     /// ```
-    pub fn env_clear(&mut self) -> &mut Self {
-        self.cmd.env_clear();
+    #[cfg(target_os = "linux")]
+    pub fn cpu_affinity(&mut self, cpus: impl IntoIterator<Item = usize>) -> &mut Self {
+        // Built before forking, as the child may only make async-signal-safe calls
+        // SAFETY: `cpu_set_t` is plain data, for which all zeroes is the empty set
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        let mut valid = true;
+        let mut empty = true;
+        for cpu in cpus {
+            if libc::CPU_SETSIZE as usize <= cpu {
+                valid = false;
+            } else {
+                // SAFETY: `cpu` is within the set
+                unsafe { libc::CPU_SET(cpu, &mut set) };
+                empty = false;
+            }
+        }
+        let valid = valid && !empty;
+        // `size_of_val` is only in the prelude since Rust 1.80
+        #[allow(unused_qualifications)]
+        let size = std::mem::size_of_val(&set);
+        // SAFETY: `sched_setaffinity` is async-signal-safe
+        unsafe {
+            self.pre_exec.push(move || {
+                if !valid {
+                    return Err(io::Error::from_raw_os_error(libc::EINVAL));
+                }
+                if libc::sched_setaffinity(0, size, &set) < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
         self
     }
 
-    /// Sets the working directory for the child process.
+    /// Pin the command to the CPU cores `cpus`, so timing is less affected by other load.
     ///
-    /// # Platform-specific behavior
+    /// Running the command fails with [`io::ErrorKind::InvalidInput`] if `cpus` is empty or
+    /// names a core the system can't have.
     ///
-    /// If the program path is relative (e.g., `"./script.sh"`), it's ambiguous
-    /// whether it should be interpreted relative to the parent's working
-    /// directory or relative to `current_dir`. The behavior in this case is
-    /// platform specific and unstable, and it's recommended to use
-    /// [`canonicalize`] to get an absolute program path instead.
+    /// The process is pinned just after it is spawned, as stable `std` doesn't expose the handle
+    /// of its first thread to start it suspended, so it may briefly run, and start threads or
+    /// children, on other cores.  Only the first 64 cores can be named.
     ///
     /// # Examples
     ///
-    /// Basic usage:
-    ///
     /// ```no_run
     /// use assert_cmd::Command;
     ///
-    /// Command::new("ls")
-    ///         .current_dir("/bin")
-    ///         .unwrap();
+    /// Command::cargo_bin("bin_fixture")
+    ///     .unwrap()
+    ///     .cpu_affinity([0])
+    ///     .assert()
+    ///     .success();
     /// ```
-    ///
-    /// [`canonicalize`]: std::fs::canonicalize()
-    pub fn current_dir<P: AsRef<path::Path>>(&mut self, dir: P) -> &mut Self {
-        self.cmd.current_dir(dir);
+    #[cfg(windows)]
+    pub fn cpu_affinity(&mut self, cpus: impl IntoIterator<Item = usize>) -> &mut Self {
+        let mut mask = 0usize;
+        let mut valid = true;
+        for cpu in cpus {
+            match u32::try_from(cpu)
+                .ok()
+                .and_then(|cpu| 1usize.checked_shl(cpu))
+            {
+                Some(bit) => mask |= bit,
+                None => valid = false,
+            }
+        }
+        // An empty mask is rejected when spawning
+        self.affinity = Some(if valid { mask } else { 0 });
         self
     }
 
@@ -413,6 +1966,10 @@ impl Command {
     /// Stdin is not inherited from the parent and any attempt by the child process to read from
     /// the stdin stream will result in the stream immediately closing.
     ///
+    /// A command that exceeds a [timeout][Command::timeout] is killed, and what it wrote until
+    /// then is returned along with the killed status.  Use [`Command::assert`] to be told which
+    /// timeout was exceeded.
+    ///
     /// # Examples
     ///
     /// ```should_panic
@@ -430,8 +1987,99 @@ impl Command {
     /// assert!(output.status.success());
     /// ```
     pub fn output(&mut self) -> io::Result<process::Output> {
-        let spawn = self.spawn()?;
-        Self::wait_with_input_output(spawn, self.stdin.as_deref().cloned(), self.timeout)
+        match self.run(Capture::Output) {
+            Ok((output, _, _)) => Ok(output),
+            Err(RunError::Io(err)) => Err(err),
+            // The child couldn't be reaped, so there is no status to report
+            Err(RunError::Deadline(deadline)) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                deadline.to_string(),
+            )),
+        }
+    }
+
+    /// Like [`Command::output`] but also reports which [`Deadline`], if any, was exceeded.
+    fn run(
+        &mut self,
+        capture: Capture,
+    ) -> Result<(process::Output, Option<Deadline>, ProcessInfo), RunError> {
+        let hooks = std::mem::take(&mut self.hooks);
+        crate::hooks::before_spawn(self, &hooks);
+        self.hooks = hooks;
+
+        crate::hooks::command_started(&self.cmd, self.env_cleared, &self.hooks);
+        let start = std::time::Instant::now();
+        let result = self.run_unrecorded(capture);
+        let elapsed = start.elapsed();
+        crate::budget::record(elapsed);
+        let status = result.as_ref().ok().map(|(output, _, _)| output.status);
+        crate::journal::record(&self.cmd, elapsed, status);
+        crate::hooks::command_finished(&self.cmd, self.env_cleared, elapsed, status, &self.hooks);
+        if let Some(threshold) = self.warn_after.filter(|t| *t < elapsed) {
+            if !crate::assert::is_quiet() {
+                // `eprintln!` is captured by the test harness, unlike writes to `io::stderr()`
+                #[allow(clippy::print_stderr)]
+                {
+                    eprintln!(
+                        "warning: took {elapsed:?}, over {threshold:?}: {}",
+                        self.render()
+                    );
+                }
+            }
+        }
+        result
+    }
+
+    fn run_unrecorded(
+        &mut self,
+        capture: Capture,
+    ) -> Result<(process::Output, Option<Deadline>, ProcessInfo), RunError> {
+        let started = std::time::SystemTime::now();
+        let spawning = std::time::Instant::now();
+        let spawn = self.spawn(capture)?;
+        let spawned = std::time::Instant::now();
+        let pid = spawn.id();
+        // Stop at the end of the budget, if that comes before the timeout
+        let remaining = crate::budget::remaining()
+            .filter(|remaining| self.timeout.map(|t| *remaining < t).unwrap_or(true));
+        let deadlines = Deadlines {
+            run: remaining.or(self.timeout),
+            stdin: self.stdin_timeout,
+            kill: self.kill_timeout,
+            signal: self.signal,
+        };
+        let input = self.stdin.clone().map(|data| match self.stdin_limit {
+            Some(limit) if limit < data.len() => {
+                let data = Arc::new(bstr::BString::from(&data[..limit]));
+                let pauses = self
+                    .stdin_pauses
+                    .iter()
+                    .copied()
+                    .filter(|(offset, _)| *offset <= limit)
+                    .collect();
+                Input::new(data, pauses)
+            }
+            _ => Input::new(data, self.stdin_pauses.clone()),
+        });
+        let (mut output, mut deadline, exited) =
+            Self::wait_with_input_output(spawn, input, self.stdout_limit, deadlines)?;
+        if let (Some(Deadline::Run(_)), Some(_)) = (deadline, remaining) {
+            deadline = crate::budget::budget().map(Deadline::Budget);
+        }
+        if let (Some(map), Some(code)) = (self.wrapper_exit_code, output.status.code()) {
+            if let Some(status) = exit_status_from_code(map(code)) {
+                output.status = status;
+            }
+        }
+        let process = ProcessInfo {
+            pid,
+            started,
+            finished: std::time::SystemTime::now(),
+            spawn_latency: spawned - spawning,
+            execution_time: exited - spawned,
+            teardown_time: exited.elapsed(),
+        };
+        Ok((output, deadline, process))
     }
 
     /// If `input`, write it to `child`'s stdin while also reading `child`'s
@@ -439,66 +2087,215 @@ impl Command {
     ///
     /// This was lifted from `std::process::Child::wait_with_output` and modified
     /// to also write to stdin.
+    ///
+    /// Returns the [`Deadline`] alongside the output if `child` was killed for exceeding it, and
+    /// when `child` was reaped.
     fn wait_with_input_output(
         mut child: process::Child,
-        input: Option<Vec<u8>>,
-        timeout: Option<std::time::Duration>,
-    ) -> io::Result<process::Output> {
-        #![allow(clippy::unwrap_used)] // changes behavior in some tests
-
-        fn read<R>(mut input: R) -> std::thread::JoinHandle<io::Result<Vec<u8>>>
-        where
-            R: Read + Send + 'static,
-        {
-            std::thread::spawn(move || {
-                let mut ret = Vec::new();
-                input.read_to_end(&mut ret).map(|_| ret)
-            })
-        }
+        input: Option<Input>,
+        stdout_limit: Option<usize>,
+        deadlines: Deadlines,
+    ) -> Result<(process::Output, Option<Deadline>, std::time::Instant), RunError> {
+        let start = std::time::Instant::now();
+        let run_limit = deadlines.run.map(|run| start + run);
 
-        let stdin = input.and_then(|i| {
-            child
-                .stdin
-                .take()
-                .map(|mut stdin| std::thread::spawn(move || stdin.write_all(&i)))
-        });
-        let stdout = child.stdout.take().map(read);
-        let stderr = child.stderr.take().map(read);
+        let mut pump = Pump::new(&mut child, input, stdout_limit)?;
 
         // Finish writing stdin before waiting, because waiting drops stdin.
-        stdin.and_then(|t| t.join().unwrap().ok());
-        let status = if let Some(timeout) = timeout {
-            wait_timeout::ChildExt::wait_timeout(&mut child, timeout)
-                .transpose()
-                .unwrap_or_else(|| {
-                    let _ = child.kill();
-                    child.wait()
-                })
-        } else {
-            child.wait()
-        }?;
-
-        let stdout = stdout
-            .and_then(|t| t.join().unwrap().ok())
-            .unwrap_or_default();
-        let stderr = stderr
-            .and_then(|t| t.join().unwrap().ok())
-            .unwrap_or_default();
+        let stdin_deadline = match (deadlines.stdin, deadlines.run) {
+            (Some(stdin), Some(run)) if run < stdin => Some(Deadline::Run(run)),
+            (Some(stdin), _) => Some(Deadline::Stdin(stdin)),
+            (None, Some(run)) => Some(Deadline::Run(run)),
+            (None, None) => None,
+        };
+        let mut exceeded = None;
+        if !pump.write_stdin(stdin_deadline.map(|d| start + d.duration()))? {
+            exceeded = stdin_deadline;
+        }
+        // Signal before the timeout, if it is due first, while `child` is known not to be reaped
+        let mut signal = deadlines
+            .signal
+            .map(|(signal, after)| (signal, start + after))
+            .filter(|(_, at)| run_limit.map(|run| *at < run).unwrap_or(true));
+        if let Some((number, at)) = signal.filter(|_| exceeded.is_none()) {
+            if !pump.read_to_end(Some(at))? {
+                send_signal(&child, number)?;
+                signal = None;
+            }
+        }
+        if exceeded.is_none() && !pump.read_to_end(run_limit)? {
+            exceeded = deadlines.run.map(Deadline::Run);
+        }
+        // `child` may close its output before exiting
+        if let Some((number, at)) = signal.filter(|_| exceeded.is_none()) {
+            let wait = at.saturating_duration_since(std::time::Instant::now());
+            if wait_timeout::ChildExt::wait_timeout(&mut child, wait)?.is_none() {
+                send_signal(&child, number)?;
+            }
+        }
+        let status = match (exceeded, deadlines.run) {
+            (Some(_), _) => None,
+            (None, Some(run)) => {
+                let remaining = run.saturating_sub(start.elapsed());
+                let status = wait_timeout::ChildExt::wait_timeout(&mut child, remaining)?;
+                if status.is_none() {
+                    exceeded = Some(Deadline::Run(run));
+                }
+                status
+            }
+            (None, None) => Some(child.wait()?),
+        };
+        let mut orphaned = false;
+        let status = match status {
+            Some(status) => status,
+            None => {
+                let _ = child.kill();
+                pump.close_stdin();
+                let status = match deadlines.kill {
+                    Some(kill) => {
+                        wait_timeout::ChildExt::wait_timeout(&mut child, kill)?.ok_or(kill)
+                    }
+                    None => Ok(child.wait()?),
+                };
+                // Collect whatever was written before the child was killed
+                let drain_limit = deadlines.kill.map(|kill| std::time::Instant::now() + kill);
+                pump.read_to_end(drain_limit)?;
+                match status {
+                    Ok(status) => status,
+                    Err(kill) => {
+                        exceeded = Some(Deadline::Kill(kill));
+                        orphaned = true;
+                        killed_status().ok_or(RunError::Deadline(Deadline::Kill(kill)))?
+                    }
+                }
+            }
+        };
+        let exited = std::time::Instant::now();
 
-        Ok(process::Output {
-            status,
-            stdout,
-            stderr,
-        })
+        let (stdout, stderr) = pump.finish();
+        if orphaned {
+            // Reap `child` whenever it does exit, rather than leave a zombie behind
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+        }
+        Ok((
+            process::Output {
+                status,
+                stdout,
+                stderr,
+            },
+            exceeded,
+            exited,
+        ))
     }
 
-    fn spawn(&mut self) -> io::Result<process::Child> {
+    fn spawn(&mut self, capture: Capture) -> io::Result<process::Child> {
         // stdout/stderr should only be piped for `output` according to `process::Command::new`.
         self.cmd.stdin(process::Stdio::piped());
-        self.cmd.stdout(process::Stdio::piped());
-        self.cmd.stderr(process::Stdio::piped());
+        match capture {
+            Capture::Output => {
+                self.cmd.stdout(process::Stdio::piped());
+                self.cmd.stderr(process::Stdio::piped());
+            }
+            Capture::Status => {
+                self.cmd.stdout(process::Stdio::null());
+                self.cmd.stderr(process::Stdio::null());
+            }
+            // Configured by the caller
+            Capture::Redirected => {}
+        }
+
+        #[cfg(unix)]
+        {
+            self.spawn_with_fds()
+        }
+        #[cfg(not(unix))]
+        {
+            self.spawn_pinned()
+        }
+    }
+
+    /// Spawn, then pin to the [`Command::cpu_affinity`] cores on Windows.
+    #[cfg(not(unix))]
+    fn spawn_pinned(&mut self) -> io::Result<process::Child> {
+        #[allow(unused_mut)]
+        let mut child = self.cmd.spawn()?;
+        #[cfg(windows)]
+        if let Some(mask) = self.affinity {
+            if let Err(err) = set_affinity(&child, mask) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(err);
+            }
+        }
+        Ok(child)
+    }
 
-        self.cmd.spawn()
+    /// Spawn, applying and reporting the [`FdPolicy`][crate::fds::FdPolicy].
+    #[cfg(unix)]
+    fn spawn_with_fds(&mut self) -> io::Result<process::Child> {
+        let report = if self.report_fds {
+            Some(self.fds.start_report()?)
+        } else {
+            None
+        };
+        self.prepare_exec()?;
+        let child = crate::fds::spawn(&mut self.cmd);
+        self.fds.stop_report();
+        self.visible_fds = report.map(|(read, write)| {
+            // Close our write end so reading stops once the child's copy is closed by `exec`
+            drop(write);
+            crate::fds::read_report(read)
+        });
+        child
+    }
+
+    /// Add the only `pre_exec` hook `std` runs, if needed, and prepare to set `LISTEN_PID` for
+    /// [`Command::inherit_listener`].
+    ///
+    /// The hook runs [`Command::pre_exec`] hooks, even those added after it, then applies the
+    /// [`FdPolicy`][crate::fds::FdPolicy], and last `exec`s with `LISTEN_PID` set.
+    #[cfg(unix)]
+    fn prepare_exec(&mut self) -> io::Result<()> {
+        use std::os::unix::process::CommandExt;
+
+        if !self.exec_hook && (!self.pre_exec.is_empty() || self.fds.is_active()) {
+            let pre_exec = Arc::clone(&self.pre_exec);
+            let fds = Arc::clone(&self.fds);
+            let activation = Arc::clone(&self.activation);
+            // SAFETY: the hooks, `FdPolicy::apply`, and `Activation::exec` only make
+            // async-signal-safe calls
+            unsafe {
+                self.cmd.pre_exec(move || {
+                    pre_exec.run()?;
+                    fds.apply()?;
+                    activation.exec()
+                });
+            }
+            self.exec_hook = true;
+        }
+        if self.listeners.is_empty() {
+            return Ok(());
+        }
+        self.activation.prepare(&self.cmd, self.env_cleared)
+    }
+
+    /// Spawn without waiting, for [`Scope::spawn`][crate::scope::Scope::spawn].
+    pub(crate) fn spawn_background(&mut self) -> io::Result<process::Child> {
+        self.cmd
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null());
+        #[cfg(unix)]
+        {
+            self.prepare_exec()?;
+            crate::fds::spawn(&mut self.cmd)
+        }
+        #[cfg(not(unix))]
+        {
+            self.spawn_pinned()
+        }
     }
 
     /// Returns the path to the program that was given to [`Command::new`].
@@ -598,6 +2395,264 @@ impl Command {
     }
 }
 
+/// A signal for [`Command::send_signal_after`].
+///
+/// Common signals are constants, so tests don't need `libc`.  Others can be converted from their
+/// number.
+#[cfg(unix)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Signal(i32);
+
+#[cfg(unix)]
+impl Signal {
+    /// `SIGINT`, sent by Ctrl-C.
+    pub const INT: Self = Self(libc::SIGINT);
+    /// `SIGTERM`, sent by `kill` and service managers to stop gracefully.
+    pub const TERM: Self = Self(libc::SIGTERM);
+    /// `SIGHUP`, sent when the terminal closes, and often used to reload configuration.
+    pub const HUP: Self = Self(libc::SIGHUP);
+    /// `SIGQUIT`, sent by `Ctrl-\`.
+    pub const QUIT: Self = Self(libc::SIGQUIT);
+    /// `SIGKILL`, which can't be caught.
+    pub const KILL: Self = Self(libc::SIGKILL);
+    /// `SIGUSR1`, whose meaning is up to the program.
+    pub const USR1: Self = Self(libc::SIGUSR1);
+    /// `SIGUSR2`, whose meaning is up to the program.
+    pub const USR2: Self = Self(libc::SIGUSR2);
+
+    /// The signal's number.
+    pub fn as_raw(self) -> i32 {
+        self.0
+    }
+}
+
+#[cfg(unix)]
+impl From<i32> for Signal {
+    fn from(signal: i32) -> Self {
+        Self(signal)
+    }
+}
+
+/// When and as what process a [`Command`] ran.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ProcessInfo {
+    pub(crate) pid: u32,
+    pub(crate) started: std::time::SystemTime,
+    pub(crate) finished: std::time::SystemTime,
+    /// How long spawning took, until the program started running.
+    pub(crate) spawn_latency: std::time::Duration,
+    /// From the program starting to it exiting and being reaped.
+    pub(crate) execution_time: std::time::Duration,
+    /// From the program being reaped to its output being collected.
+    pub(crate) teardown_time: std::time::Duration,
+}
+
+/// What to collect from a run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Capture {
+    /// `stdout` and `stderr`, along with the exit status.
+    Output,
+    /// Only the exit status, discarding `stdout` and `stderr`.
+    Status,
+    /// Only the exit status, with `stdout` and `stderr` already redirected.
+    Redirected,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Deadlines {
+    run: Option<std::time::Duration>,
+    stdin: Option<std::time::Duration>,
+    kill: Option<std::time::Duration>,
+    /// [`Command::send_signal_after`]
+    signal: Option<(i32, std::time::Duration)>,
+}
+
+/// A [`Command`] timeout that was exceeded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Deadline {
+    /// [`Command::stdin_timeout`]
+    Stdin(std::time::Duration),
+    /// [`Command::timeout`]
+    Run(std::time::Duration),
+    /// [`Command::kill_timeout`]
+    Kill(std::time::Duration),
+    /// [`budget`][crate::budget]
+    Budget(std::time::Duration),
+}
+
+impl Deadline {
+    fn duration(self) -> std::time::Duration {
+        match self {
+            Self::Stdin(timeout)
+            | Self::Run(timeout)
+            | Self::Kill(timeout)
+            | Self::Budget(timeout) => timeout,
+        }
+    }
+}
+
+impl std::fmt::Display for Deadline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdin(timeout) => write!(f, "Timed out writing to stdin after {timeout:?}"),
+            Self::Run(timeout) => write!(f, "Command timed out after {timeout:?}"),
+            Self::Kill(timeout) => {
+                write!(f, "Command did not exit within {timeout:?} of being killed")
+            }
+            Self::Budget(budget) => write!(
+                f,
+                "Commands in this test process exceeded their budget of {budget:?}"
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum RunError {
+    Io(io::Error),
+    Deadline(Deadline),
+}
+
+impl From<io::Error> for RunError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// [SplitMix64](https://prng.di.unimi.it/splitmix64.c), a small generator whose output is the
+/// same everywhere for a seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// The descriptor [`Command::inherit_listener`] passes the first listener as.
+#[cfg(unix)]
+const FIRST_LISTENER_FD: usize = 3;
+
+#[cfg(unix)]
+fn listener_conflict(fd: std::os::unix::io::RawFd) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("descriptor {fd} would be both an inherited listener and an inherited descriptor"),
+    )
+}
+
+#[cfg(unix)]
+fn send_signal(child: &process::Child, signal: i32) -> io::Result<()> {
+    let pid = libc::pid_t::try_from(child.id()).map_err(io::Error::other)?;
+    // SAFETY: `child` hasn't been reaped, so `pid` can't have been reused
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Signals are only set on Unix.
+#[cfg(not(unix))]
+fn send_signal(_child: &process::Child, _signal: i32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn exit_status_from_code(code: i32) -> Option<process::ExitStatus> {
+    use std::os::unix::process::ExitStatusExt;
+
+    // The code is the second byte of the wait status
+    Some(process::ExitStatus::from_raw((code & 0xff) << 8))
+}
+
+#[cfg(windows)]
+fn exit_status_from_code(code: i32) -> Option<process::ExitStatus> {
+    use std::os::windows::process::ExitStatusExt;
+
+    Some(process::ExitStatus::from_raw(code as u32))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn exit_status_from_code(_code: i32) -> Option<process::ExitStatus> {
+    None
+}
+
+/// Pin `child` to the CPU cores in the bit `mask`.
+///
+/// This is after `child` started running, see [`Command::cpu_affinity`].
+#[cfg(windows)]
+fn set_affinity(child: &process::Child, mask: usize) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetProcessAffinityMask(process: *mut ffi::c_void, mask: usize) -> i32;
+    }
+
+    if mask == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no CPU cores to run on",
+        ));
+    }
+    // SAFETY: the handle stays valid for as long as `child` is borrowed
+    if unsafe { SetProcessAffinityMask(child.as_raw_handle(), mask) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// The status of a child that was killed but hasn't exited yet.
+#[cfg(unix)]
+fn killed_status() -> Option<process::ExitStatus> {
+    use std::os::unix::process::ExitStatusExt;
+
+    Some(process::ExitStatus::from_raw(libc::SIGKILL))
+}
+
+/// The status of a child that was killed but hasn't exited yet.
+#[cfg(not(unix))]
+fn killed_status() -> Option<process::ExitStatus> {
+    // `Child::kill` terminates with code 1
+    exit_status_from_code(1)
+}
+
+/// Whether `locale` can be used on this host.
+///
+/// Where installed locales can't be listed, this optimistically returns `true`.
+fn is_locale_installed(locale: &str) -> bool {
+    static INSTALLED: std::sync::OnceLock<Option<Vec<String>>> = std::sync::OnceLock::new();
+
+    fn normalize(locale: &str) -> String {
+        // `de_DE.UTF-8` is listed as `de_DE.utf8`
+        locale.to_lowercase().replace('-', "")
+    }
+
+    if matches!(locale, "C" | "POSIX" | "C.UTF-8" | "C.utf8") {
+        return true;
+    }
+    let installed = INSTALLED.get_or_init(|| {
+        let output = process::Command::new("locale").arg("-a").output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let installed = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(normalize)
+            .collect();
+        Some(installed)
+    });
+    match installed {
+        Some(installed) => installed.contains(&normalize(locale)),
+        None => true,
+    }
+}
+
 impl From<process::Command> for Command {
     fn from(cmd: process::Command) -> Self {
         Command::from_std(cmd)
@@ -610,9 +2665,9 @@ impl OutputOkExt for &mut Command {
         if output.status.success() {
             Ok(output)
         } else {
-            let error = OutputError::new(output).set_cmd(format!("{:?}", self.cmd));
+            let error = OutputError::new(output).set_cmd(self.render());
             let error = if let Some(stdin) = self.stdin.as_ref() {
-                error.set_stdin(stdin.deref().clone())
+                error.set_shared_stdin(Arc::clone(stdin))
             } else {
                 error
             };
@@ -625,15 +2680,15 @@ impl OutputOkExt for &mut Command {
             Ok(output) => {
                 if let Some(stdin) = self.stdin.as_ref() {
                     panic!(
-                        "Completed successfully:\ncommand=`{:?}`\nstdin=```{}```\nstdout=```{}```",
-                        self.cmd,
+                        "Completed successfully:\ncommand=`{}`\nstdin=```{}```\nstdout=```{}```",
+                        self.render(),
                         DebugBytes::new(stdin),
                         DebugBytes::new(&output.stdout)
                     )
                 } else {
                     panic!(
-                        "Completed successfully:\ncommand=`{:?}`\nstdout=```{}```",
-                        self.cmd,
+                        "Completed successfully:\ncommand=`{}`\nstdout=```{}```",
+                        self.render(),
                         DebugBytes::new(&output.stdout)
                     )
                 }
@@ -645,17 +2700,32 @@ impl OutputOkExt for &mut Command {
 
 impl OutputAssertExt for &mut Command {
     fn assert(self) -> Assert {
-        let output = match self.output() {
-            Ok(output) => output,
-            Err(err) => {
-                panic!("Failed to spawn {self:?}: {err}");
-            }
-        };
-        let assert = Assert::new(output).append_context("command", format!("{:?}", self.cmd));
-        if let Some(stdin) = self.stdin.as_ref() {
-            assert.append_context("stdin", DebugBuffer::new(stdin.deref().clone()))
-        } else {
-            assert
-        }
+        self.try_assert().unwrap_or_else(AssertError::settle)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn env_case_collision() {
+        let mut cmd = Command::new("prog");
+        cmd.env("ASSERT_CMD_CASE", "1");
+        assert_eq!(
+            cmd.env_case_collision(ffi::OsStr::new("assert_cmd_case")),
+            Some("ASSERT_CMD_CASE".into())
+        );
+        assert_eq!(
+            cmd.env_case_collision(ffi::OsStr::new("ASSERT_CMD_CASE")),
+            None
+        );
+
+        assert_eq!(
+            cmd.env_case_collision(ffi::OsStr::new("path")),
+            Some("PATH".into())
+        );
+        cmd.env_clear();
+        assert_eq!(cmd.env_case_collision(ffi::OsStr::new("path")), None);
     }
 }
@@ -1,15 +1,118 @@
+//! Customize how failures are styled.
+//!
+//! Styling only applies with the `color` feature; `NO_COLOR` and non-terminal output are still
+//! honored when printing.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use assert_cmd::color::Style;
+//! use assert_cmd::color::Theme;
+//!
+//! Theme::new()
+//!     .key(Style::new().underline())
+//!     .value(Style::new())
+//!     .predicate_color(false)
+//!     .install();
+//! # Theme::uninstall();
+//! ```
+
+pub use anstyle::Style;
+
+/// Styles for failure messages, installed process-wide with [`Theme::install`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    key: Style,
+    value: Style,
+    predicate_color: bool,
+}
+
+static THEME: std::sync::Mutex<Option<Theme>> = std::sync::Mutex::new(None);
+
+impl Theme {
+    /// The default theme.
+    pub fn new() -> Self {
+        Self {
+            key: anstyle::AnsiColor::Blue.on_default() | anstyle::Effects::BOLD,
+            value: anstyle::AnsiColor::Yellow.on_default() | anstyle::Effects::BOLD,
+            predicate_color: true,
+        }
+    }
+
+    /// A theme without any styling.
+    pub fn plain() -> Self {
+        Self {
+            key: Style::new(),
+            value: Style::new(),
+            predicate_color: false,
+        }
+    }
+
+    /// Style for names, like `stdout` or `command`.
+    pub fn key(mut self, style: Style) -> Self {
+        self.key = style;
+        self
+    }
+
+    /// Style for the values of names, like the captured `stdout`.
+    pub fn value(mut self, style: Style) -> Self {
+        self.value = style;
+        self
+    }
+
+    /// Keep the styling [`predicates`] applies to failed predicates, like diffs.
+    ///
+    /// When `false`, the predicate's description is printed without styling.
+    pub fn predicate_color(mut self, yes: bool) -> Self {
+        self.predicate_color = yes;
+        self
+    }
+
+    /// Use this theme for all later failures in this process.
+    pub fn install(self) {
+        *THEME
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(self);
+    }
+
+    /// Restore the default theme.
+    pub fn uninstall() {
+        *THEME
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+
+    pub(crate) fn predicate_color_enabled(&self) -> bool {
+        self.predicate_color
+    }
+
+    pub(crate) fn installed() -> Self {
+        THEME
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .unwrap_or_default()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default)]
 pub(crate) struct Palette {
-    key: anstyle::Style,
-    value: anstyle::Style,
+    key: Style,
+    value: Style,
 }
 
 impl Palette {
     pub(crate) fn color() -> Self {
         if cfg!(feature = "color") {
+            let theme = Theme::installed();
             Self {
-                key: anstyle::AnsiColor::Blue.on_default() | anstyle::Effects::BOLD,
-                value: anstyle::AnsiColor::Yellow.on_default() | anstyle::Effects::BOLD,
+                key: theme.key,
+                value: theme.value,
             }
         } else {
             Self::plain()
@@ -32,11 +135,11 @@ impl Palette {
 #[derive(Debug)]
 pub(crate) struct Styled<D> {
     display: D,
-    style: anstyle::Style,
+    style: Style,
 }
 
 impl<D: std::fmt::Display> Styled<D> {
-    pub(crate) fn new(display: D, style: anstyle::Style) -> Self {
+    pub(crate) fn new(display: D, style: Style) -> Self {
         Self { display, style }
     }
 }
@@ -0,0 +1,187 @@
+//! Check a binary stays compatible with a reference build, like the last release.
+//!
+//! Each case runs both binaries the same way and compares their `stdout`, `stderr`, and exit
+//! codes.  Intentional changes can be waived per case.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::compat::Compat;
+//! use assert_cmd::compat::Part;
+//!
+//! // CI downloads the last release and points `ASSERT_CMD_REFERENCE_BIN` at it
+//! let Some(compat) = Compat::from_env(assert_cmd::cargo::cargo_bin("mytool")) else {
+//!     return;
+//! };
+//! compat
+//!     .waive("version", Part::Stdout)
+//!     .case("version", |cmd| {
+//!         cmd.arg("--version");
+//!     })
+//!     .case("list", |cmd| {
+//!         cmd.args(["list", "--json"]);
+//!     });
+//! ```
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write as _;
+use std::path;
+use std::process;
+
+use crate::output::DebugBytes;
+use crate::Command;
+
+/// Environment variable for the reference binary's path, read by [`Compat::from_env`].
+pub const REFERENCE_BIN_ENV: &str = "ASSERT_CMD_REFERENCE_BIN";
+
+/// Paired runs of a reference and a candidate binary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compat {
+    reference: path::PathBuf,
+    candidate: path::PathBuf,
+    waivers: Vec<(String, Part)>,
+}
+
+impl Compat {
+    /// Compare `candidate` against `reference`.
+    pub fn new(reference: impl Into<path::PathBuf>, candidate: impl Into<path::PathBuf>) -> Self {
+        Self {
+            reference: reference.into(),
+            candidate: candidate.into(),
+            waivers: Vec::new(),
+        }
+    }
+
+    /// Compare `candidate` against the binary at [`REFERENCE_BIN_ENV`].
+    ///
+    /// If it isn't set, a message is printed and [`None`] is returned so the test can be skipped.
+    pub fn from_env(candidate: impl Into<path::PathBuf>) -> Option<Self> {
+        match env::var_os(REFERENCE_BIN_ENV).filter(|p| !p.is_empty()) {
+            Some(reference) => Some(Self::new(reference, candidate)),
+            None => {
+                crate::skip::report(format_args!(
+                    "`{REFERENCE_BIN_ENV}` is not set, no reference binary"
+                ));
+                None
+            }
+        }
+    }
+
+    /// Allow `part` to differ in the case `name`.
+    pub fn waive(mut self, name: impl Into<String>, part: Part) -> Self {
+        self.waivers.push((name.into(), part));
+        self
+    }
+
+    /// Run both binaries, configured by `configure`, and ensure they behave the same.
+    #[track_caller]
+    pub fn case<F>(&self, name: &str, configure: F) -> &Self
+    where
+        F: Fn(&mut Command),
+    {
+        self.try_case(name, configure)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`Compat::case`].
+    pub fn try_case<F>(&self, name: &str, configure: F) -> Result<&Self, CompatError>
+    where
+        F: Fn(&mut Command),
+    {
+        let run = |program: &path::Path| {
+            let mut cmd = Command::new(program);
+            configure(&mut cmd);
+            cmd.output().map_err(|err| {
+                CompatError::new(format!(
+                    "case `{name}`: failed to run `{}`: {err}",
+                    program.display()
+                ))
+            })
+        };
+        let reference = run(&self.reference)?;
+        let candidate = run(&self.candidate)?;
+
+        let mut message = String::new();
+        for part in [Part::Code, Part::Stdout, Part::Stderr] {
+            let waived = self
+                .waivers
+                .iter()
+                .any(|(waived, waived_part)| waived == name && *waived_part == part);
+            if waived {
+                continue;
+            }
+            let (expected, actual) = (part.render(&reference), part.render(&candidate));
+            if expected != actual {
+                let _ = writeln!(
+                    message,
+                    "{part} differs\n  reference: {expected}\n  candidate: {actual}"
+                );
+            }
+        }
+        if message.is_empty() {
+            Ok(self)
+        } else {
+            Err(CompatError::new(format!(
+                "case `{name}` is incompatible with `{}`:\n{message}",
+                self.reference.display()
+            )))
+        }
+    }
+}
+
+/// Part of a run compared by [`Compat`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Part {
+    /// The exit code
+    Code,
+    /// `stdout`
+    Stdout,
+    /// `stderr`
+    Stderr,
+}
+
+impl Part {
+    fn render(self, output: &process::Output) -> String {
+        match self {
+            Self::Code => output
+                .status
+                .code()
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "<interrupted>".to_owned()),
+            Self::Stdout => DebugBytes::new(&output.stdout).to_string(),
+            Self::Stderr => DebugBytes::new(&output.stderr).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Code => f.write_str("code"),
+            Self::Stdout => f.write_str("stdout"),
+            Self::Stderr => f.write_str("stderr"),
+        }
+    }
+}
+
+/// [`Compat`] assertion failure.
+#[derive(Debug)]
+pub struct CompatError {
+    message: String,
+}
+
+impl CompatError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl Error for CompatError {}
+
+impl fmt::Display for CompatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::redact::write(f, &self.message)
+    }
+}
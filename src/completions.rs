@@ -0,0 +1,112 @@
+//! Check shell completion scripts, like those printed by `mytool completions bash`.
+//!
+//! See [`Assert::completion_script`][crate::assert::Assert::completion_script].
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::completions::Shell;
+//!
+//! for shell in Shell::all() {
+//!     Command::cargo_bin("mytool")
+//!         .unwrap()
+//!         .args(["completions", shell.name()])
+//!         .assert()
+//!         .completion_script(shell);
+//! }
+//! ```
+
+use std::fmt;
+use std::io;
+
+use bstr::ByteSlice;
+
+use crate::Command;
+
+/// A shell that completion scripts can be written for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Shell {
+    /// GNU Bash
+    Bash,
+    /// Z shell
+    Zsh,
+    /// Friendly interactive shell
+    Fish,
+}
+
+impl Shell {
+    /// Every supported shell.
+    pub fn all() -> [Self; 3] {
+        [Self::Bash, Self::Zsh, Self::Fish]
+    }
+
+    /// The shell's program name, as typically passed to a `completions` subcommand.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Zsh => "zsh",
+            Self::Fish => "fish",
+        }
+    }
+
+    /// Arguments to parse a script from `stdin` without running it.
+    fn syntax_check_args(self) -> &'static [&'static str] {
+        match self {
+            Self::Bash | Self::Zsh => &["-n"],
+            Self::Fish => &["--no-execute"],
+        }
+    }
+
+    /// What every completion script for this shell contains.
+    fn marker(self) -> &'static str {
+        match self {
+            Self::Bash | Self::Fish => "complete",
+            Self::Zsh => "compdef",
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Describe why `script` isn't a valid completion script for `shell`, if it isn't.
+///
+/// If `shell` is installed, the script is parsed by it.  Otherwise only lightweight checks are
+/// made, and a message is printed.
+pub(crate) fn check(shell: Shell, script: &[u8]) -> Option<String> {
+    if script.trim().is_empty() {
+        return Some(format!(
+            "expected a {shell} completion script but found nothing"
+        ));
+    }
+    if !script.contains_str(shell.marker()) {
+        return Some(format!(
+            "expected a {shell} completion script, which would contain `{}`",
+            shell.marker()
+        ));
+    }
+    let output = Command::new(shell.name())
+        .args(shell.syntax_check_args())
+        .write_stdin(script)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => None,
+        Ok(output) => Some(format!(
+            "`{shell} {}` rejected the completion script: {}",
+            shell.syntax_check_args().join(" "),
+            output.stderr.trim().as_bstr()
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            crate::skip::report(format_args!(
+                "`{shell}` is not installed, not parsing its completion script"
+            ));
+            None
+        }
+        Err(err) => Some(format!("failed to run `{shell}`: {err}")),
+    }
+}
@@ -0,0 +1,261 @@
+//! Control and report which file descriptors a child inherits.
+//!
+//! The policy is applied by a single `pre_exec` hook, so it only uses atomics to be safe to read
+//! after `fork`.
+
+use std::fs;
+use std::io;
+use std::io::Read as _;
+use std::os::unix::io::AsRawFd as _;
+use std::os::unix::io::FromRawFd as _;
+use std::os::unix::io::OwnedFd;
+use std::os::unix::io::RawFd;
+use std::process;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+/// Descriptors at or above this can't be kept open by [`FdPolicy::keep`].
+pub(crate) const MAX_KEPT_FD: RawFd = 128;
+
+/// Don't scan beyond this many descriptors, even if the limit is higher.
+const MAX_SCANNED_FDS: RawFd = 65536;
+
+/// Held for writing while a descriptor is open but not yet close-on-exec, and for reading by
+/// each [`spawn`], so no child started by this crate inherits it.
+static SPAWN_LOCK: RwLock<()> = RwLock::new(());
+
+/// Spawn `cmd`, without inheriting descriptors being set up by other threads.
+pub(crate) fn spawn(cmd: &mut process::Command) -> io::Result<process::Child> {
+    let _spawning = SPAWN_LOCK.read().unwrap_or_else(PoisonError::into_inner);
+    cmd.spawn()
+}
+
+#[derive(Debug)]
+pub(crate) struct FdPolicy {
+    close: AtomicBool,
+    keep: [AtomicU64; 2],
+    /// Where to report the inherited descriptors, or `-1`.
+    report: AtomicI32,
+    /// One past the highest descriptor that may be open.
+    limit: RawFd,
+}
+
+impl FdPolicy {
+    pub(crate) fn new() -> Self {
+        // SAFETY: `sysconf` has no preconditions
+        let limit = unsafe { libc::sysconf(libc::_SC_OPEN_MAX) };
+        let limit = RawFd::try_from(limit)
+            .ok()
+            .filter(|limit| 0 < *limit)
+            .unwrap_or(MAX_SCANNED_FDS)
+            .min(MAX_SCANNED_FDS);
+        Self {
+            close: AtomicBool::new(false),
+            keep: [AtomicU64::new(0), AtomicU64::new(0)],
+            report: AtomicI32::new(-1),
+            limit,
+        }
+    }
+
+    /// A policy closing and keeping the same descriptors, for another command.
+    pub(crate) fn copy(&self) -> Self {
+        Self {
+            close: AtomicBool::new(self.close.load(Ordering::Relaxed)),
+            keep: [
+                AtomicU64::new(self.keep[0].load(Ordering::Relaxed)),
+                AtomicU64::new(self.keep[1].load(Ordering::Relaxed)),
+            ],
+            report: AtomicI32::new(-1),
+            limit: self.limit,
+        }
+    }
+
+    pub(crate) fn set_close(&self, yes: bool) {
+        self.close.store(yes, Ordering::Relaxed);
+    }
+
+    /// Pass `fd` to the child, even when closing others.
+    pub(crate) fn keep(&self, fd: RawFd) -> io::Result<()> {
+        let index = usize::try_from(fd)
+            .ok()
+            .filter(|_| fd < MAX_KEPT_FD)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("only descriptors below {MAX_KEPT_FD} can be inherited, not {fd}"),
+                )
+            })?;
+        self.keep[index / 64].fetch_or(1 << (index % 64), Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn is_kept(&self, fd: RawFd) -> bool {
+        match usize::try_from(fd) {
+            Ok(index) if fd < MAX_KEPT_FD => {
+                self.keep[index / 64].load(Ordering::Relaxed) & (1 << (index % 64)) != 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this needs a `pre_exec` hook at all.
+    pub(crate) fn is_active(&self) -> bool {
+        self.close.load(Ordering::Relaxed)
+            || self.report.load(Ordering::Relaxed) >= 0
+            || self.keep.iter().any(|k| k.load(Ordering::Relaxed) != 0)
+    }
+
+    /// Create a pipe for the next child to report its inherited descriptors to.
+    ///
+    /// Returns the read and write ends.  Once the child has been spawned, drop the write end and
+    /// pass the read end to [`read_report`].
+    pub(crate) fn start_report(&self) -> io::Result<(OwnedFd, OwnedFd)> {
+        let (read, write) = cloexec_pipe()?;
+        let fds = [read.as_raw_fd(), write.as_raw_fd()];
+        // Drop what doesn't fit rather than block the child before it can `exec`
+        // SAFETY: `fds[1]` is open
+        if unsafe { libc::fcntl(fds[1], libc::F_SETFL, libc::O_NONBLOCK) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        self.report.store(fds[1], Ordering::Relaxed);
+        Ok((read, write))
+    }
+
+    pub(crate) fn stop_report(&self) {
+        self.report.store(-1, Ordering::Relaxed);
+    }
+
+    /// Apply the policy in the child, just before `exec`.
+    ///
+    /// This must only make async-signal-safe calls.
+    pub(crate) fn apply(&self) -> io::Result<()> {
+        let report = self.report.load(Ordering::Relaxed);
+        for fd in 0..MAX_KEPT_FD {
+            if self.is_kept(fd) {
+                // SAFETY: `fcntl` is async-signal-safe and fails harmlessly on closed descriptors
+                unsafe { libc::fcntl(fd, libc::F_SETFD, 0) };
+            }
+        }
+        if self.close.load(Ordering::Relaxed) {
+            for fd in 3..self.limit {
+                if fd != report && !self.is_kept(fd) && is_inherited(fd) {
+                    // SAFETY: `close` is async-signal-safe
+                    unsafe { libc::close(fd) };
+                }
+            }
+        }
+        if 0 <= report {
+            for fd in (0..self.limit).filter(|fd| *fd != report && is_inherited(*fd)) {
+                let mut buffer = [0_u8; 12];
+                let len = format_fd(fd, &mut buffer);
+                // SAFETY: `write` is async-signal-safe and `buffer` has `len` bytes
+                unsafe { libc::write(report, buffer.as_ptr().cast(), len) };
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Open a pipe whose ends won't be inherited by any child.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "solaris",
+))]
+fn cloexec_pipe() -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for both ends
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `pipe2` just opened these and nothing else owns them
+    Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) })
+}
+
+/// Open a pipe whose ends won't be inherited by any child spawned by this crate.
+///
+/// Without `pipe2`, the ends are briefly inheritable, so [`spawn`]s wait until they aren't.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "solaris",
+)))]
+fn cloexec_pipe() -> io::Result<(OwnedFd, OwnedFd)> {
+    let _opening = SPAWN_LOCK.write().unwrap_or_else(PoisonError::into_inner);
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for both ends
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `pipe` just opened these and nothing else owns them
+    let ends = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+    for fd in fds {
+        // SAFETY: `fd` is open
+        if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(ends)
+}
+
+/// Whether `fd` is open and will survive `exec`.
+fn is_inherited(fd: RawFd) -> bool {
+    // SAFETY: `fcntl` is async-signal-safe
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    0 <= flags && flags & libc::FD_CLOEXEC == 0
+}
+
+/// Write `fd` and a newline to `buffer` without allocating, returning the length.
+fn format_fd(fd: RawFd, buffer: &mut [u8; 12]) -> usize {
+    let mut digits = [0_u8; 10];
+    let mut remaining = fd.unsigned_abs();
+    let mut count = 0;
+    loop {
+        digits[count] = b'0' + (remaining % 10) as u8;
+        count += 1;
+        remaining /= 10;
+        if remaining == 0 {
+            break;
+        }
+    }
+    for (i, digit) in digits[..count].iter().rev().enumerate() {
+        buffer[i] = *digit;
+    }
+    buffer[count] = b'\n';
+    count + 1
+}
+
+/// Read the descriptors a spawned child reported.
+pub(crate) fn read_report(read: OwnedFd) -> Vec<RawFd> {
+    let mut report = String::new();
+    let _ = fs::File::from(read).read_to_string(&mut report);
+    report.lines().filter_map(|fd| fd.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format() {
+        let mut buffer = [0; 12];
+        let len = format_fd(0, &mut buffer);
+        assert_eq!(&buffer[..len], b"0\n");
+        let len = format_fd(1024, &mut buffer);
+        assert_eq!(&buffer[..len], b"1024\n");
+    }
+}
@@ -0,0 +1,402 @@
+//! Capture output to files rather than memory, for commands that write a lot.
+//!
+//! Assertions stream from the files, so memory use stays flat however much is written.  On
+//! failure, the report shows the start and end of each file along with its path; the files are
+//! kept so they can be inspected.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", "a lot of output")
+//!     .assert_to_files()
+//!     .success()
+//!     .stdout_contains("a lot")
+//!     .stdout_each_line("a lot of output");
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Seek;
+use std::path;
+use std::process;
+
+use bstr::ByteSlice;
+use predicates_tree::CaseTreeExt;
+
+use crate::assert::Assert;
+use crate::assert::AssertError;
+use crate::assert::AssertResult;
+use crate::assert::IntoCodePredicate;
+use crate::assert::IntoOutputPredicate;
+use crate::assert::Stream;
+use crate::output::DebugBytes;
+
+/// How much of the start and end of each file to show on failure.
+const EXCERPT_LEN: u64 = 512;
+
+/// The files a command's `stdout` and `stderr` were written to.
+///
+/// They are removed when this is dropped, unless [kept][OutputFiles::keep].
+#[derive(Debug)]
+pub(crate) struct OutputFiles {
+    dir: path::PathBuf,
+    keep: bool,
+}
+
+impl OutputFiles {
+    pub(crate) fn new() -> io::Result<Self> {
+        let dir = crate::temp::create_dir("output")?;
+        Ok(Self { dir, keep: false })
+    }
+
+    pub(crate) fn stdout(&self) -> path::PathBuf {
+        self.dir.join("stdout")
+    }
+
+    pub(crate) fn stderr(&self) -> path::PathBuf {
+        self.dir.join("stderr")
+    }
+
+    /// Leave the files behind, so the paths in a failure report can be inspected.
+    fn keep(&mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for OutputFiles {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+/// Assert the status and file-backed output of a command.
+///
+/// Created with [`Command::assert_to_files`][crate::Command::assert_to_files].  Failures are
+/// [`AssertError`]s, reported like [`Assert`]'s.
+///
+/// The files are removed when this is dropped, unless an assertion failed.
+#[derive(Debug)]
+pub struct FileAssert {
+    /// Only `None` while an assertion is being made.
+    assert: Option<Assert>,
+    files: OutputFiles,
+}
+
+impl FileAssert {
+    pub(crate) fn new(assert: Assert, files: OutputFiles) -> Self {
+        let assert = assert
+            .append_context("stdout file", files.stdout().display().to_string())
+            .append_context("stdout content", Excerpt(files.stdout()))
+            .append_context("stderr file", files.stderr().display().to_string())
+            .append_context("stderr content", Excerpt(files.stderr()));
+        Self {
+            assert: Some(assert),
+            files,
+        }
+    }
+
+    fn assert(&self) -> &Assert {
+        self.assert.as_ref().expect("only taken while asserting")
+    }
+
+    /// The command's exit status.
+    pub fn status(&self) -> process::ExitStatus {
+        self.assert().get_output().status
+    }
+
+    /// The file `stdout` was written to.
+    pub fn stdout_path(&self) -> path::PathBuf {
+        self.files.stdout()
+    }
+
+    /// The file `stderr` was written to.
+    pub fn stderr_path(&self) -> path::PathBuf {
+        self.files.stderr()
+    }
+
+    /// Ensure the command succeeded.
+    ///
+    /// See [`Assert::success`].
+    #[track_caller]
+    pub fn success(self) -> Self {
+        self.settle(Assert::try_success)
+    }
+
+    /// `try_` variant of [`FileAssert::success`].
+    pub fn try_success(self) -> Result<Self, AssertError> {
+        self.check(Assert::try_success)
+    }
+
+    /// Ensure the command failed.
+    ///
+    /// See [`Assert::failure`].
+    #[track_caller]
+    pub fn failure(self) -> Self {
+        self.settle(Assert::try_failure)
+    }
+
+    /// `try_` variant of [`FileAssert::failure`].
+    pub fn try_failure(self) -> Result<Self, AssertError> {
+        self.check(Assert::try_failure)
+    }
+
+    /// Ensure the command returned the expected code.
+    ///
+    /// See [`Assert::code`].
+    #[track_caller]
+    pub fn code<I, P>(self, pred: I) -> Self
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.settle(|assert| assert.try_code(pred.into_code()))
+    }
+
+    /// `try_` variant of [`FileAssert::code`].
+    pub fn try_code<I, P>(self, pred: I) -> Result<Self, AssertError>
+    where
+        I: IntoCodePredicate<P>,
+        P: predicates_core::Predicate<i32>,
+    {
+        self.check(|assert| assert.try_code(pred.into_code()))
+    }
+
+    /// Ensure `stdout` contains `needle`.
+    #[track_caller]
+    pub fn stdout_contains(self, needle: impl AsRef<[u8]>) -> Self {
+        let path = self.stdout_path();
+        self.settle(|assert| contains(assert, Stream::Stdout, &path, needle.as_ref()))
+    }
+
+    /// `try_` variant of [`FileAssert::stdout_contains`].
+    pub fn try_stdout_contains(self, needle: impl AsRef<[u8]>) -> Result<Self, AssertError> {
+        let path = self.stdout_path();
+        self.check(|assert| contains(assert, Stream::Stdout, &path, needle.as_ref()))
+    }
+
+    /// Ensure `stderr` contains `needle`.
+    #[track_caller]
+    pub fn stderr_contains(self, needle: impl AsRef<[u8]>) -> Self {
+        let path = self.stderr_path();
+        self.settle(|assert| contains(assert, Stream::Stderr, &path, needle.as_ref()))
+    }
+
+    /// `try_` variant of [`FileAssert::stderr_contains`].
+    pub fn try_stderr_contains(self, needle: impl AsRef<[u8]>) -> Result<Self, AssertError> {
+        let path = self.stderr_path();
+        self.check(|assert| contains(assert, Stream::Stderr, &path, needle.as_ref()))
+    }
+
+    /// Ensure every line of `stdout` matches the predicate.
+    ///
+    /// See [`Assert::stdout_each_line`].
+    #[track_caller]
+    pub fn stdout_each_line<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let path = self.stdout_path();
+        self.settle(|assert| each_line(assert, Stream::Stdout, &path, &pred.into_output()))
+    }
+
+    /// `try_` variant of [`FileAssert::stdout_each_line`].
+    pub fn try_stdout_each_line<I, P>(self, pred: I) -> Result<Self, AssertError>
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let path = self.stdout_path();
+        self.check(|assert| each_line(assert, Stream::Stdout, &path, &pred.into_output()))
+    }
+
+    /// Ensure every line of `stderr` matches the predicate.
+    #[track_caller]
+    pub fn stderr_each_line<I, P>(self, pred: I) -> Self
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let path = self.stderr_path();
+        self.settle(|assert| each_line(assert, Stream::Stderr, &path, &pred.into_output()))
+    }
+
+    /// `try_` variant of [`FileAssert::stderr_each_line`].
+    pub fn try_stderr_each_line<I, P>(self, pred: I) -> Result<Self, AssertError>
+    where
+        I: IntoOutputPredicate<P>,
+        P: predicates_core::Predicate<[u8]>,
+    {
+        let path = self.stderr_path();
+        self.check(|assert| each_line(assert, Stream::Stderr, &path, &pred.into_output()))
+    }
+
+    /// Run `check` on the [`Assert`], keeping the files if it fails.
+    fn check(mut self, check: impl FnOnce(Assert) -> AssertResult) -> Result<Self, AssertError> {
+        let assert = self.assert.take().expect("only taken while asserting");
+        match check(assert) {
+            Ok(assert) => {
+                self.assert = Some(assert);
+                Ok(self)
+            }
+            Err(err) => {
+                self.files.keep();
+                Err(err)
+            }
+        }
+    }
+
+    /// Run `check` on the [`Assert`], panicking if it fails, unless the failure was expected.
+    #[track_caller]
+    fn settle(mut self, check: impl FnOnce(Assert) -> AssertResult) -> Self {
+        let assert = self.assert.take().expect("only taken while asserting");
+        let assert = check(assert).unwrap_or_else(|err| {
+            self.files.keep();
+            err.settle()
+        });
+        self.assert = Some(assert);
+        self
+    }
+}
+
+fn contains(assert: Assert, stream: Stream, path: &path::Path, needle: &[u8]) -> AssertResult {
+    let detail = match stream_contains(path, needle) {
+        Ok(true) => None,
+        Ok(false) => Some(format!(
+            "Unexpected {stream}, expected to contain {}",
+            DebugBytes::new(needle)
+        )),
+        Err(err) => Some(format!("Failed to read {stream}: {err}")),
+    };
+    assert.try_external(stream, detail)
+}
+
+fn each_line(
+    assert: Assert,
+    stream: Stream,
+    path: &path::Path,
+    pred: &dyn predicates_core::Predicate<[u8]>,
+) -> AssertResult {
+    let detail = match first_unmatched_line(path, pred) {
+        Ok(None) => None,
+        Ok(Some(detail)) => Some(format!("Unexpected {stream} on line {detail}")),
+        Err(err) => Some(format!("Failed to read {stream}: {err}")),
+    };
+    assert.try_external(stream, detail)
+}
+
+/// Describe the first line of `path` failing `pred`, if any.
+fn first_unmatched_line(
+    path: &path::Path,
+    pred: &dyn predicates_core::Predicate<[u8]>,
+) -> io::Result<Option<String>> {
+    let mut reader = io::BufReader::new(fs::File::open(path)?);
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        line_number += 1;
+        let content = line.strip_suffix(b"\n").unwrap_or(&line);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+        if let Some(case) = pred.find_case(false, content) {
+            return Ok(Some(format!(
+                "{line_number}: {:?}, failed {}",
+                content.as_bstr(),
+                case.tree()
+            )));
+        }
+    }
+}
+
+/// The start and end of an output file, read when a failure is reported.
+struct Excerpt(path::PathBuf);
+
+impl fmt::Display for Excerpt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match excerpts(&self.0) {
+            Ok(Excerpts::Whole(data)) => write!(f, "{}", DebugBytes::new(&data)),
+            Ok(Excerpts::Split { len, head, tail }) => write!(
+                f,
+                "{} ... {} ({len} bytes)",
+                DebugBytes::new(&head),
+                DebugBytes::new(&tail)
+            ),
+            Err(err) => write!(f, "<unreadable: {err}>"),
+        }
+    }
+}
+
+/// Search `path` for `needle` without reading it all into memory.
+fn stream_contains(path: &path::Path, needle: &[u8]) -> io::Result<bool> {
+    const CHUNK_LEN: usize = 64 * 1024;
+
+    if needle.is_empty() {
+        return Ok(true);
+    }
+    let mut file = fs::File::open(path)?;
+    let mut window = Vec::with_capacity(CHUNK_LEN + needle.len());
+    let mut chunk = vec![0; CHUNK_LEN];
+    loop {
+        let read = file.read(&mut chunk)?;
+        if read == 0 {
+            return Ok(false);
+        }
+        window.extend_from_slice(&chunk[..read]);
+        if window.find(needle).is_some() {
+            return Ok(true);
+        }
+        // Keep enough to find a match spanning chunks
+        let keep = window.len().min(needle.len() - 1);
+        window.drain(..window.len() - keep);
+    }
+}
+
+enum Excerpts {
+    Whole(Vec<u8>),
+    Split {
+        len: u64,
+        head: Vec<u8>,
+        tail: Vec<u8>,
+    },
+}
+
+fn excerpts(path: &path::Path) -> io::Result<Excerpts> {
+    let len = fs::metadata(path)?.len();
+    if len <= EXCERPT_LEN * 2 {
+        return Ok(Excerpts::Whole(fs::read(path)?));
+    }
+    let mut file = fs::File::open(path)?;
+    let mut head = Vec::new();
+    file.by_ref().take(EXCERPT_LEN).read_to_end(&mut head)?;
+    file.seek(io::SeekFrom::End(-(EXCERPT_LEN as i64)))?;
+    let mut tail = Vec::new();
+    file.take(EXCERPT_LEN).read_to_end(&mut tail)?;
+    Ok(Excerpts::Split { len, head, tail })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn contains_across_chunks() {
+        let files = OutputFiles::new().unwrap();
+        let mut data = vec![b'a'; 64 * 1024 - 2];
+        data.extend_from_slice(b"needle");
+        fs::write(files.stdout(), &data).unwrap();
+        assert!(stream_contains(&files.stdout(), b"needle").unwrap());
+        assert!(!stream_contains(&files.stdout(), b"haystack").unwrap());
+    }
+}
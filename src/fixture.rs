@@ -0,0 +1,228 @@
+//! Configure how to invoke a program once and stamp out a fresh [`Command`] per test.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::fixture::CommandFixture;
+//!
+//! use std::time::Duration;
+//!
+//! fn fixture() -> CommandFixture {
+//!     CommandFixture::cargo_bin("bin_fixture")
+//!         .unwrap()
+//!         .arg("--color=never")
+//!         .env("RUST_LOG", "debug")
+//!         .timeout(Duration::from_secs(10))
+//! }
+//!
+//! fixture()
+//!     .command()
+//!     .env("stdout", "hello")
+//!     .assert()
+//!     .success();
+//! ```
+//!
+//! Checks every test should make can be run [automatically][CommandFixture::always]:
+//! ```rust,no_run
+//! use assert_cmd::fixture::CommandFixture;
+//! use predicates::prelude::*;
+//!
+//! let fixture = CommandFixture::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .always(|assert| assert.stderr(predicate::str::contains("panicked at").not()));
+//!
+//! fixture.command().assert().success();
+//! ```
+//!
+//! A fixture can also be [installed][CommandFixture::install] for the whole test binary:
+//! ```rust,no_run
+//! use assert_cmd::fixture::CommandFixture;
+//!
+//! CommandFixture::cargo_bin("bin_fixture").unwrap().install();
+//!
+//! CommandFixture::installed()
+//!     .unwrap()
+//!     .command()
+//!     .assert()
+//!     .success();
+//! ```
+
+use std::ffi;
+use std::fmt;
+use std::path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use crate::assert::Assert;
+use crate::Command;
+
+type Check = Arc<dyn Fn(Assert) -> Assert + Send + Sync>;
+
+/// How to invoke a program, shared by many tests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandFixture {
+    program: ffi::OsString,
+    args: Vec<ffi::OsString>,
+    envs: Vec<(ffi::OsString, Option<ffi::OsString>)>,
+    env_clear: bool,
+    current_dir: Option<path::PathBuf>,
+    timeout: Option<Duration>,
+    always: Checks,
+}
+
+/// Assertions made on every run, compared by identity.
+#[derive(Clone, Default)]
+struct Checks(Vec<Check>);
+
+impl PartialEq for Checks {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(&other.0).all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl Eq for Checks {}
+
+impl fmt::Debug for Checks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter().map(|_| "..")).finish()
+    }
+}
+
+static FIXTURE: Mutex<Option<CommandFixture>> = Mutex::new(None);
+
+impl CommandFixture {
+    /// Invoke `program`.
+    pub fn new(program: impl Into<ffi::OsString>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            env_clear: false,
+            current_dir: None,
+            timeout: None,
+            always: Checks::default(),
+        }
+    }
+
+    /// Invoke a binary of the current crate.
+    ///
+    /// See [`Command::cargo_bin`].
+    pub fn cargo_bin<S: AsRef<str>>(name: S) -> Result<Self, crate::cargo::CargoError> {
+        let cmd = crate::cargo::cargo_bin_cmd(name)?;
+        // Keep any runner's arguments ahead of the fixture's
+        let mut fixture = Self::new(cmd.get_program());
+        fixture.args = cmd.get_args().map(ToOwned::to_owned).collect();
+        Ok(fixture)
+    }
+
+    /// Pass `arg` to every command.
+    pub fn arg(mut self, arg: impl Into<ffi::OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Pass `args` to every command.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<ffi::OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the environment variable `key` for every command.
+    pub fn env(mut self, key: impl Into<ffi::OsString>, value: impl Into<ffi::OsString>) -> Self {
+        self.envs.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Remove the environment variable `key` for every command.
+    pub fn env_remove(mut self, key: impl Into<ffi::OsString>) -> Self {
+        self.envs.push((key.into(), None));
+        self
+    }
+
+    /// Don't inherit any environment variables besides those set with
+    /// [`CommandFixture::env`].
+    pub fn env_clear(mut self) -> Self {
+        self.env_clear = true;
+        self.envs.clear();
+        self
+    }
+
+    /// Run every command in `dir`.
+    pub fn current_dir(mut self, dir: impl Into<path::PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Kill every command after `timeout`.
+    ///
+    /// See [`Command::timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Make `check` on every run of every command, before the test's own assertions.
+    ///
+    /// This is for checks the whole suite shares, like that nothing panicked.  `check` is run by
+    /// [`Command::after_wait`], so it panics on failure like other assertions, even for
+    /// [`Command::try_assert`].
+    pub fn always<F>(mut self, check: F) -> Self
+    where
+        F: Fn(Assert) -> Assert + Send + Sync + 'static,
+    {
+        self.always.0.push(Arc::new(check));
+        self
+    }
+
+    /// Create a fresh [`Command`] with this configuration.
+    ///
+    /// The command can be further configured, like adding arguments after the fixture's.
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        if self.env_clear {
+            cmd.env_clear();
+        }
+        for (key, value) in &self.envs {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
+        if let Some(timeout) = self.timeout {
+            cmd.timeout(timeout);
+        }
+        for check in &self.always.0 {
+            let check = Arc::clone(check);
+            cmd.after_wait(move |assert| check(assert));
+        }
+        cmd
+    }
+
+    /// Share this fixture with the rest of the test binary, see [`CommandFixture::installed`].
+    pub fn install(self) {
+        *FIXTURE.lock().unwrap_or_else(PoisonError::into_inner) = Some(self);
+    }
+
+    /// Remove the installed fixture.
+    pub fn uninstall() {
+        *FIXTURE.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// The [installed][CommandFixture::install] fixture, if any.
+    pub fn installed() -> Option<Self> {
+        FIXTURE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
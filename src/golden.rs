@@ -0,0 +1,349 @@
+//! Compare output against expectation files, see
+//! [`Assert::stdout_matches_path`][crate::assert::Assert::stdout_matches_path].
+//!
+//! When a comparison fails, a unified diff is printed.  If a patch directory is set, with
+//! [`set_patch_dir`] or the `ASSERT_CMD_PATCH_DIR` environment variable, the diff is also written
+//! there as a `.patch` file that updates the expectation when applied with `git apply` or `patch
+//! -p1`.
+//!
+//! To update the expectations instead, run the tests with `ASSERT_CMD_BLESS=1`, like
+//! `ASSERT_CMD_BLESS=1 cargo test`.  Each comparison that would fail rewrites its file and passes,
+//! printing which file was updated.  On Unix, the updated files are listed again when the test
+//! binary exits, so they aren't lost among the test output.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//!
+//! assert_cmd::golden::set_patch_dir(Some("target/patches".into()));
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .arg("--help")
+//!     .assert()
+//!     .stdout_matches_path("tests/snapshots/help.txt");
+//! ```
+
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use bstr::ByteSlice;
+
+/// Environment variable for the patch directory, used when [`set_patch_dir`] wasn't called.
+pub const PATCH_DIR_ENV: &str = "ASSERT_CMD_PATCH_DIR";
+
+/// Environment variable that, when `1`, updates expectation files instead of failing.
+pub const BLESS_ENV: &str = "ASSERT_CMD_BLESS";
+
+/// Lines of unchanged context around each change.
+const CONTEXT: usize = 3;
+
+/// Beyond this many line pairs, replace the whole file rather than finding the smallest diff.
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+static PATCH_DIR: Mutex<Option<path::PathBuf>> = Mutex::new(None);
+
+static BLESS: AtomicBool = AtomicBool::new(false);
+
+static UPDATED: Mutex<Vec<path::PathBuf>> = Mutex::new(Vec::new());
+
+/// Write a `.patch` file to `dir` for each failed comparison in this process.
+///
+/// [`None`] falls back to [`PATCH_DIR_ENV`].
+pub fn set_patch_dir(dir: Option<path::PathBuf>) {
+    *PATCH_DIR.lock().unwrap_or_else(PoisonError::into_inner) = dir;
+}
+
+/// Where `.patch` files are written, if anywhere.
+pub fn patch_dir() -> Option<path::PathBuf> {
+    let dir = PATCH_DIR
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    dir.or_else(|| {
+        env::var_os(PATCH_DIR_ENV)
+            .filter(|p| !p.is_empty())
+            .map(path::PathBuf::from)
+    })
+}
+
+/// Update expectation files instead of failing, for the rest of this process.
+///
+/// When `false`, falls back to [`BLESS_ENV`].
+pub fn set_bless(yes: bool) {
+    BLESS.store(yes, Ordering::Relaxed);
+}
+
+/// Whether expectation files are updated instead of failing.
+pub fn is_blessing() -> bool {
+    BLESS.load(Ordering::Relaxed) || env::var_os(BLESS_ENV).is_some_and(|v| v == "1")
+}
+
+/// Expectation files updated so far in this process, in the order they were updated.
+pub fn updated() -> Vec<path::PathBuf> {
+    UPDATED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Overwrite the expectation at `path` with `actual`, creating it if needed.
+pub(crate) fn bless(path: &path::Path, actual: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, actual)?;
+    let mut updated = UPDATED.lock().unwrap_or_else(PoisonError::into_inner);
+    #[cfg(unix)]
+    if updated.is_empty() {
+        // SAFETY: `print_summary` is a plain function that doesn't unwind
+        unsafe {
+            libc::atexit(print_summary);
+        }
+    }
+    updated.push(path.to_owned());
+    drop(updated);
+    if !crate::assert::is_quiet() {
+        let _ = writeln!(io::stderr(), "updated: {}", path.display());
+    }
+    Ok(())
+}
+
+/// List the files updated by this process, as it exits.
+#[cfg(unix)]
+extern "C" fn print_summary() {
+    // Still printed when quiet, as a single write that nothing else runs alongside
+    let summary = summary(&updated());
+    let _ = io::stderr().write_all(summary.as_bytes());
+}
+
+#[cfg(unix)]
+fn summary(updated: &[path::PathBuf]) -> String {
+    let mut summary = match updated.len() {
+        1 => "blessed 1 expectation file:\n".to_owned(),
+        n => format!("blessed {n} expectation files:\n"),
+    };
+    for path in updated {
+        summary.push_str("  ");
+        summary.push_str(&path.display().to_string());
+        summary.push('\n');
+    }
+    summary
+}
+
+/// How `path` appears in a patch, relative to the current directory when possible.
+fn patch_name(path: &path::Path) -> String {
+    let relative = env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(path::Path::to_owned))
+        .unwrap_or_else(|| path.to_owned());
+    relative
+        .components()
+        .filter_map(|c| match c {
+            path::Component::Normal(c) => Some(c.to_string_lossy().into_owned()),
+            path::Component::ParentDir => Some("..".to_owned()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Write a patch updating `path` from `expected`, or creating it if [`None`], to `actual`.
+///
+/// Returns where the patch was written, if a [patch directory][patch_dir] is set.
+pub(crate) fn write_patch(
+    path: &path::Path,
+    expected: Option<&[u8]>,
+    actual: &[u8],
+) -> io::Result<Option<path::PathBuf>> {
+    let Some(dir) = patch_dir() else {
+        return Ok(None);
+    };
+    let name = patch_name(path);
+    let old = match expected {
+        Some(_) => format!("a/{name}"),
+        None => "/dev/null".to_owned(),
+    };
+    let patch = unified_diff(
+        expected.unwrap_or_default(),
+        actual,
+        &old,
+        &format!("b/{name}"),
+    );
+    fs::create_dir_all(&dir)?;
+    let patch_path = dir.join(format!("{}.patch", name.replace(['/', '.'], "_")));
+    fs::write(&patch_path, patch)?;
+    Ok(Some(patch_path))
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Line-by-line edit script from `old` to `new`, from their longest common subsequence.
+fn diff_lines(old: &[&[u8]], new: &[&[u8]]) -> Vec<Op> {
+    let (n, m) = (old.len(), new.len());
+    if MAX_DIFF_CELLS < n.saturating_mul(m) {
+        return (0..n)
+            .map(Op::Delete)
+            .chain((0..m).map(Op::Insert))
+            .collect();
+    }
+    // `lcs[i][j]` is the length of the longest common subsequence of `old[i..]` and `new[j..]`
+    let mut lcs = vec![0_u32; (n + 1) * (m + 1)];
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if old[i] == new[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            ops.push(Op::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[at(i, j + 1)] <= lcs[at(i + 1, j)]) {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    ops
+}
+
+/// Render a unified diff from `old` to `new`, as read by `patch` and `git apply`.
+pub(crate) fn unified_diff(old: &[u8], new: &[u8], old_name: &str, new_name: &str) -> Vec<u8> {
+    let old: Vec<_> = old.split_inclusive(|b| *b == b'\n').collect();
+    let new: Vec<_> = new.split_inclusive(|b| *b == b'\n').collect();
+    let ops = diff_lines(&old, &new);
+
+    let mut diff = format!("--- {old_name}\n+++ {new_name}\n").into_bytes();
+    let changes: Vec<_> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(index, _)| index)
+        .collect();
+    let mut remaining = changes.as_slice();
+    while let Some(&first) = remaining.first() {
+        // Extend the hunk while the next change is close enough to share context
+        let mut last = first;
+        let mut rest = &remaining[1..];
+        while let Some(&next) = rest.first() {
+            if next - last > 2 * CONTEXT + 1 {
+                break;
+            }
+            last = next;
+            rest = &rest[1..];
+        }
+        remaining = rest;
+
+        let start = first.saturating_sub(CONTEXT);
+        let end = (last + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+        // Where the hunk starts in each file, even if it has no lines in it
+        let (old_start, new_start) = ops[..start].iter().fold((0, 0), |(o, n), op| match op {
+            Op::Equal(..) => (o + 1, n + 1),
+            Op::Delete(_) => (o + 1, n),
+            Op::Insert(_) => (o, n + 1),
+        });
+        let old_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|op| !matches!(op, Op::Delete(_)))
+            .count();
+        let range = |start: usize, len: usize| {
+            let start = if len == 0 { start } else { start + 1 };
+            format!("{start},{len}")
+        };
+        diff.extend_from_slice(
+            format!(
+                "@@ -{} +{} @@\n",
+                range(old_start, old_len),
+                range(new_start, new_len)
+            )
+            .as_bytes(),
+        );
+        for op in hunk {
+            let (prefix, line) = match *op {
+                Op::Equal(i, _) => (b' ', old[i]),
+                Op::Delete(i) => (b'-', old[i]),
+                Op::Insert(j) => (b'+', new[j]),
+            };
+            diff.push(prefix);
+            diff.extend_from_slice(line);
+            if !line.ends_with(b"\n") {
+                diff.extend_from_slice(b"\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    diff
+}
+
+/// Render the diff from `expected` to `actual` for a failure message.
+pub(crate) fn display_diff(expected: &[u8], actual: &[u8]) -> String {
+    let diff = unified_diff(expected, actual, "expected", "actual");
+    diff.to_str_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn summary_lists_files() {
+        assert_eq!(
+            summary(&["a.txt".into(), "b/c.txt".into()]),
+            "blessed 2 expectation files:\n  a.txt\n  b/c.txt\n"
+        );
+        assert_eq!(
+            summary(&["a.txt".into()]),
+            "blessed 1 expectation file:\n  a.txt\n"
+        );
+    }
+
+    #[test]
+    fn diff() {
+        let old = b"a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = b"a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk";
+        let diff = unified_diff(old, new, "a/file", "b/file");
+        assert_eq!(
+            diff.to_str_lossy(),
+            "--- a/file\n+++ b/file\n\
+             @@ -1,5 +1,5 @@\n a\n-b\n+B\n c\n d\n e\n\
+             @@ -8,3 +8,4 @@\n h\n i\n j\n+k\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn diff_new_file() {
+        let diff = unified_diff(b"", b"a\n", "/dev/null", "b/file");
+        assert_eq!(
+            diff.to_str_lossy(),
+            "--- /dev/null\n+++ b/file\n@@ -0,0 +1,1 @@\n+a\n"
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Run code before every [`Command`] is spawned and on every [`Assert`] it produces.
+//!
+//! Hooks handle cross-cutting concerns, like injecting an environment variable or collecting
+//! artifacts, without changing every call site.  They can be set per [`Command`], with
+//! [`Command::before_spawn`] and [`Command::after_wait`], or for the whole test binary by
+//! [installing][Hooks::install] them.  Installed hooks run first.
+//!
+//! Installed hooks can also follow [progress][Progress] through a long suite, like one test
+//! running hundreds of cases, by being told when each command starts and finishes.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::hooks::Hooks;
+//!
+//! Hooks::new()
+//!     .before_spawn(|cmd| {
+//!         cmd.env("RUST_BACKTRACE", "1");
+//!     })
+//!     .after_wait(|assert| assert.append_context("suite", "integration"))
+//!     .on_command_finish(|progress| {
+//!         eprint!("\r{} commands, {} failed", progress.finished(), progress.failed());
+//!     })
+//!     .install();
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .assert()
+//!     .success();
+//! ```
+
+use std::fmt;
+use std::process;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+
+use crate::assert::Assert;
+use crate::Command;
+
+type BeforeSpawn = Arc<dyn Fn(&mut Command) + Send + Sync>;
+type AfterWait = Arc<dyn Fn(Assert) -> Assert + Send + Sync>;
+type OnProgress = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Hooks run around each run of a [`Command`].
+#[derive(Clone, Default)]
+pub struct Hooks {
+    before_spawn: Vec<BeforeSpawn>,
+    after_wait: Vec<AfterWait>,
+    on_command_start: Vec<OnProgress>,
+    on_command_finish: Vec<OnProgress>,
+}
+
+static HOOKS: Mutex<Option<Hooks>> = Mutex::new(None);
+
+static STARTED: AtomicUsize = AtomicUsize::new(0);
+static FINISHED: AtomicUsize = AtomicUsize::new(0);
+static FAILED: AtomicUsize = AtomicUsize::new(0);
+
+impl Hooks {
+    /// Create an empty set of hooks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `hook` with the command before each time it is spawned.
+    ///
+    /// As a command may be run more than once, `hook` should be idempotent, like setting an
+    /// environment variable rather than adding an argument.
+    pub fn before_spawn<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut Command) + Send + Sync + 'static,
+    {
+        self.before_spawn.push(Arc::new(hook));
+        self
+    }
+
+    /// Pass each [`Assert`] through `hook` before any assertions are made on it.
+    ///
+    /// This is only called for runs that produce an [`Assert`], like [`Command::assert`].
+    pub fn after_wait<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(Assert) -> Assert + Send + Sync + 'static,
+    {
+        self.after_wait.push(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` as each command is started, after any `before_spawn` hooks.
+    pub fn on_command_start<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        self.on_command_start.push(Arc::new(hook));
+        self
+    }
+
+    /// Call `hook` as each command finishes, whether or not it succeeded.
+    ///
+    /// This is called before any assertions are made, so a command counts as failed by how it
+    /// exited, not by what is asserted about it.
+    pub fn on_command_finish<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        self.on_command_finish.push(Arc::new(hook));
+        self
+    }
+
+    /// Apply these hooks to all later runs in this process, replacing any installed hooks.
+    pub fn install(self) {
+        *HOOKS.lock().unwrap_or_else(PoisonError::into_inner) = Some(self);
+    }
+
+    /// Remove the installed hooks, if any.
+    pub fn uninstall() {
+        *HOOKS.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    fn installed() -> Option<Self> {
+        HOOKS.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("before_spawn", &self.before_spawn.len())
+            .field("after_wait", &self.after_wait.len())
+            .field("on_command_start", &self.on_command_start.len())
+            .field("on_command_finish", &self.on_command_finish.len())
+            .finish()
+    }
+}
+
+/// A command starting or finishing, with counts for all commands run in this process.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    command: String,
+    started: usize,
+    finished: usize,
+    failed: usize,
+    elapsed: Option<Duration>,
+    status: Option<process::ExitStatus>,
+}
+
+impl Progress {
+    /// The command line, as shown in failure reports.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// How many commands have been started, including this one.
+    pub fn started(&self) -> usize {
+        self.started
+    }
+
+    /// How many commands have finished, including this one once it has.
+    pub fn finished(&self) -> usize {
+        self.finished
+    }
+
+    /// How many finished commands failed to run or exited unsuccessfully.
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// How long this command ran, once it has finished.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+
+    /// How this command exited, if it has finished and wasn't killed for a deadline or failed
+    /// to start.
+    pub fn status(&self) -> Option<process::ExitStatus> {
+        self.status
+    }
+}
+
+/// Run the installed, then `local`, `before_spawn` hooks on `cmd`.
+pub(crate) fn before_spawn(cmd: &mut Command, local: &Hooks) {
+    let installed = Hooks::installed().unwrap_or_default();
+    for hook in installed.before_spawn.iter().chain(&local.before_spawn) {
+        hook(cmd);
+    }
+}
+
+/// Count `cmd` as started, calling the installed, then `local`, `on_command_start` hooks.
+pub(crate) fn command_started(cmd: &process::Command, cleared: bool, local: &Hooks) {
+    let started = STARTED.fetch_add(1, Ordering::SeqCst) + 1;
+    let installed = Hooks::installed().unwrap_or_default();
+    let mut hooks = installed
+        .on_command_start
+        .iter()
+        .chain(&local.on_command_start)
+        .peekable();
+    if hooks.peek().is_none() {
+        return;
+    }
+    let progress = Progress {
+        command: crate::output::render_command(cmd, cleared),
+        started,
+        finished: FINISHED.load(Ordering::SeqCst),
+        failed: FAILED.load(Ordering::SeqCst),
+        elapsed: None,
+        status: None,
+    };
+    for hook in hooks {
+        hook(&progress);
+    }
+}
+
+/// Count `cmd` as finished, calling the installed, then `local`, `on_command_finish` hooks.
+pub(crate) fn command_finished(
+    cmd: &process::Command,
+    cleared: bool,
+    elapsed: Duration,
+    status: Option<process::ExitStatus>,
+    local: &Hooks,
+) {
+    let failed = if status.is_some_and(|status| status.success()) {
+        FAILED.load(Ordering::SeqCst)
+    } else {
+        FAILED.fetch_add(1, Ordering::SeqCst) + 1
+    };
+    let finished = FINISHED.fetch_add(1, Ordering::SeqCst) + 1;
+    let installed = Hooks::installed().unwrap_or_default();
+    let mut hooks = installed
+        .on_command_finish
+        .iter()
+        .chain(&local.on_command_finish)
+        .peekable();
+    if hooks.peek().is_none() {
+        return;
+    }
+    let progress = Progress {
+        command: crate::output::render_command(cmd, cleared),
+        started: STARTED.load(Ordering::SeqCst),
+        finished,
+        failed,
+        elapsed: Some(elapsed),
+        status,
+    };
+    for hook in hooks {
+        hook(&progress);
+    }
+}
+
+/// Run the installed, then `local`, `after_wait` hooks on `assert`.
+pub(crate) fn after_wait(mut assert: Assert, local: &Hooks) -> Assert {
+    let installed = Hooks::installed().unwrap_or_default();
+    for hook in installed.after_wait.iter().chain(&local.after_wait) {
+        assert = hook(assert);
+    }
+    assert
+}
@@ -0,0 +1,211 @@
+//! Record every command run by [`Command`] to a [JSON Lines](https://jsonlines.org/) file.
+//!
+//! Each line describes one run:
+//! ```json
+//! {"argv":["bin_fixture","-A"],"env":{"stdout":"hello","TERM":null},"cwd":null,"duration_ms":2.5,"code":0}
+//! ```
+//! Only environment variables changed for the command are recorded, with `null` meaning removed.
+//! Secrets are [redacted][crate::redact], as in failure reports.
+//!
+//! A journal may be [installed][Journal::install] or, for a whole test run, set with the
+//! `ASSERT_CMD_JOURNAL` environment variable.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::journal::Journal;
+//!
+//! Journal::new("target/commands.jsonl").install();
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .assert()
+//!     .success();
+//! ```
+//!
+//! [`Command`]: crate::Command
+
+use std::env;
+use std::ffi;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path;
+use std::process;
+use std::time::Duration;
+
+/// Environment variable for a journal path, used when no [`Journal`] is installed.
+pub const JOURNAL_ENV: &str = "ASSERT_CMD_JOURNAL";
+
+/// Where to record runs, installed process-wide with [`Journal::install`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Journal {
+    path: path::PathBuf,
+}
+
+static JOURNAL: std::sync::Mutex<Option<Journal>> = std::sync::Mutex::new(None);
+
+impl Journal {
+    /// Append runs to `path`, creating it if needed.
+    pub fn new(path: impl Into<path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Record all later runs in this process.
+    pub fn install(self) {
+        *JOURNAL
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(self);
+    }
+
+    /// Stop recording, unless [`JOURNAL_ENV`] is set.
+    pub fn uninstall() {
+        *JOURNAL
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+
+    fn installed() -> Option<Self> {
+        let installed = JOURNAL
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        installed.or_else(|| {
+            env::var_os(JOURNAL_ENV)
+                .filter(|p| !p.is_empty())
+                .map(Self::new)
+        })
+    }
+}
+
+/// Record a run of `cmd` in the installed journal, if any.
+///
+/// The journal is best-effort: failing to write it doesn't fail the run.
+pub(crate) fn record(
+    cmd: &process::Command,
+    duration: Duration,
+    status: Option<process::ExitStatus>,
+) {
+    let Some(journal) = Journal::installed() else {
+        return;
+    };
+    let entry = entry(cmd, duration, status);
+    if let Ok(mut file) = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&journal.path)
+    {
+        // A single write so concurrent tests don't interleave entries
+        let _ = file.write_all(entry.as_bytes());
+    }
+}
+
+/// Render a run as a line of JSON, with secrets [redacted][crate::redact].
+fn entry(
+    cmd: &process::Command,
+    duration: Duration,
+    status: Option<process::ExitStatus>,
+) -> String {
+    let mut entry = String::from("{\"argv\":[");
+    let argv = std::iter::once(cmd.get_program()).chain(cmd.get_args());
+    for (i, arg) in argv.enumerate() {
+        if i != 0 {
+            entry.push(',');
+        }
+        push_redacted(&mut entry, arg);
+    }
+    entry.push_str("],\"env\":{");
+    for (i, (key, value)) in cmd.get_envs().enumerate() {
+        if i != 0 {
+            entry.push(',');
+        }
+        push_str(&mut entry, key);
+        entry.push(':');
+        match value {
+            Some(_) if crate::redact::is_sensitive(key) => {
+                push_str(&mut entry, crate::redact::PLACEHOLDER.as_ref());
+            }
+            Some(value) => push_redacted(&mut entry, value),
+            None => entry.push_str("null"),
+        }
+    }
+    entry.push_str("},\"cwd\":");
+    match cmd.get_current_dir() {
+        Some(cwd) => push_redacted(&mut entry, cwd.as_os_str()),
+        None => entry.push_str("null"),
+    }
+    let _ = write!(
+        entry,
+        ",\"duration_ms\":{:.3},\"code\":",
+        duration.as_secs_f64() * 1000.0
+    );
+    match status.and_then(|s| s.code()) {
+        Some(code) => {
+            let _ = write!(entry, "{code}");
+        }
+        None => entry.push_str("null"),
+    }
+    entry.push_str("}\n");
+    entry
+}
+
+/// Push `value` as a JSON string, with secrets [redacted][crate::redact::redact].
+///
+/// This is done before escaping, so what is replaced matches what the command was given.
+fn push_redacted(entry: &mut String, value: &ffi::OsStr) {
+    let value = value.to_string_lossy();
+    push_str(entry, ffi::OsStr::new(&*crate::redact::redact(&value)));
+}
+
+/// Push `value` as a JSON string.
+pub(crate) fn push_str(entry: &mut String, value: &ffi::OsStr) {
+    entry.push('"');
+    for c in value.to_string_lossy().chars() {
+        match c {
+            '"' => entry.push_str("\\\""),
+            '\\' => entry.push_str("\\\\"),
+            '\n' => entry.push_str("\\n"),
+            '\r' => entry.push_str("\\r"),
+            '\t' => entry.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(entry, "\\u{:04x}", u32::from(c));
+            }
+            c => entry.push(c),
+        }
+    }
+    entry.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entry_format() {
+        let mut cmd = process::Command::new("tool");
+        cmd.arg("say \"hi\"\n")
+            .env("KEY", "value")
+            .env_remove("TERM")
+            .current_dir("dir");
+        let entry = entry(&cmd, Duration::from_micros(2500), None);
+        assert_eq!(
+            entry,
+            r#"{"argv":["tool","say \"hi\"\n"],"env":{"KEY":"value","TERM":null},"cwd":"dir","duration_ms":2.500,"code":null}
+"#
+        );
+    }
+
+    #[test]
+    fn entry_redacted() {
+        crate::redact::secret("journal-unit-secret");
+        let mut cmd = process::Command::new("tool");
+        cmd.arg("--key=journal-unit-secret")
+            .env("API_TOKEN", "abc\"def");
+        let entry = entry(&cmd, Duration::ZERO, None);
+        assert!(!entry.contains("journal-unit-secret"), "{entry}");
+        assert!(!entry.contains("abc"), "{entry}");
+        assert!(entry.contains(r#""--key=[redacted]""#), "{entry}");
+        assert!(entry.contains(r#""API_TOKEN":"[redacted]""#), "{entry}");
+    }
+}
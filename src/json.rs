@@ -0,0 +1,255 @@
+//! Compare JSON output structurally, ignoring fields that change between runs.
+//!
+//! See [`Assert::stdout_json`][crate::assert::Assert::stdout_json].
+//!
+//! Ignored fields are selected by paths like `$.timestamp` or `$.items[*].id`:
+//! - `$` is the document
+//! - `.name` or `["name"]` is a field of an object
+//! - `[2]` is an element of an array
+//! - `.*` or `[*]` is every field or element
+//!
+//! Ignored values are replaced with a placeholder on both sides before comparing, so the field
+//! must still be present, but may have any value.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::json::JsonRules;
+//!
+//! Command::cargo_bin("my-tool")
+//!     .unwrap()
+//!     .args(["list", "--json"])
+//!     .assert()
+//!     .stdout_json_with(
+//!         r#"{"timestamp": 0, "items": [{"id": 0, "name": "a"}]}"#,
+//!         &JsonRules::new().ignore("$.timestamp").ignore("$.items[*].id"),
+//!     );
+//! ```
+
+use std::fmt;
+
+use serde_json::Value;
+
+const PLACEHOLDER: &str = "[ignored]";
+
+/// How to compare JSON documents, for
+/// [`Assert::stdout_json_with`][crate::assert::Assert::stdout_json_with].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JsonRules {
+    ignore: Vec<String>,
+}
+
+impl JsonRules {
+    /// Compare every value.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept any value at `path`, like `$.items[*].id`.
+    ///
+    /// See the [module documentation][self] for the path syntax.
+    pub fn ignore(mut self, path: impl Into<String>) -> Self {
+        self.ignore.push(path.into());
+        self
+    }
+
+    /// Describe how `actual` differs from `expected`, if it does.
+    pub(crate) fn mismatch(&self, expected: &str, actual: &[u8]) -> Option<String> {
+        let mut expected: Value = match serde_json::from_str(expected) {
+            Ok(expected) => expected,
+            Err(err) => return Some(format!("expected JSON is invalid: {err}")),
+        };
+        let mut actual: Value = match serde_json::from_slice(actual) {
+            Ok(actual) => actual,
+            Err(err) => return Some(format!("expected JSON but {err}")),
+        };
+        for path in &self.ignore {
+            let segments = match parse_path(path) {
+                Ok(segments) => segments,
+                Err(err) => return Some(format!("invalid ignore path `{path}`: {err}")),
+            };
+            replace(&mut expected, &segments);
+            replace(&mut actual, &segments);
+        }
+        difference("$".to_owned(), &expected, &actual)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>, &'static str> {
+    let mut rest = path.strip_prefix('$').ok_or("must start with `$`")?;
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let (name, after) = after.split_at(end);
+            segments.push(match name {
+                "" => return Err("expected a field name after `.`"),
+                "*" => Segment::Wildcard,
+                name => Segment::Key(name.to_owned()),
+            });
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (inner, after) = after.split_once(']').ok_or("unclosed `[`")?;
+            let quoted = inner
+                .strip_prefix('"')
+                .and_then(|inner| inner.strip_suffix('"'))
+                .or_else(|| {
+                    inner
+                        .strip_prefix('\'')
+                        .and_then(|inner| inner.strip_suffix('\''))
+                });
+            segments.push(match (inner, quoted) {
+                (_, Some(name)) => Segment::Key(name.to_owned()),
+                ("*", None) => Segment::Wildcard,
+                (index, None) => Segment::Index(
+                    index
+                        .parse()
+                        .map_err(|_| "expected an index, `*`, or a quoted name in `[]`")?,
+                ),
+            });
+            rest = after;
+        } else {
+            return Err("expected `.` or `[`");
+        }
+    }
+    Ok(segments)
+}
+
+/// Replace every value at `path` with the placeholder.
+fn replace(value: &mut Value, path: &[Segment]) {
+    let Some((segment, rest)) = path.split_first() else {
+        *value = Value::String(PLACEHOLDER.to_owned());
+        return;
+    };
+    match (segment, value) {
+        (Segment::Key(key), Value::Object(fields)) => {
+            if let Some(value) = fields.get_mut(key) {
+                replace(value, rest);
+            }
+        }
+        (Segment::Index(index), Value::Array(elements)) => {
+            if let Some(value) = elements.get_mut(*index) {
+                replace(value, rest);
+            }
+        }
+        (Segment::Wildcard, Value::Object(fields)) => {
+            for value in fields.values_mut() {
+                replace(value, rest);
+            }
+        }
+        (Segment::Wildcard, Value::Array(elements)) => {
+            for value in elements {
+                replace(value, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Describe the first place `actual` differs from `expected`, at `path`.
+fn difference(path: String, expected: &Value, actual: &Value) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                let path = format!("{path}{}", Key(key));
+                match actual.get(key) {
+                    Some(actual) => {
+                        if let Some(difference) = difference(path, expected, actual) {
+                            return Some(difference);
+                        }
+                    }
+                    None => return Some(format!("missing {path}, expected {expected}")),
+                }
+            }
+            actual
+                .iter()
+                .find(|(key, _)| !expected.contains_key(*key))
+                .map(|(key, actual)| format!("unexpected {path}{}: {actual}", Key(key)))
+        }
+        (Value::Array(expected_elements), Value::Array(actual_elements)) => {
+            if expected_elements.len() != actual_elements.len() {
+                return Some(format!(
+                    "at {path}: expected {} elements, found {}",
+                    expected_elements.len(),
+                    actual_elements.len()
+                ));
+            }
+            expected_elements
+                .iter()
+                .zip(actual_elements)
+                .enumerate()
+                .find_map(|(index, (expected, actual))| {
+                    difference(format!("{path}[{index}]"), expected, actual)
+                })
+        }
+        _ if expected == actual => None,
+        _ => Some(format!("at {path}: expected {expected}, found {actual}")),
+    }
+}
+
+/// Render a field name as a path segment.
+struct Key<'a>(&'a str);
+
+impl fmt::Display for Key<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plain = !self.0.is_empty()
+            && self
+                .0
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        if plain {
+            write!(f, ".{}", self.0)
+        } else {
+            write!(f, "[{}]", Value::String(self.0.to_owned()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ignore_paths() {
+        let expected = r#"{"time": 0, "items": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]}"#;
+        let actual = br#"{"items": [{"name": "a", "id": 7}, {"name": "b", "id": 9}], "time": 5}"#;
+        let rules = JsonRules::new();
+        assert_eq!(
+            rules.mismatch(expected, actual).unwrap(),
+            "at $.items[0].id: expected 1, found 7"
+        );
+        let rules = rules.ignore("$.time").ignore("$.items[*].id");
+        assert_eq!(rules.mismatch(expected, actual), None);
+
+        let actual = br#"{"items": [{"name": "a"}, {"name": "b", "id": 9}], "time": 5}"#;
+        assert_eq!(
+            rules.mismatch(expected, actual).unwrap(),
+            r#"missing $.items[0].id, expected "[ignored]""#
+        );
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            parse_path(r#"$.a[0]["b.c"][*].*"#).unwrap(),
+            [
+                Segment::Key("a".to_owned()),
+                Segment::Index(0),
+                Segment::Key("b.c".to_owned()),
+                Segment::Wildcard,
+                Segment::Wildcard,
+            ]
+        );
+        assert!(parse_path("a.b").is_err());
+        assert!(parse_path("$.a[").is_err());
+        assert!(parse_path("$.a[b]").is_err());
+    }
+}
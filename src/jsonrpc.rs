@@ -0,0 +1,122 @@
+//! Frame messages for `Content-Length`-framed stdio protocols, like JSON-RPC for language servers.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use predicates::prelude::*;
+//!
+//! Command::cargo_bin("my-language-server")
+//!     .unwrap()
+//!     .write_stdin_jsonrpc([
+//!         r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+//!         r#"{"jsonrpc":"2.0","method":"exit"}"#,
+//!     ])
+//!     .assert()
+//!     .stdout_jsonrpc(predicate::str::contains(r#""id":1"#));
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use bstr::ByteSlice;
+
+const CONTENT_LENGTH: &str = "Content-Length";
+
+/// Frame `body` with a `Content-Length` header.
+pub fn frame(body: impl AsRef<[u8]>) -> Vec<u8> {
+    let body = body.as_ref();
+    let mut framed = format!("{CONTENT_LENGTH}: {}\r\n\r\n", body.len()).into_bytes();
+    framed.extend_from_slice(body);
+    framed
+}
+
+/// Split `data` into the bodies of its framed messages.
+pub fn unframe(data: &[u8]) -> Result<Vec<Vec<u8>>, FramingError> {
+    let mut messages = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let header_end = rest
+            .find(b"\r\n\r\n")
+            .ok_or_else(|| FramingError::new(messages.len(), "incomplete header"))?;
+        let length = rest[..header_end]
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once_str(":")?;
+                name.trim()
+                    .eq_ignore_ascii_case(CONTENT_LENGTH.as_bytes())
+                    .then_some(value)
+            })
+            .ok_or_else(|| FramingError::new(messages.len(), "missing `Content-Length`"))?;
+        let length = length
+            .to_str()
+            .ok()
+            .and_then(|length| length.trim().parse::<usize>().ok())
+            .ok_or_else(|| FramingError::new(messages.len(), "invalid `Content-Length`"))?;
+        let body = &rest[header_end + 4..];
+        if body.len() < length {
+            return Err(FramingError::new(messages.len(), "truncated body"));
+        }
+        messages.push(body[..length].to_vec());
+        rest = &body[length..];
+    }
+    Ok(messages)
+}
+
+/// Failure to [`unframe`] messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramingError {
+    index: usize,
+    reason: &'static str,
+}
+
+impl FramingError {
+    fn new(index: usize, reason: &'static str) -> Self {
+        Self { index, reason }
+    }
+}
+
+impl Error for FramingError {}
+
+impl fmt::Display for FramingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "message {} is malformed: {}", self.index, self.reason)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut data = frame("{}");
+        data.extend(frame(r#"{"id":1}"#));
+        assert_eq!(
+            data.as_bstr(),
+            "Content-Length: 2\r\n\r\n{}Content-Length: 8\r\n\r\n{\"id\":1}"
+        );
+        assert_eq!(
+            unframe(&data).unwrap(),
+            [b"{}".to_vec(), br#"{"id":1}"#.to_vec()]
+        );
+    }
+
+    #[test]
+    fn extra_headers() {
+        let data = b"content-length: 2\r\nContent-Type: application/json\r\n\r\n{}";
+        assert_eq!(unframe(data).unwrap(), [b"{}".to_vec()]);
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(
+            unframe(b"Content-Length: 5\r\n\r\n{}").unwrap_err(),
+            FramingError::new(0, "truncated body")
+        );
+        assert_eq!(
+            unframe(b"Content-Length: 2\r\n\r\n{}garbage").unwrap_err(),
+            FramingError::new(1, "incomplete header")
+        );
+    }
+}
@@ -17,6 +17,7 @@
 //! - `env` / `envs` / `env_remove` / `env_clear`
 //! - `write_stdin` / `pipe_stdin`
 //! - `timeout`
+//! - `locale`
 //!
 //! Validate a [`Command`]:
 //! - `ok` / `unwrap` / `unwrap_err`
@@ -130,10 +131,36 @@ macro_rules! crate_name {
     };
 }
 
+#[cfg(feature = "ansi")]
+pub mod ansi;
 pub mod assert;
+pub mod budget;
 pub mod cargo;
 pub mod cmd;
+pub mod compat;
+pub mod completions;
+pub mod files;
+pub mod fixture;
+pub mod golden;
+pub mod hooks;
+pub mod journal;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod jsonrpc;
+pub mod matrix;
+pub mod normalize;
 pub mod output;
+pub mod platform;
+pub mod pred;
+pub mod redact;
+pub mod requires;
+pub mod scope;
+#[cfg(unix)]
+pub mod shim;
+pub mod skip;
+#[cfg(all(feature = "strace", target_os = "linux"))]
+pub mod strace;
+pub mod stress;
 
 /// Extension traits that are useful to have available.
 pub mod prelude {
@@ -144,7 +171,15 @@ pub mod prelude {
 
 pub use crate::cmd::Command;
 
-mod color;
+#[cfg(unix)]
+mod activation;
+pub mod color;
+#[cfg(unix)]
+mod fds;
+#[cfg(unix)]
+mod pre_exec;
+mod pump;
+mod temp;
 use color::Palette;
 
 doc_comment::doctest!("../README.md");
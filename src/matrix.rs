@@ -0,0 +1,323 @@
+//! Run a [`Command`] across combinations of environment variables or arguments.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::matrix::EnvMatrix;
+//!
+//! let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+//! cmd.env("stdout", "hello");
+//! EnvMatrix::new()
+//!     .var("NO_COLOR", [None, Some("1")])
+//!     .var("TERM", ["dumb", "xterm-256color"])
+//!     .run(&mut cmd, |_cell, assert| {
+//!         assert.success().stdout("hello\n");
+//!     });
+//! ```
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::matrix::ArgMatrix;
+//!
+//! ArgMatrix::new()
+//!     .args([["-q"], ["-v"], ["-vv"]])
+//!     .run(
+//!         || Command::cargo_bin("bin_fixture").unwrap(),
+//!         |_cell, assert| {
+//!             assert.success();
+//!         },
+//!     );
+//! ```
+//!
+//! [`Command`]: crate::Command
+
+use std::ffi;
+use std::fmt;
+
+use crate::assert::Assert;
+use crate::output::quote_os;
+use crate::Command;
+
+/// Combinations of environment variables to run a [`Command`] with.
+///
+/// Each variable's values are crossed with every other variable's, with [`None`] meaning the
+/// variable is removed.
+#[derive(Clone, Debug, Default)]
+pub struct EnvMatrix {
+    vars: Vec<(ffi::OsString, Vec<Option<ffi::OsString>>)>,
+}
+
+impl EnvMatrix {
+    /// Create an empty matrix, which has a single cell with no variables set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a dimension for `key`, with one cell per value.
+    ///
+    /// This uses [`IntoEnvValue`] so values may be optional.
+    pub fn var<K, I>(mut self, key: K, values: I) -> Self
+    where
+        K: AsRef<ffi::OsStr>,
+        I: IntoIterator,
+        I::Item: IntoEnvValue,
+    {
+        let values = values
+            .into_iter()
+            .map(IntoEnvValue::into_env_value)
+            .collect();
+        self.vars.push((key.as_ref().to_owned(), values));
+        self
+    }
+
+    /// Enumerate every combination of the variables.
+    pub fn cells(&self) -> Vec<EnvCell> {
+        let mut cells = vec![EnvCell::default()];
+        for (key, values) in &self.vars {
+            cells = cells
+                .into_iter()
+                .flat_map(|cell| {
+                    values.iter().map(move |value| {
+                        let mut cell = cell.clone();
+                        cell.vars.push((key.clone(), value.clone()));
+                        cell
+                    })
+                })
+                .collect();
+        }
+        cells
+    }
+
+    /// Run `cmd` once per cell, passing the resulting [`Assert`] to `check`.
+    ///
+    /// The cell is added to the [`Assert`]'s context so failures report which combination
+    /// failed.
+    ///
+    /// Each cell's variables are set on `cmd` itself, so it is left with the last cell's.
+    #[track_caller]
+    pub fn run<F>(&self, cmd: &mut Command, mut check: F)
+    where
+        F: FnMut(&EnvCell, Assert),
+    {
+        for cell in self.cells() {
+            cell.apply(cmd);
+            let assert = cmd.assert().append_context("env", cell.clone());
+            check(&cell, assert);
+        }
+    }
+}
+
+/// Used by [`EnvMatrix::var`] to convert `Self` into a value, with [`None`] meaning removed.
+pub trait IntoEnvValue {
+    /// Convert to an environment variable value.
+    fn into_env_value(self) -> Option<ffi::OsString>;
+}
+
+impl IntoEnvValue for &str {
+    fn into_env_value(self) -> Option<ffi::OsString> {
+        Some(self.into())
+    }
+}
+
+impl IntoEnvValue for String {
+    fn into_env_value(self) -> Option<ffi::OsString> {
+        Some(self.into())
+    }
+}
+
+impl IntoEnvValue for &ffi::OsStr {
+    fn into_env_value(self) -> Option<ffi::OsString> {
+        Some(self.to_owned())
+    }
+}
+
+impl IntoEnvValue for ffi::OsString {
+    fn into_env_value(self) -> Option<ffi::OsString> {
+        Some(self)
+    }
+}
+
+impl<V: IntoEnvValue> IntoEnvValue for Option<V> {
+    fn into_env_value(self) -> Option<ffi::OsString> {
+        self.and_then(IntoEnvValue::into_env_value)
+    }
+}
+
+/// One combination of environment variables from an [`EnvMatrix`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvCell {
+    vars: Vec<(ffi::OsString, Option<ffi::OsString>)>,
+}
+
+impl EnvCell {
+    /// The value of `key` in this cell.
+    ///
+    /// Returns [`None`] if `key` is removed or not part of the matrix.
+    pub fn get<K: AsRef<ffi::OsStr>>(&self, key: K) -> Option<&ffi::OsStr> {
+        let key = key.as_ref();
+        self.vars
+            .iter()
+            .find(|(k, _)| k == key)
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    /// Iterate over the variables of this cell, with [`None`] meaning removed.
+    pub fn iter(&self) -> impl Iterator<Item = (&ffi::OsStr, Option<&ffi::OsStr>)> {
+        self.vars.iter().map(|(k, v)| (k.as_os_str(), v.as_deref()))
+    }
+
+    fn apply(&self, cmd: &mut Command) {
+        for (key, value) in &self.vars {
+            match value {
+                Some(value) => cmd.env(key, value),
+                None => cmd.env_remove(key),
+            };
+        }
+    }
+}
+
+impl fmt::Display for EnvCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            match value {
+                Some(value) => write!(f, "{}={}", quote_os(key), quote_os(value))?,
+                None => write!(f, "{}=<unset>", quote_os(key))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Combinations of arguments to run a [`Command`] with.
+///
+/// Each dimension's argument lists are crossed with every other dimension's, and a cell's
+/// arguments are the concatenation of its lists, in order.
+#[derive(Clone, Debug, Default)]
+pub struct ArgMatrix {
+    dimensions: Vec<Vec<Vec<ffi::OsString>>>,
+}
+
+impl ArgMatrix {
+    /// Create an empty matrix, which has a single cell with no arguments.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a dimension with one cell per list of arguments in `lists`.
+    pub fn args<I, L, S>(mut self, lists: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: IntoIterator<Item = S>,
+        S: AsRef<ffi::OsStr>,
+    {
+        let lists = lists
+            .into_iter()
+            .map(|list| list.into_iter().map(|s| s.as_ref().to_owned()).collect())
+            .collect();
+        self.dimensions.push(lists);
+        self
+    }
+
+    /// Enumerate every combination of the arguments.
+    pub fn cells(&self) -> Vec<ArgCell> {
+        let mut cells = vec![ArgCell::default()];
+        for lists in &self.dimensions {
+            cells = cells
+                .into_iter()
+                .flat_map(|cell| {
+                    lists.iter().map(move |list| {
+                        let mut cell = cell.clone();
+                        cell.args.extend(list.iter().cloned());
+                        cell
+                    })
+                })
+                .collect();
+        }
+        cells
+    }
+
+    /// Run a fresh command from `cmd` once per cell, with the cell's arguments appended, passing
+    /// the resulting [`Assert`] to `check`.
+    ///
+    /// The cell is added to the [`Assert`]'s context so failures report which arguments failed.
+    #[track_caller]
+    pub fn run<C, F>(&self, mut cmd: C, mut check: F)
+    where
+        C: FnMut() -> Command,
+        F: FnMut(&ArgCell, Assert),
+    {
+        for cell in self.cells() {
+            let assert = cmd()
+                .args(&cell.args)
+                .assert()
+                .append_context("args", cell.clone());
+            check(&cell, assert);
+        }
+    }
+}
+
+/// One combination of arguments from an [`ArgMatrix`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArgCell {
+    args: Vec<ffi::OsString>,
+}
+
+impl ArgCell {
+    /// The arguments of this cell.
+    pub fn args(&self) -> &[ffi::OsString] {
+        &self.args
+    }
+}
+
+impl fmt::Display for ArgCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, arg) in self.args.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", quote_os(arg))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cells() {
+        let matrix = EnvMatrix::new()
+            .var("NO_COLOR", [None, Some("1")])
+            .var("TERM", ["dumb", "xterm"]);
+        let cells: Vec<_> = matrix.cells().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            cells,
+            [
+                "NO_COLOR=<unset> TERM=dumb",
+                "NO_COLOR=<unset> TERM=xterm",
+                "NO_COLOR=1 TERM=dumb",
+                "NO_COLOR=1 TERM=xterm",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(EnvMatrix::new().cells(), [EnvCell::default()]);
+    }
+
+    #[test]
+    fn arg_cells() {
+        let matrix = ArgMatrix::new()
+            .args([vec!["-q"], vec!["-v", "--long"]])
+            .args([["a"], ["b"]]);
+        let cells: Vec<_> = matrix.cells().iter().map(ToString::to_string).collect();
+        assert_eq!(cells, ["-q a", "-q b", "-v --long a", "-v --long b",]);
+        assert_eq!(ArgMatrix::new().cells(), [ArgCell::default()]);
+    }
+}
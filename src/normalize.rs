@@ -0,0 +1,301 @@
+//! Normalize captured output so one expectation passes across platforms.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::prelude::*;
+//! use assert_cmd::normalize::NormalizePaths;
+//!
+//! use std::process::Command;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", r"C:\Users\me\project\src\main.rs")
+//!     .assert()
+//!     .normalize_paths(NormalizePaths::new().strip_drive_letters(true))
+//!     .stdout("/Users/me/project/src/main.rs\n");
+//! ```
+
+use std::env;
+
+use bstr::ByteSlice;
+
+/// Placeholder substituted for the temporary directory by [`NormalizePaths::replace_temp_dir`].
+pub const TEMP_DIR_PLACEHOLDER: &str = "[TEMP]";
+
+/// How [`Assert::normalize_paths`][crate::assert::Assert::normalize_paths] rewrites output.
+///
+/// By default, only `\` is rewritten to `/`.  Note that this applies to all of the output, not
+/// just paths.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizePaths {
+    strip_drive_letters: bool,
+    replace_temp_dir: bool,
+}
+
+impl NormalizePaths {
+    /// Rewrite `\` to `/`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also remove drive letters, like `C:`, from the start of absolute paths.
+    pub fn strip_drive_letters(mut self, yes: bool) -> Self {
+        self.strip_drive_letters = yes;
+        self
+    }
+
+    /// Also replace the [temporary directory][std::env::temp_dir], in any of its spellings, with
+    /// [`TEMP_DIR_PLACEHOLDER`].
+    pub fn replace_temp_dir(mut self, yes: bool) -> Self {
+        self.replace_temp_dir = yes;
+        self
+    }
+
+    pub(crate) fn apply(&self, data: &[u8]) -> Vec<u8> {
+        let mut normalized = self.apply_separators(data);
+        if self.replace_temp_dir {
+            for temp_dir in self.temp_dirs() {
+                normalized = normalized.replace(&temp_dir, TEMP_DIR_PLACEHOLDER);
+            }
+        }
+        normalized
+    }
+
+    fn apply_separators(&self, data: &[u8]) -> Vec<u8> {
+        let data = data.replace(b"\\", b"/");
+        if !self.strip_drive_letters {
+            return data;
+        }
+
+        let mut normalized = Vec::with_capacity(data.len());
+        let mut index = 0;
+        while index < data.len() {
+            let at_boundary = index == 0 || !is_path_char(data[index - 1]);
+            if at_boundary
+                && data[index].is_ascii_alphabetic()
+                && data[index + 1..].starts_with(b":/")
+            {
+                index += 2;
+                continue;
+            }
+            normalized.push(data[index]);
+            index += 1;
+        }
+        normalized
+    }
+
+    /// Spellings of the temp dir, longest first so a prefix doesn't shadow a longer match.
+    fn temp_dirs(&self) -> Vec<Vec<u8>> {
+        let temp_dir = env::temp_dir();
+        let mut candidates = vec![temp_dir.clone()];
+        if let Ok(canonical) = temp_dir.canonicalize() {
+            candidates.push(canonical);
+        }
+
+        let mut temp_dirs: Vec<Vec<u8>> = candidates
+            .iter()
+            .filter_map(|p| p.to_str())
+            .map(|p| {
+                let p = self.apply_separators(p.as_bytes());
+                p.trim_end_with(|c| c == '/').to_owned()
+            })
+            .filter(|p| !p.is_empty())
+            .collect();
+        temp_dirs.sort_by_key(|p| std::cmp::Reverse(p.len()));
+        temp_dirs.dedup();
+        temp_dirs
+    }
+}
+
+/// How [`Assert::normalize_text`][crate::assert::Assert::normalize_text] rewrites output.
+///
+/// By default, a byte order mark is removed and `\r\n` is rewritten to `\n`.  Output starting
+/// with a UTF-16 byte order mark is also converted to UTF-8.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalizeText {
+    strip_bom: bool,
+    unify_newlines: bool,
+    #[cfg(feature = "unicode")]
+    unicode: Option<UnicodeForm>,
+}
+
+impl NormalizeText {
+    /// Strip byte order marks and unify newlines.
+    pub fn new() -> Self {
+        Self {
+            strip_bom: true,
+            unify_newlines: true,
+            #[cfg(feature = "unicode")]
+            unicode: None,
+        }
+    }
+
+    /// Remove a UTF-8 or UTF-16 byte order mark, converting UTF-16 to UTF-8.
+    pub fn strip_bom(mut self, yes: bool) -> Self {
+        self.strip_bom = yes;
+        self
+    }
+
+    /// Rewrite `\r\n` to `\n`.
+    pub fn unify_newlines(mut self, yes: bool) -> Self {
+        self.unify_newlines = yes;
+        self
+    }
+
+    /// Rewrite text into a Unicode normalization `form`, so composed and decomposed characters,
+    /// like filenames from macOS, compare equal.
+    ///
+    /// Invalid UTF-8 is left as-is.
+    #[cfg(feature = "unicode")]
+    pub fn unicode(mut self, form: Option<UnicodeForm>) -> Self {
+        self.unicode = form;
+        self
+    }
+
+    /// The normalized data, and what was changed.
+    pub(crate) fn apply(&self, data: &[u8]) -> (Vec<u8>, Vec<&'static str>) {
+        let mut changes = Vec::new();
+        let mut normalized = data.to_vec();
+        if self.strip_bom {
+            if let Some(rest) = data.strip_prefix(b"\xef\xbb\xbf") {
+                normalized = rest.to_vec();
+                changes.push("UTF-8 BOM");
+            } else if let Some(rest) = data.strip_prefix(b"\xff\xfe") {
+                normalized = decode_utf16(rest, u16::from_le_bytes);
+                changes.push("UTF-16LE BOM");
+            } else if let Some(rest) = data.strip_prefix(b"\xfe\xff") {
+                normalized = decode_utf16(rest, u16::from_be_bytes);
+                changes.push("UTF-16BE BOM");
+            }
+        }
+        if self.unify_newlines && normalized.contains_str("\r\n") {
+            normalized = normalized.replace("\r\n", "\n");
+            changes.push("CRLF");
+        }
+        #[cfg(feature = "unicode")]
+        if let Some(form) = self.unicode {
+            if let Ok(text) = std::str::from_utf8(&normalized) {
+                let text = form.apply(text);
+                if text.as_bytes() != normalized.as_slice() {
+                    normalized = text.into_bytes();
+                    changes.push(form.name());
+                }
+            }
+        }
+        (normalized, changes)
+    }
+}
+
+/// A Unicode normalization form, see [`NormalizeText::unicode`].
+#[cfg(feature = "unicode")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UnicodeForm {
+    /// Canonical composition, like `é` as a single character
+    Nfc,
+    /// Compatibility composition, also folding variants, like `ﬁ` to `fi`
+    Nfkc,
+}
+
+#[cfg(feature = "unicode")]
+impl UnicodeForm {
+    fn apply(self, text: &str) -> String {
+        use unicode_normalization::UnicodeNormalization as _;
+
+        match self {
+            Self::Nfc => text.nfc().collect(),
+            Self::Nfkc => text.nfkc().collect(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Nfc => "NFC",
+            Self::Nfkc => "NFKC",
+        }
+    }
+}
+
+impl Default for NormalizeText {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn decode_utf16(data: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Vec<u8> {
+    let units = data.chunks(2).map(|pair| match pair {
+        [a, b] => from_bytes([*a, *b]),
+        // An odd trailing byte can't be decoded
+        _ => 0xfffd,
+    });
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect::<String>()
+        .into_bytes()
+}
+
+fn is_path_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'.' | b'/')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn separators() {
+        let normalized = NormalizePaths::new().apply(br"C:\Users\me\src\main.rs");
+        assert_eq!(normalized.as_bstr(), "C:/Users/me/src/main.rs");
+    }
+
+    #[test]
+    fn drive_letters() {
+        let normalized = NormalizePaths::new()
+            .strip_drive_letters(true)
+            .apply(br"error: C:\a\b.rs and d:\c.rs, not ab:\c or http://x");
+        assert_eq!(
+            normalized.as_bstr(),
+            "error: /a/b.rs and /c.rs, not ab:/c or http://x"
+        );
+    }
+
+    #[test]
+    fn temp_dir() {
+        let temp_file = env::temp_dir().join("assert_cmd").join("file.txt");
+        let output = format!("wrote {}\n", temp_file.display());
+        let normalized = NormalizePaths::new()
+            .strip_drive_letters(true)
+            .replace_temp_dir(true)
+            .apply(output.as_bytes());
+        assert_eq!(normalized.as_bstr(), "wrote [TEMP]/assert_cmd/file.txt\n");
+    }
+
+    #[test]
+    fn text() {
+        let (normalized, changes) = NormalizeText::new().apply(b"\xef\xbb\xbfa\r\nb\n");
+        assert_eq!(normalized.as_bstr(), "a\nb\n");
+        assert_eq!(changes, ["UTF-8 BOM", "CRLF"]);
+
+        let (normalized, changes) = NormalizeText::new().apply(b"\xff\xfea\x00\r\x00\n\x00");
+        assert_eq!(normalized.as_bstr(), "a\n");
+        assert_eq!(changes, ["UTF-16LE BOM", "CRLF"]);
+
+        let (normalized, changes) = NormalizeText::new().apply(b"a\n");
+        assert_eq!(normalized.as_bstr(), "a\n");
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn unicode() {
+        let nfc = NormalizeText::new().unicode(Some(UnicodeForm::Nfc));
+        let (normalized, changes) = nfc.apply("cafe\u{301}".as_bytes());
+        assert_eq!(normalized.as_bstr(), "caf\u{e9}");
+        assert_eq!(changes, ["NFC"]);
+
+        let nfkc = NormalizeText::new().unicode(Some(UnicodeForm::Nfkc));
+        let (normalized, _) = nfkc.apply("\u{fb01}le".as_bytes());
+        assert_eq!(normalized.as_bstr(), "file");
+    }
+}
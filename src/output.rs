@@ -1,9 +1,15 @@
 //! Simplify one-off runs of programs.
 
 use bstr::ByteSlice;
+use std::borrow::Cow;
 use std::error::Error;
+use std::ffi;
 use std::fmt;
+use std::fmt::Write as _;
+use std::io;
+use std::path;
 use std::process;
+use std::sync::Arc;
 
 /// Converts a type to an [`OutputResult`].
 ///
@@ -109,7 +115,7 @@ impl OutputOkExt for &mut process::Command {
         if output.status.success() {
             Ok(output)
         } else {
-            let error = OutputError::new(output).set_cmd(format!("{self:?}"));
+            let error = OutputError::new(output).set_cmd(render_command(self, false));
             Err(error)
         }
     }
@@ -117,8 +123,8 @@ impl OutputOkExt for &mut process::Command {
     fn unwrap_err(self) -> OutputError {
         match self.ok() {
             Ok(output) => panic!(
-                "Completed successfully:\ncommand=`{:?}`\nstdout=```{}```",
-                self,
+                "Completed successfully:\ncommand=`{}`\nstdout=```{}```",
+                render_command(self, false),
                 DebugBytes::new(&output.stdout)
             ),
             Err(err) => err,
@@ -126,6 +132,12 @@ impl OutputOkExt for &mut process::Command {
     }
 }
 
+impl OutputOkExt for Result<process::Output, io::Error> {
+    fn ok(self) -> OutputResult {
+        self.map_err(OutputError::with_cause)?.ok()
+    }
+}
+
 /// [`Output`] represented as a [`Result`].
 ///
 /// Generally produced by [`OutputOkExt`].
@@ -167,7 +179,7 @@ pub type OutputResult = Result<process::Output, OutputError>;
 #[derive(Debug)]
 pub struct OutputError {
     cmd: Option<String>,
-    stdin: Option<bstr::BString>,
+    stdin: Option<Arc<bstr::BString>>,
     cause: OutputCause,
 }
 
@@ -177,6 +189,15 @@ impl OutputError {
     /// [`Output`]: std::process::Output
     /// [`Error`]: std::error::Error
     pub fn new(output: process::Output) -> Self {
+        Self::from_shared(Arc::new(output))
+    }
+
+    /// Convert an [`Output`] shared with other values, like an [`Assert`], into an [`Error`].
+    ///
+    /// [`Output`]: std::process::Output
+    /// [`Assert`]: crate::assert::Assert
+    /// [`Error`]: std::error::Error
+    pub fn from_shared(output: Arc<process::Output>) -> Self {
         Self {
             cmd: None,
             stdin: None,
@@ -206,7 +227,12 @@ impl OutputError {
 
     /// Add the `stdin` for additional context.
     pub fn set_stdin(mut self, stdin: Vec<u8>) -> Self {
-        self.stdin = Some(bstr::BString::from(stdin));
+        self.stdin = Some(Arc::new(bstr::BString::from(stdin)));
+        self
+    }
+
+    pub(crate) fn set_shared_stdin(mut self, stdin: Arc<bstr::BString>) -> Self {
+        self.stdin = Some(stdin);
         self
     }
 
@@ -235,17 +261,37 @@ impl OutputError {
             OutputCause::Unexpected(_) => None,
         }
     }
+
+    /// Share the contained [`Output`] without copying it.
+    ///
+    /// [`Output`]: std::process::Output
+    pub fn shared_output(&self) -> Option<Arc<process::Output>> {
+        match self.cause {
+            OutputCause::Expected(ref e) => Some(Arc::clone(&e.output)),
+            OutputCause::Unexpected(_) => None,
+        }
+    }
 }
 
 impl Error for OutputError {}
 
 impl fmt::Display for OutputError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::redact::write(f, Report(self))
+    }
+}
+
+/// [`OutputError`]'s report, before redaction.
+struct Report<'a>(&'a OutputError);
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Report(error) = self;
         let palette = crate::Palette::color();
-        if let Some(ref cmd) = self.cmd {
+        if let Some(ref cmd) = error.cmd {
             writeln!(f, "{:#}={:#}", palette.key("command"), palette.value(cmd))?;
         }
-        if let Some(ref stdin) = self.stdin {
+        if let Some(ref stdin) = error.stdin {
             writeln!(
                 f,
                 "{:#}={:#}",
@@ -253,7 +299,7 @@ impl fmt::Display for OutputError {
                 palette.value(DebugBytes::new(stdin))
             )?;
         }
-        write!(f, "{:#}", self.cause)
+        write!(f, "{:#}", error.cause)
     }
 }
 
@@ -274,7 +320,7 @@ impl fmt::Display for OutputCause {
 
 #[derive(Debug)]
 struct Output {
-    output: process::Output,
+    output: Arc<process::Output>,
 }
 
 impl fmt::Display for Output {
@@ -296,6 +342,16 @@ pub(crate) fn output_fmt(output: &process::Output, f: &mut fmt::Formatter<'_>) -
         )?;
     }
 
+    writeln!(
+        f,
+        "{:#}=`{:#}`",
+        palette.key("sizes"),
+        palette.value(format_args!(
+            "stdout {}, stderr {}",
+            human_size(output.stdout.len()),
+            human_size(output.stderr.len())
+        ))
+    )?;
     write!(
         f,
         "{:#}={:#}\n{:#}={:#}\n",
@@ -307,6 +363,392 @@ pub(crate) fn output_fmt(output: &process::Output, f: &mut fmt::Formatter<'_>) -
     Ok(())
 }
 
+/// Render `bytes` with a binary unit, like `1.5 KiB`.
+pub(crate) fn human_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while 1024.0 <= size && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Render `duration` in the largest fitting unit, like `850ms` or `2m 05s`.
+pub(crate) fn human_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    if 60 <= secs {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else if 1 <= secs {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else if 1 <= duration.as_millis() {
+        format!("{}ms", duration.as_millis())
+    } else {
+        format!("{}µs", duration.as_micros())
+    }
+}
+
+/// Render `time` as an RFC 3339 UTC timestamp, like `2024-05-01T12:30:00.250Z`.
+pub(crate) fn timestamp(time: std::time::SystemTime) -> String {
+    let Ok(since) = time.duration_since(std::time::UNIX_EPOCH) else {
+        return format!("{time:?}");
+    };
+    let secs = since.as_secs();
+    // Days to a civil date, after Howard Hinnant's `civil_from_days`, shifted so eras start on
+    // March 1st, 0000
+    let days = secs / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    let time_of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        since.subsec_millis()
+    )
+}
+
+/// Render `cmd` as a shell command line, like `cd dir && KEY=value program arg`.
+///
+/// Unlike `cmd`'s `Debug`, only what needs quoting is quoted, and nothing is lost but
+/// [secrets][crate::redact].
+///
+/// `cleared` is whether `cmd` starts from an empty environment, rendered as `env -i`.
+pub(crate) fn render_command(cmd: &process::Command, cleared: bool) -> String {
+    let mut line = String::new();
+    if let Some(dir) = cmd.get_current_dir() {
+        let _ = write!(line, "cd {} && ", quote_os(display_path(dir).as_os_str()));
+    }
+    let mut parts = Vec::new();
+    let removed: Vec<_> = cmd
+        .get_envs()
+        .filter(|(_, value)| value.is_none())
+        .map(|(key, _)| key)
+        .collect();
+    if cleared {
+        parts.push("env -i".to_owned());
+    } else if !removed.is_empty() {
+        parts.push("env".to_owned());
+        parts.extend(
+            removed
+                .into_iter()
+                .map(|key| format!("-u {}", quote_os(key))),
+        );
+    }
+    parts.extend(cmd.get_envs().filter_map(|(key, value)| {
+        let value = if crate::redact::is_sensitive(key) {
+            crate::redact::PLACEHOLDER.to_owned()
+        } else {
+            quote_os(value?)
+        };
+        Some(format!("{}={value}", quote_os(key)))
+    }));
+    let program = display_path(path::Path::new(cmd.get_program()));
+    parts.push(quote_os(program.as_os_str()));
+    parts.extend(cmd.get_args().map(quote_os));
+    line.push_str(&parts.join(" "));
+    crate::redact::redact(&line).into_owned()
+}
+
+/// Describe how the environment `cmd` runs with differs from this process's, like
+/// `+ADDED=1, ~CHANGED=2, -REMOVED`, with the values of [sensitive][crate::redact::is_sensitive]
+/// variables hidden.
+///
+/// `cleared` is whether `cmd` starts from an empty environment.
+pub(crate) fn env_diff(cmd: &process::Command, cleared: bool) -> Option<String> {
+    let mut changes = Vec::new();
+    if cleared {
+        let kept: Vec<_> = cmd
+            .get_envs()
+            .filter(|(_, value)| value.is_some())
+            .map(|(key, _)| key)
+            .collect();
+        let removed = std::env::vars_os()
+            .filter(|(key, _)| !kept.iter().any(|kept| same_env_name(kept, key)))
+            .count();
+        changes.push(format!("cleared, {removed} inherited removed"));
+    }
+    for (key, value) in cmd.get_envs() {
+        let inherited = std::env::var_os(key);
+        match value {
+            Some(value) => {
+                let sign = match inherited {
+                    None => '+',
+                    Some(inherited) if inherited == value => continue,
+                    Some(_) => '~',
+                };
+                let value = if crate::redact::is_sensitive(key) {
+                    crate::redact::PLACEHOLDER.to_owned()
+                } else {
+                    quote_os(value)
+                };
+                changes.push(format!("{sign}{}={value}", quote_os(key)));
+            }
+            None if inherited.is_some() && !cleared => {
+                changes.push(format!("-{}", quote_os(key)));
+            }
+            None => {}
+        }
+    }
+    (!changes.is_empty()).then(|| crate::redact::redact(&changes.join(", ")).into_owned())
+}
+
+/// Longest path Windows accepts without an extended-length `\\?\` prefix.
+const MAX_PATH: usize = 260;
+
+/// `path` as users write it, without an extended-length `\\?\` prefix when it means the same
+/// without one.
+pub(crate) fn display_path(path: &path::Path) -> Cow<'_, path::Path> {
+    if !cfg!(windows) {
+        return Cow::Borrowed(path);
+    }
+    match path.to_str().and_then(strip_verbatim) {
+        Some(plain) => Cow::Owned(plain.into()),
+        None => Cow::Borrowed(path),
+    }
+}
+
+/// `path` with an extended-length `\\?\` prefix if it is too long to be used without one.
+pub(crate) fn extended_length(path: path::PathBuf) -> path::PathBuf {
+    if !cfg!(windows) {
+        return path;
+    }
+    match path.to_str().and_then(to_verbatim) {
+        Some(verbatim) => verbatim.into(),
+        None => path,
+    }
+}
+
+/// `path` without its `\\?\` or `\\?\UNC\` prefix, unless it is too long or has names, like
+/// with a trailing dot, that only the prefix allows.
+fn strip_verbatim(path: &str) -> Option<String> {
+    let plain = if let Some(share) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{share}")
+    } else {
+        let rest = path.strip_prefix(r"\\?\")?;
+        if !is_drive_absolute(rest) {
+            return None;
+        }
+        rest.to_owned()
+    };
+    let literal = plain.contains('/')
+        || plain
+            .split('\\')
+            .any(|name| name.ends_with('.') || name.ends_with(' '));
+    (plain.len() < MAX_PATH && !literal).then_some(plain)
+}
+
+/// `path` with a `\\?\` or `\\?\UNC\` prefix, if it is an absolute path of at least
+/// [`MAX_PATH`].
+fn to_verbatim(path: &str) -> Option<String> {
+    if path.len() < MAX_PATH || path.starts_with(r"\\?\") {
+        return None;
+    }
+    let path = path.replace('/', "\\");
+    // The prefix turns off resolving `.` and `..`
+    if path.split('\\').any(|name| name == "." || name == "..") {
+        return None;
+    }
+    if let Some(share) = path.strip_prefix(r"\\") {
+        Some(format!(r"\\?\UNC\{share}"))
+    } else if is_drive_absolute(&path) {
+        Some(format!(r"\\?\{path}"))
+    } else {
+        None
+    }
+}
+
+/// Whether `path` starts like `C:\`.
+fn is_drive_absolute(path: &str) -> bool {
+    matches!(path.as_bytes(), [drive, b':', b'\\', ..] if drive.is_ascii_alphabetic())
+}
+
+/// Whether `a` and `b` name the same environment variable, ignoring case on Windows.
+pub(crate) fn same_env_name(a: &ffi::OsStr, b: &ffi::OsStr) -> bool {
+    if cfg!(windows) {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// Quote `value` for a POSIX shell, when needed.
+///
+/// Control characters and anything that isn't valid Unicode are escaped with `$'...'`, like
+/// `$'\xff'` for a byte or `$'\uD800'` for an unpaired surrogate, so they can be told apart.
+pub(crate) fn quote_os(value: &ffi::OsStr) -> String {
+    let pieces = os_pieces(value);
+    let plain = !pieces.is_empty()
+        && pieces.iter().all(|piece| {
+            matches!(piece, Ok(c) if c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(*c))
+        });
+    if plain {
+        return pieces.into_iter().flatten().collect();
+    }
+    let escape = pieces.iter().any(|piece| match piece {
+        Ok(c) => c.is_control(),
+        Err(_) => true,
+    });
+    if !escape {
+        let value: String = pieces.into_iter().flatten().collect();
+        return format!("'{}'", value.replace('\'', r"'\''"));
+    }
+    let mut quoted = String::from("$'");
+    for piece in pieces {
+        match piece {
+            Ok('\\') => quoted.push_str(r"\\"),
+            Ok('\'') => quoted.push_str(r"\'"),
+            Ok('\n') => quoted.push_str(r"\n"),
+            Ok('\r') => quoted.push_str(r"\r"),
+            Ok('\t') => quoted.push_str(r"\t"),
+            Ok(c) if c.is_ascii_control() => {
+                let _ = write!(quoted, "\\x{:02x}", u32::from(c));
+            }
+            Ok(c) if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04X}", u32::from(c));
+            }
+            Ok(c) => quoted.push(c),
+            #[cfg(windows)]
+            Err(unit) => {
+                let _ = write!(quoted, "\\u{unit:04X}");
+            }
+            #[cfg(not(windows))]
+            Err(byte) => {
+                let _ = write!(quoted, "\\x{byte:02x}");
+            }
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// Split `value` into characters, and the raw units that aren't valid Unicode.
+#[cfg(unix)]
+fn os_pieces(value: &ffi::OsStr) -> Vec<Result<char, u32>> {
+    use std::os::unix::ffi::OsStrExt as _;
+
+    let mut pieces = Vec::new();
+    for chunk in ByteSlice::utf8_chunks(value.as_bytes()) {
+        pieces.extend(chunk.valid().chars().map(Ok));
+        pieces.extend(chunk.invalid().iter().map(|byte| Err(u32::from(*byte))));
+    }
+    pieces
+}
+
+/// Split `value` into characters, and the raw units that aren't valid Unicode.
+#[cfg(windows)]
+fn os_pieces(value: &ffi::OsStr) -> Vec<Result<char, u32>> {
+    use std::os::windows::ffi::OsStrExt as _;
+
+    char::decode_utf16(value.encode_wide())
+        .map(|c| c.map_err(|err| u32::from(err.unpaired_surrogate())))
+        .collect()
+}
+
+/// Split `value` into characters, and the raw units that aren't valid Unicode.
+#[cfg(not(any(unix, windows)))]
+fn os_pieces(value: &ffi::OsStr) -> Vec<Result<char, u32>> {
+    value.to_string_lossy().chars().map(Ok).collect()
+}
+
+/// Whether `expected` and `actual` are the same once all whitespace is removed.
+pub(crate) fn differs_only_in_whitespace(expected: &str, actual: &str) -> bool {
+    let non_whitespace = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    expected != actual && non_whitespace(expected) == non_whitespace(actual)
+}
+
+/// Render tabs, trailing spaces, carriage returns, and newlines visibly.
+pub(crate) fn visible_whitespace(text: &str) -> String {
+    let mut visible = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        let (line, newline) = line
+            .strip_suffix('\n')
+            .map(|line| (line, true))
+            .unwrap_or((line, false));
+        let (line, cr) = line
+            .strip_suffix('\r')
+            .map(|line| (line, true))
+            .unwrap_or((line, false));
+        let content = line.trim_end_matches([' ', '\t']);
+        visible.extend(content.chars().map(|c| match c {
+            '\t' => '→',
+            '\r' => '␍',
+            c => c,
+        }));
+        visible.extend(line[content.len()..].chars().map(|c| match c {
+            '\t' => '→',
+            _ => '·',
+        }));
+        if cr {
+            visible.push('␍');
+        }
+        if newline {
+            visible.push_str("↵\n");
+        }
+    }
+    visible
+}
+
+/// Remove ANSI escape sequences (CSI and OSC) from `data`.
+pub(crate) fn strip_ansi(data: &[u8]) -> Vec<u8> {
+    const ESC: u8 = 0x1b;
+    const BEL: u8 = 0x07;
+
+    let mut stripped = Vec::with_capacity(data.len());
+    let mut bytes = data.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        if byte != ESC {
+            stripped.push(byte);
+            continue;
+        }
+        match bytes.peek() {
+            Some(b'[') => {
+                bytes.next();
+                // Parameters and intermediates, up to and including the final byte
+                for byte in bytes.by_ref() {
+                    if (0x40..=0x7e).contains(&byte) {
+                        break;
+                    }
+                }
+            }
+            Some(b']') => {
+                bytes.next();
+                // Terminated by BEL or ST (`ESC \\`)
+                while let Some(byte) = bytes.next() {
+                    if byte == BEL {
+                        break;
+                    }
+                    if byte == ESC && bytes.peek() == Some(&b'\\') {
+                        bytes.next();
+                        break;
+                    }
+                }
+            }
+            Some(_) => {
+                bytes.next();
+            }
+            None => {}
+        }
+    }
+    stripped
+}
+
 #[derive(Debug)]
 pub(crate) struct DebugBytes<'a> {
     bytes: &'a [u8],
@@ -326,14 +768,12 @@ impl fmt::Display for DebugBytes<'_> {
 
 #[derive(Debug)]
 pub(crate) struct DebugBuffer {
-    buffer: bstr::BString,
+    buffer: Arc<bstr::BString>,
 }
 
 impl DebugBuffer {
-    pub(crate) fn new(buffer: Vec<u8>) -> Self {
-        DebugBuffer {
-            buffer: buffer.into(),
-        }
+    pub(crate) fn new(buffer: Arc<bstr::BString>) -> Self {
+        DebugBuffer { buffer }
     }
 }
 
@@ -430,6 +870,105 @@ fn write_debug_bstrs<'a>(
 
 #[cfg(test)]
 mod test {
+    use super::*;
+
+    #[test]
+    fn quote() {
+        assert_eq!(quote_os("--flag=a.txt".as_ref()), "--flag=a.txt");
+        assert_eq!(quote_os("".as_ref()), "''");
+        assert_eq!(quote_os("it's here".as_ref()), r"'it'\''s here'");
+        assert_eq!(quote_os("café".as_ref()), "'café'");
+        assert_eq!(quote_os("a\tb\n".as_ref()), r"$'a\tb\n'");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn quote_invalid_unicode() {
+        use std::os::unix::ffi::OsStrExt as _;
+
+        assert_eq!(
+            quote_os(ffi::OsStr::from_bytes(b"caf\xe9 'x'")),
+            r"$'caf\xe9 \'x\''"
+        );
+    }
+
+    #[test]
+    fn human() {
+        use std::time::Duration;
+
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(human_size(3 << 20), "3.0 MiB");
+        assert_eq!(human_duration(Duration::from_micros(40)), "40µs");
+        assert_eq!(human_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(human_duration(Duration::from_millis(1250)), "1.25s");
+        assert_eq!(human_duration(Duration::from_secs(125)), "2m 05s");
+        let time = std::time::UNIX_EPOCH + Duration::from_millis(1_709_210_096_250);
+        assert_eq!(timestamp(time), "2024-02-29T12:34:56.250Z");
+    }
+
+    #[test]
+    fn env_changes() {
+        let mut cmd = process::Command::new("prog");
+        cmd.env("ASSERT_CMD_TEST_ADDED", "a b")
+            .env("ASSERT_CMD_TEST_TOKEN", "hunter2")
+            .env_remove("ASSERT_CMD_TEST_UNSET");
+        assert_eq!(
+            env_diff(&cmd, false).unwrap(),
+            "+ASSERT_CMD_TEST_ADDED='a b', +ASSERT_CMD_TEST_TOKEN=[redacted]"
+        );
+        assert_eq!(env_diff(&process::Command::new("prog"), false), None);
+    }
+
+    #[test]
+    fn verbatim_paths() {
+        assert_eq!(
+            strip_verbatim(r"\\?\C:\target\debug\tool.exe").unwrap(),
+            r"C:\target\debug\tool.exe"
+        );
+        assert_eq!(
+            strip_verbatim(r"\\?\UNC\server\share\tool.exe").unwrap(),
+            r"\\server\share\tool.exe"
+        );
+        assert_eq!(strip_verbatim(r"\\?\C:\odd.\tool.exe"), None);
+        assert_eq!(strip_verbatim(r"C:\tool.exe"), None);
+
+        let long = format!(r"C:\{}\tool.exe", "a".repeat(MAX_PATH));
+        assert_eq!(to_verbatim(&long).unwrap(), format!(r"\\?\{long}"));
+        assert_eq!(strip_verbatim(&format!(r"\\?\{long}")), None);
+        let share = format!(r"\\server\share\{}", "a".repeat(MAX_PATH));
+        assert!(to_verbatim(&share)
+            .unwrap()
+            .starts_with(r"\\?\UNC\server\share\"));
+        assert_eq!(to_verbatim(r"C:\tool.exe"), None);
+    }
+
+    #[test]
+    fn env_names() {
+        assert!(same_env_name("PATH".as_ref(), "PATH".as_ref()));
+        assert_eq!(
+            same_env_name("Path".as_ref(), "PATH".as_ref()),
+            cfg!(windows)
+        );
+    }
+
+    #[test]
+    fn command() {
+        let mut cmd = process::Command::new("prog");
+        cmd.args(["plain", "two words"])
+            .env("KEY", "a b")
+            .env_remove("GONE")
+            .current_dir("some dir");
+        assert_eq!(
+            render_command(&cmd, false),
+            "cd 'some dir' && env -u GONE KEY='a b' prog plain 'two words'"
+        );
+
+        let mut cmd = process::Command::new("prog");
+        cmd.env_clear().env("KEY", "a b").env_remove("GONE");
+        assert_eq!(render_command(&cmd, true), "env -i KEY='a b' prog");
+    }
+
     #[test]
     fn format_bytes() {
         let mut s = String::new();
@@ -512,6 +1051,24 @@ mod test {
         );
     }
 
+    #[test]
+    fn visible_whitespace() {
+        assert_eq!(super::visible_whitespace("a\tb  \r\nc\n"), "a→b··␍↵\nc↵\n");
+    }
+
+    #[test]
+    fn differs_only_in_whitespace() {
+        assert!(super::differs_only_in_whitespace("a b\n", "a\tb\r\n"));
+        assert!(!super::differs_only_in_whitespace("a b\n", "a b\n"));
+        assert!(!super::differs_only_in_whitespace("a b\n", "a c\n"));
+    }
+
+    #[test]
+    fn strip_ansi() {
+        let styled = b"\x1b[1;31merror\x1b[0m: \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07\n";
+        assert_eq!(super::strip_ansi(styled), b"error: link\n");
+    }
+
     #[test]
     fn no_trailing_newline() {
         let s = "no\ntrailing\nnewline";
@@ -0,0 +1,136 @@
+//! Encode platform-specific expectations in a single test.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::platform::skip_on;
+//! use assert_cmd::platform::Platform;
+//!
+//! if skip_on(Platform::os("windows")) {
+//!     return;
+//! }
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", "hello")
+//!     .assert()
+//!     .stdout_per_platform(&[
+//!         (Platform::os("macos"), "hello\n"),
+//!         (Platform::any(), "hello\n"),
+//!     ]);
+//! ```
+
+use std::env;
+use std::fmt;
+
+/// A set of platforms, matched against the [`std::env::consts`] of the running test.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Platform {
+    os: Option<String>,
+    arch: Option<String>,
+    family: Option<String>,
+}
+
+impl Platform {
+    /// Every platform.
+    pub fn any() -> Self {
+        Self {
+            os: None,
+            arch: None,
+            family: None,
+        }
+    }
+
+    /// Platforms where [`std::env::consts::OS`] is `os`, like `"linux"` or `"windows"`.
+    pub fn os(os: impl Into<String>) -> Self {
+        Self::any().and_os(os)
+    }
+
+    /// Platforms where [`std::env::consts::ARCH`] is `arch`, like `"x86_64"` or `"aarch64"`.
+    pub fn arch(arch: impl Into<String>) -> Self {
+        Self::any().and_arch(arch)
+    }
+
+    /// Platforms where [`std::env::consts::FAMILY`] is `family`, `"unix"` or `"windows"`.
+    pub fn family(family: impl Into<String>) -> Self {
+        Self::any().and_family(family)
+    }
+
+    /// Narrow to platforms where [`std::env::consts::OS`] is also `os`.
+    pub fn and_os(mut self, os: impl Into<String>) -> Self {
+        self.os = Some(os.into());
+        self
+    }
+
+    /// Narrow to platforms where [`std::env::consts::ARCH`] is also `arch`.
+    pub fn and_arch(mut self, arch: impl Into<String>) -> Self {
+        self.arch = Some(arch.into());
+        self
+    }
+
+    /// Narrow to platforms where [`std::env::consts::FAMILY`] is also `family`.
+    pub fn and_family(mut self, family: impl Into<String>) -> Self {
+        self.family = Some(family.into());
+        self
+    }
+
+    /// Whether the running test is on one of these platforms.
+    pub fn is_current(&self) -> bool {
+        let matches = |expected: &Option<String>, actual: &str| {
+            expected
+                .as_deref()
+                .map(|expected| expected == actual)
+                .unwrap_or(true)
+        };
+        matches(&self.os, env::consts::OS)
+            && matches(&self.arch, env::consts::ARCH)
+            && matches(&self.family, env::consts::FAMILY)
+    }
+
+    /// Describe the running test's platform.
+    pub(crate) fn current() -> String {
+        format!("{}-{}", env::consts::OS, env::consts::ARCH)
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<_> = [
+            ("os", &self.os),
+            ("arch", &self.arch),
+            ("family", &self.family),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.as_ref().map(|value| format!("{name}={value}")))
+        .collect();
+        if parts.is_empty() {
+            f.write_str("any")
+        } else {
+            f.write_str(&parts.join(" "))
+        }
+    }
+}
+
+/// Whether to skip a test on `platform`, printing a message when it should be.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use assert_cmd::platform::skip_on;
+/// use assert_cmd::platform::Platform;
+///
+/// if skip_on(Platform::family("windows")) {
+///     return;
+/// }
+/// ```
+pub fn skip_on(platform: Platform) -> bool {
+    if !platform.is_current() {
+        return false;
+    }
+    crate::skip::report(format_args!(
+        "not supported on {platform} ({})",
+        Platform::current()
+    ));
+    true
+}
@@ -0,0 +1,89 @@
+//! Run `pre_exec` hooks from a single hook, added to `std` when the command is run.
+//!
+//! `std` runs hooks in the order they were added and can't reorder them, but socket activation
+//! has to `exec` from the last one.  Keeping the hooks here lets them be added after the command
+//! was first run, and still run before it `exec`s.
+//!
+//! The parent only locks a list to add a hook, never while spawning, so the child can read it
+//! with `try_lock`.
+
+use std::fmt;
+use std::io;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+type Hook = Box<dyn FnMut() -> io::Result<()> + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct PreExec {
+    /// Run first, for a copy of another command.
+    parent: Option<Arc<PreExec>>,
+    hooks: Mutex<Vec<Hook>>,
+    /// How many hooks this and `parent` have, readable without locking.
+    len: AtomicUsize,
+}
+
+impl PreExec {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `parent`'s hooks before any added to this.
+    ///
+    /// `parent` must not be changed while this is used.
+    pub(crate) fn after(parent: Arc<Self>) -> Self {
+        let len = AtomicUsize::new(parent.len.load(Ordering::Relaxed));
+        Self {
+            parent: Some(parent),
+            hooks: Mutex::default(),
+            len,
+        }
+    }
+
+    /// Add `hook`, to run after those already added.
+    ///
+    /// # Safety
+    ///
+    /// See [`std::os::unix::process::CommandExt::pre_exec`].
+    pub(crate) unsafe fn push<F>(&self, hook: F)
+    where
+        F: FnMut() -> io::Result<()> + Send + Sync + 'static,
+    {
+        self.hooks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Box::new(hook));
+        self.len.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len.load(Ordering::Relaxed) == 0
+    }
+
+    /// Run the hooks in the child, in the order they were added.
+    ///
+    /// This must only make async-signal-safe calls.
+    pub(crate) fn run(&self) -> io::Result<()> {
+        if let Some(parent) = &self.parent {
+            parent.run()?;
+        }
+        let Ok(mut hooks) = self.hooks.try_lock() else {
+            return Err(io::Error::from_raw_os_error(libc::EAGAIN));
+        };
+        for hook in hooks.iter_mut() {
+            hook()?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PreExec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreExec")
+            .field("hooks", &self.len.load(Ordering::Relaxed))
+            .finish()
+    }
+}
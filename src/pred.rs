@@ -0,0 +1,493 @@
+//! Output predicates whose failures show the relevant lines of the output.
+//!
+//! A failing [`predicates::str::contains`] only reports that the predicate failed, leaving the
+//! whole output to be read through.  These predicates instead show, with line numbers, where the
+//! text was closest to matching, the line being checked, or the section between markers.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::pred;
+//! use predicates::prelude::*;
+//!
+//! Command::cargo_bin("bin_fixture")
+//!     .unwrap()
+//!     .env("stdout", "Compiling\n--- summary ---\n3 passed\n--- end ---\n")
+//!     .assert()
+//!     .stdout(pred::starts_with("Compiling"))
+//!     .stdout(pred::line(3, predicate::str::ends_with("passed")))
+//!     .stdout(pred::between_markers(
+//!         "--- summary ---",
+//!         "--- end ---",
+//!         predicate::str::contains("3 passed"),
+//!     ));
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use bstr::ByteSlice;
+use predicates_core::reflection::Case;
+use predicates_core::reflection::PredicateReflection;
+use predicates_core::reflection::Product;
+use predicates_core::Predicate;
+
+/// Lines of output shown around the lines of interest.
+const CONTEXT: usize = 2;
+
+/// Most lines shown in one excerpt.
+const MAX_LINES: usize = 20;
+
+/// Output contains `text`.
+///
+/// On failure, shows the lines where the longest leading part of `text` was found.
+pub fn contains(text: impl Into<String>) -> ContainsPredicate {
+    ContainsPredicate { text: text.into() }
+}
+
+/// Output starts with `text`.
+///
+/// On failure, shows the first lines of the output and where they differ from `text`.
+pub fn starts_with(text: impl Into<String>) -> StartsWithPredicate {
+    StartsWithPredicate { text: text.into() }
+}
+
+/// Line `number` of the output, counting from 1, satisfies `pred`.
+///
+/// On failure, shows the line with the lines around it.
+///
+/// # Panics
+///
+/// If `number` is 0.
+pub fn line<P>(number: usize, pred: P) -> LinePredicate<P>
+where
+    P: Predicate<str>,
+{
+    assert!(0 < number, "lines are numbered from 1");
+    LinePredicate { number, pred }
+}
+
+/// The lines between the first line containing `start` and the next line containing `end`
+/// satisfy `pred`.
+///
+/// The marker lines aren't part of the section.  On failure, shows the section, or which marker
+/// is missing.
+pub fn between_markers<P>(
+    start: impl Into<String>,
+    end: impl Into<String>,
+    pred: P,
+) -> SectionPredicate<P>
+where
+    P: Predicate<str>,
+{
+    SectionPredicate {
+        start: start.into(),
+        end: Some(end.into()),
+        pred,
+    }
+}
+
+/// The lines after the first line containing `heading`, up to the next blank line or the end of
+/// the output, satisfy `pred`.
+///
+/// On failure, shows the section, or that the heading is missing.
+pub fn under_heading<P>(heading: impl Into<String>, pred: P) -> SectionPredicate<P>
+where
+    P: Predicate<str>,
+{
+    SectionPredicate {
+        start: heading.into(),
+        end: None,
+        pred,
+    }
+}
+
+/// Output contains text, created with [`contains`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContainsPredicate {
+    text: String,
+}
+
+impl PredicateReflection for ContainsPredicate {}
+
+impl Predicate<[u8]> for ContainsPredicate {
+    fn eval(&self, variable: &[u8]) -> bool {
+        variable.contains_str(&self.text)
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<Case<'a>> {
+        let actual = self.eval(variable);
+        if expected != actual {
+            return None;
+        }
+        let case = Case::new(Some(self), actual);
+        let output = Lines::new(variable);
+        if let Some(offset) = variable.find(&self.text) {
+            let found = output.spanning(offset, self.text.len());
+            return Some(
+                case.add_product(Product::new("found at line", found.start + 1))
+                    .add_product(Product::new("lines", output.excerpt(found))),
+            );
+        }
+        // Show where the most of `text` matched, to point at a typo or a changed word
+        let closest = self
+            .text
+            .char_indices()
+            .map(|(i, c)| &self.text[..i + c.len_utf8()])
+            .take_while(|prefix| variable.contains_str(prefix))
+            .last();
+        Some(match closest {
+            Some(prefix) => {
+                let offset = variable.find(prefix).unwrap_or_default();
+                let found = output.spanning(offset, prefix.len());
+                case.add_product(Product::new(
+                    "closest",
+                    format!("{prefix:?} at line {}", found.start + 1),
+                ))
+                .add_product(Product::new("lines", output.excerpt(found)))
+            }
+            None => case.add_product(Product::new("lines", output.excerpt(0..0))),
+        })
+    }
+}
+
+impl fmt::Display for ContainsPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var.contains({:?})", self.text)
+    }
+}
+
+/// Output starts with text, created with [`starts_with`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartsWithPredicate {
+    text: String,
+}
+
+impl PredicateReflection for StartsWithPredicate {}
+
+impl Predicate<[u8]> for StartsWithPredicate {
+    fn eval(&self, variable: &[u8]) -> bool {
+        variable.starts_with(self.text.as_bytes())
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<Case<'a>> {
+        let actual = self.eval(variable);
+        if expected != actual {
+            return None;
+        }
+        let output = Lines::new(variable);
+        let mut case = Case::new(Some(self), actual);
+        let lines = if actual {
+            output.spanning(0, self.text.len())
+        } else {
+            let differs = variable
+                .iter()
+                .zip(self.text.as_bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let at = output.spanning(differs, 0);
+            let column = variable[..differs]
+                .rfind_byte(b'\n')
+                .map(|newline| differs - newline - 1)
+                .unwrap_or(differs);
+            case = case.add_product(Product::new(
+                "differs at",
+                format!("line {}, column {}", at.start + 1, column + 1),
+            ));
+            at
+        };
+        Some(case.add_product(Product::new("lines", output.excerpt(lines))))
+    }
+}
+
+impl fmt::Display for StartsWithPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var.starts_with({:?})", self.text)
+    }
+}
+
+/// One line of output satisfies a predicate, created with [`line()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinePredicate<P> {
+    number: usize,
+    pred: P,
+}
+
+impl<P> PredicateReflection for LinePredicate<P> where P: Predicate<str> {}
+
+impl<P> Predicate<[u8]> for LinePredicate<P>
+where
+    P: Predicate<str>,
+{
+    fn eval(&self, variable: &[u8]) -> bool {
+        Lines::new(variable)
+            .get(self.number - 1)
+            .is_some_and(|line| self.pred.eval(line))
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<Case<'a>> {
+        let output = Lines::new(variable);
+        let index = self.number - 1;
+        let Some(line) = output.get(index) else {
+            // A missing line fails the predicate either way
+            if expected {
+                return None;
+            }
+            return Some(
+                Case::new(Some(self), false)
+                    .add_product(Product::new("line count", output.len()))
+                    .add_product(Product::new(
+                        "lines",
+                        output.excerpt(output.len()..output.len()),
+                    )),
+            );
+        };
+        let child = self.pred.find_case(expected, line)?;
+        Some(
+            Case::new(Some(self), expected)
+                .add_child(child)
+                .add_product(Product::new("lines", output.excerpt(index..index + 1))),
+        )
+    }
+}
+
+impl<P> fmt::Display for LinePredicate<P>
+where
+    P: Predicate<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var.line({}) satisfies {}", self.number, self.pred)
+    }
+}
+
+/// A section of output satisfies a predicate, created with [`between_markers`] or
+/// [`under_heading`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionPredicate<P> {
+    start: String,
+    /// [`None`] for a section ending at a blank line.
+    end: Option<String>,
+    pred: P,
+}
+
+impl<P> SectionPredicate<P> {
+    /// Where the section is, from the first line after the start marker to the end marker line.
+    fn find(&self, output: &Lines) -> Result<Range<usize>, String> {
+        let start = output
+            .lines
+            .iter()
+            .position(|line| line.contains(&self.start))
+            .ok_or_else(|| format!("start marker {:?} not found", self.start))?;
+        let Some(end) = &self.end else {
+            let end = output.lines[start + 1..]
+                .iter()
+                .position(|line| line.trim().is_empty())
+                .map(|offset| start + 1 + offset)
+                .unwrap_or(output.len());
+            return Ok(start + 1..end);
+        };
+        let end = output.lines[start + 1..]
+            .iter()
+            .position(|line| line.contains(end))
+            .map(|offset| start + 1 + offset)
+            .ok_or_else(|| format!("end marker {end:?} not found after line {}", start + 1))?;
+        Ok(start + 1..end)
+    }
+}
+
+impl<P> PredicateReflection for SectionPredicate<P> where P: Predicate<str> {}
+
+impl<P> Predicate<[u8]> for SectionPredicate<P>
+where
+    P: Predicate<str>,
+{
+    fn eval(&self, variable: &[u8]) -> bool {
+        let output = Lines::new(variable);
+        self.find(&output)
+            .is_ok_and(|section| self.pred.eval(&output.join(section)))
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<Case<'a>> {
+        let output = Lines::new(variable);
+        let section = match self.find(&output) {
+            Ok(section) => section,
+            Err(missing) => {
+                // A missing marker fails the predicate either way
+                if expected {
+                    return None;
+                }
+                return Some(
+                    Case::new(Some(self), false)
+                        .add_product(Product::new("missing", missing))
+                        .add_product(Product::new("lines", output.excerpt(0..0))),
+                );
+            }
+        };
+        let child = self
+            .pred
+            .find_case(expected, &output.join(section.clone()))?;
+        let lines = if section.is_empty() {
+            format!("empty, after line {}", section.start)
+        } else {
+            format!("lines {}-{}", section.start + 1, section.end)
+        };
+        Some(
+            Case::new(Some(self), expected)
+                .add_child(child)
+                .add_product(Product::new("section", lines))
+                .add_product(Product::new("lines", output.excerpt(section))),
+        )
+    }
+}
+
+impl<P> fmt::Display for SectionPredicate<P>
+where
+    P: Predicate<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.end {
+            Some(end) => write!(
+                f,
+                "var.between({:?}, {end:?}) satisfies {}",
+                self.start, self.pred
+            ),
+            None => write!(
+                f,
+                "var.under_heading({:?}) satisfies {}",
+                self.start, self.pred
+            ),
+        }
+    }
+}
+
+/// Output split into lines, for showing numbered excerpts.
+struct Lines {
+    lines: Vec<String>,
+    /// Byte offset of the start of each line.
+    offsets: Vec<usize>,
+}
+
+impl Lines {
+    fn new(output: &[u8]) -> Self {
+        let mut lines = Vec::new();
+        let mut offsets = Vec::new();
+        let mut offset = 0;
+        for line in output.lines_with_terminator() {
+            offsets.push(offset);
+            offset += line.len();
+            lines.push(line.trim_end_with(|c| c == '\n' || c == '\r'));
+        }
+        Self {
+            lines: lines
+                .into_iter()
+                .map(|line| line.to_str_lossy().into_owned())
+                .collect(),
+            offsets,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn get(&self, index: usize) -> Option<&str> {
+        self.lines.get(index).map(String::as_str)
+    }
+
+    fn join(&self, range: Range<usize>) -> String {
+        self.lines[range].join("\n")
+    }
+
+    /// The lines covering `len` bytes from `offset`.
+    fn spanning(&self, offset: usize, len: usize) -> Range<usize> {
+        let line_of = |offset: usize| {
+            self.offsets
+                .partition_point(|start| *start <= offset)
+                .saturating_sub(1)
+        };
+        let start = line_of(offset);
+        let end = line_of(offset + len.saturating_sub(1)).max(start) + 1;
+        start..end.min(self.len().max(start))
+    }
+
+    /// Render `marked`, and the lines around it, numbered from 1.
+    fn excerpt(&self, marked: Range<usize>) -> String {
+        if self.lines.is_empty() {
+            return "(no output)".to_owned();
+        }
+        let start = marked.start.saturating_sub(CONTEXT).min(self.len() - 1);
+        let end = (marked.end + CONTEXT)
+            .max(start + 1)
+            .min(self.len())
+            .min(start + MAX_LINES);
+        let width = end.to_string().len();
+        let mut excerpt = String::new();
+        for index in start..end {
+            let mark = if marked.contains(&index) { '>' } else { ' ' };
+            excerpt.push_str(&format!(
+                "\n{mark} {:>width$} | {}",
+                index + 1,
+                self.lines[index]
+            ));
+        }
+        if end < self.len() {
+            excerpt.push_str(&format!("\n  ({} more lines)", self.len() - end));
+        }
+        excerpt
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use predicates::prelude::*;
+
+    #[test]
+    fn excerpt() {
+        let output = Lines::new(b"one\ntwo\nthree\nfour\nfive\nsix\nseven\n");
+        assert_eq!(
+            output.excerpt(3..4),
+            "\n  2 | two\n  3 | three\n> 4 | four\n  5 | five\n  6 | six\n  (1 more lines)"
+        );
+        assert_eq!(output.spanning(4, 7), 1..3);
+        assert_eq!(Lines::new(b"").excerpt(0..0), "(no output)");
+    }
+
+    #[test]
+    fn contains_shows_closest() {
+        let output = b"building\nerror: file not found\ndone\n";
+        let pred = contains("error: file missing");
+        assert!(!pred.eval(output));
+        let case = pred.find_case(false, output).unwrap();
+        let products: Vec<_> = case.products().map(|p| p.value().to_string()).collect();
+        assert_eq!(products[0], r#""error: file " at line 2"#);
+        assert!(products[1].contains("> 2 | error: file not found"));
+    }
+
+    #[test]
+    fn line_and_markers() {
+        let output = b"a\n[begin]\nb\nc\n[end]\nd\n";
+        assert!(line(3, predicate::str::diff("b")).eval(output));
+        assert!(!line(9, predicate::str::diff("b")).eval(output));
+        assert!(between_markers("[begin]", "[end]", predicate::str::diff("b\nc")).eval(output));
+        let pred = between_markers("[begin]", "[stop]", predicate::always());
+        let case = pred.find_case(false, output).unwrap();
+        let missing = case.products().next().unwrap().value().to_string();
+        assert_eq!(missing, r#"end marker "[stop]" not found after line 2"#);
+
+        let output = b"Usage:\n  tool run\n\nOptions:\n  -q\n";
+        assert!(under_heading("Usage:", predicate::str::diff("  tool run")).eval(output));
+        assert!(under_heading("Options:", predicate::str::diff("  -q")).eval(output));
+    }
+
+    #[test]
+    fn starts_with_shows_column() {
+        let output = b"version 1.2.3\n";
+        let pred = starts_with("version 1.3");
+        let case = pred.find_case(false, output).unwrap();
+        let differs = case.products().next().unwrap().value().to_string();
+        assert_eq!(differs, "line 1, column 11");
+    }
+}
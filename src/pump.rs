@@ -0,0 +1,497 @@
+//! Move data between a child's stdio pipes and memory, with deadlines.
+//!
+//! On Unix, this is done on the calling thread by `poll`ing the pipes, so running a command
+//! doesn't cost any thread creation.  Elsewhere, a thread is used per pipe.
+
+use std::io;
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+pub(crate) use imp::Pump;
+
+/// Data to write to a child's stdin.
+#[derive(Clone, Debug)]
+pub(crate) struct Input {
+    data: Arc<bstr::BString>,
+    /// Offsets into `data` to pause at, in order, and for how long
+    pauses: Vec<(usize, Duration)>,
+}
+
+impl Input {
+    pub(crate) fn new(data: Arc<bstr::BString>, pauses: Vec<(usize, Duration)>) -> Self {
+        Self { data, pauses }
+    }
+
+    /// Each run of data, with how long to pause after it.
+    #[cfg(any(not(unix), test))]
+    fn chunks(&self) -> impl Iterator<Item = (&[u8], Duration)> {
+        let mut start = 0;
+        let mut pauses = self.pauses.iter();
+        std::iter::from_fn(move || {
+            if start == self.data.len() && pauses.len() == 0 {
+                return None;
+            }
+            let (end, pause) = pauses
+                .next()
+                .copied()
+                .unwrap_or((self.data.len(), Duration::ZERO));
+            let chunk = &self.data[start..end];
+            start = end;
+            Some((chunk, pause))
+        })
+    }
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+
+    use super::io;
+    use super::process;
+    use super::Input;
+    use super::Instant;
+
+    pub(crate) struct Pump {
+        stdin: Option<Writer>,
+        stdout: Option<process::ChildStdout>,
+        stderr: Option<process::ChildStderr>,
+        /// Close `stdout` once this much is read
+        stdout_limit: Option<usize>,
+        out: Vec<u8>,
+        err: Vec<u8>,
+    }
+
+    struct Writer {
+        pipe: process::ChildStdin,
+        input: Input,
+        written: usize,
+        /// Index of the next pause in `input`
+        next_pause: usize,
+        /// When to resume writing after a pause
+        resume_at: Option<Instant>,
+    }
+
+    impl Writer {
+        /// End of the data to write before the next pause.
+        fn chunk_end(&self) -> usize {
+            self.input
+                .pauses
+                .get(self.next_pause)
+                .map(|(offset, _)| *offset)
+                .unwrap_or(self.input.data.len())
+        }
+
+        /// Start the next pause if the current chunk is written.
+        fn pause_if_due(&mut self) {
+            if let Some((offset, pause)) = self.input.pauses.get(self.next_pause) {
+                if self.written == *offset {
+                    self.next_pause += 1;
+                    self.resume_at = Some(Instant::now() + *pause);
+                }
+            }
+        }
+
+        /// Whether the writer is paused at `now`, ending the pause if it is over.
+        fn is_paused(&mut self, now: Instant) -> bool {
+            match self.resume_at {
+                Some(resume_at) if now < resume_at => true,
+                Some(_) => {
+                    self.resume_at = None;
+                    self.pause_if_due();
+                    self.resume_at.is_some()
+                }
+                None => false,
+            }
+        }
+
+        fn is_done(&self) -> bool {
+            self.written == self.input.data.len()
+                && self.resume_at.is_none()
+                && self.next_pause == self.input.pauses.len()
+        }
+    }
+
+    impl Pump {
+        pub(crate) fn new(
+            child: &mut process::Child,
+            input: Option<Input>,
+            stdout_limit: Option<usize>,
+        ) -> io::Result<Self> {
+            let stdin = child.stdin.take();
+            let stdin = match (stdin, input) {
+                (Some(pipe), Some(input)) => {
+                    set_nonblocking(&pipe)?;
+                    let mut writer = Writer {
+                        pipe,
+                        input,
+                        written: 0,
+                        next_pause: 0,
+                        resume_at: None,
+                    };
+                    writer.pause_if_due();
+                    Some(writer)
+                }
+                // Close stdin so the child sees EOF
+                (_, None) | (None, _) => None,
+            };
+            let stdout = child.stdout.take();
+            if let Some(stdout) = &stdout {
+                set_nonblocking(stdout)?;
+            }
+            let stderr = child.stderr.take();
+            if let Some(stderr) = &stderr {
+                set_nonblocking(stderr)?;
+            }
+            Ok(Self {
+                stdin,
+                stdout,
+                stderr,
+                stdout_limit,
+                out: Vec::new(),
+                err: Vec::new(),
+            })
+        }
+
+        /// Write all of the input, returning `false` if `limit` was reached first.
+        pub(crate) fn write_stdin(&mut self, limit: Option<Instant>) -> io::Result<bool> {
+            self.pump(limit, |pump| pump.stdin.is_none())
+        }
+
+        /// Read stdout and stderr to EOF, returning `false` if `limit` was reached first.
+        pub(crate) fn read_to_end(&mut self, limit: Option<Instant>) -> io::Result<bool> {
+            self.pump(limit, |pump| {
+                pump.stdin.is_none() && pump.stdout.is_none() && pump.stderr.is_none()
+            })
+        }
+
+        /// Stop writing stdin.
+        pub(crate) fn close_stdin(&mut self) {
+            self.stdin = None;
+        }
+
+        pub(crate) fn finish(self) -> (Vec<u8>, Vec<u8>) {
+            (self.out, self.err)
+        }
+
+        fn pump(
+            &mut self,
+            limit: Option<Instant>,
+            done: impl Fn(&Self) -> bool,
+        ) -> io::Result<bool> {
+            const STDIN: usize = 0;
+            const STDOUT: usize = 1;
+            const STDERR: usize = 2;
+
+            loop {
+                let now = Instant::now();
+                // Close stdin so the child sees EOF
+                if self.stdin.as_ref().is_some_and(Writer::is_done) {
+                    self.stdin = None;
+                }
+                let paused = self.stdin.as_mut().is_some_and(|w| w.is_paused(now));
+                if done(self) {
+                    return Ok(true);
+                }
+                if limit.is_some_and(|limit| limit <= now) {
+                    return Ok(false);
+                }
+                let wake = match (limit, self.stdin.as_ref().and_then(|w| w.resume_at)) {
+                    (Some(limit), Some(resume_at)) => Some(limit.min(resume_at)),
+                    (limit, resume_at) => limit.or(resume_at),
+                };
+                let timeout = match wake {
+                    Some(wake) => {
+                        // Round up so we don't spin on sub-millisecond remainders
+                        let remaining = wake.saturating_duration_since(now);
+                        let millis = remaining.as_millis() + 1;
+                        libc::c_int::try_from(millis).unwrap_or(libc::c_int::MAX)
+                    }
+                    None => -1,
+                };
+
+                let stdin = self.stdin.as_ref().filter(|_| !paused);
+                let mut fds = [
+                    poll_fd(stdin.map(|w| w.pipe.as_raw_fd()), libc::POLLOUT),
+                    poll_fd(self.stdout.as_ref().map(AsRawFd::as_raw_fd), libc::POLLIN),
+                    poll_fd(self.stderr.as_ref().map(AsRawFd::as_raw_fd), libc::POLLIN),
+                ];
+                // SAFETY: `fds` is a valid, appropriately sized array for the duration of the call
+                let ready =
+                    unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
+                if ready < 0 {
+                    let err = io::Error::last_os_error();
+                    if err.kind() == io::ErrorKind::Interrupted {
+                        continue;
+                    }
+                    return Err(err);
+                }
+
+                if fds[STDIN].revents != 0 {
+                    self.write_some();
+                }
+                if fds[STDOUT].revents != 0
+                    && read_some(&mut self.stdout, &mut self.out, self.stdout_limit)
+                {
+                    // Closing the read end breaks the pipe for the child
+                    self.stdout = None;
+                }
+                if fds[STDERR].revents != 0 && read_some(&mut self.stderr, &mut self.err, None) {
+                    self.stderr = None;
+                }
+            }
+        }
+
+        fn write_some(&mut self) {
+            let Some(writer) = &mut self.stdin else {
+                return;
+            };
+            let end = writer.chunk_end();
+            match writer.pipe.write(&writer.input.data[writer.written..end]) {
+                Ok(n) => {
+                    writer.written += n;
+                    writer.pause_if_due();
+                    if writer.is_done() {
+                        self.stdin = None;
+                    }
+                }
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+                    ) => {}
+                // Most likely the child exited or closed stdin; there is nobody left to write to
+                Err(_) => self.stdin = None,
+            }
+        }
+    }
+
+    /// Returns `true` on EOF or once `limit` bytes have been read.
+    fn read_some<R: Read>(
+        pipe: &mut Option<R>,
+        buffer: &mut Vec<u8>,
+        limit: Option<usize>,
+    ) -> bool {
+        let Some(pipe) = pipe else {
+            return true;
+        };
+        let mut chunk = [0; 8192];
+        loop {
+            let len = match limit {
+                Some(limit) if limit <= buffer.len() => return true,
+                Some(limit) => chunk.len().min(limit - buffer.len()),
+                None => chunk.len(),
+            };
+            match pipe.read(&mut chunk[..len]) {
+                Ok(0) => return true,
+                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return false,
+                // Treat like `read_to_end` failing: stop reading
+                Err(_) => return true,
+            }
+        }
+    }
+
+    fn poll_fd(fd: Option<libc::c_int>, events: libc::c_short) -> libc::pollfd {
+        libc::pollfd {
+            // Negative descriptors are ignored by `poll`
+            fd: fd.unwrap_or(-1),
+            events,
+            revents: 0,
+        }
+    }
+
+    fn set_nonblocking(fd: &impl AsRawFd) -> io::Result<()> {
+        let fd = fd.as_raw_fd();
+        // SAFETY: `fd` is an open descriptor owned by the caller
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is an open descriptor owned by the caller
+        let result = unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use std::io::{Read, Write};
+    use std::sync::mpsc;
+
+    use super::io;
+    use super::process;
+    use super::Input;
+    use super::Instant;
+
+    enum Stream {
+        Stdout,
+        Stderr,
+    }
+
+    pub(crate) struct Pump {
+        stdin: Option<mpsc::Receiver<()>>,
+        outputs: mpsc::Receiver<(Stream, Vec<u8>)>,
+        pending: usize,
+        out: Vec<u8>,
+        err: Vec<u8>,
+    }
+
+    impl Pump {
+        pub(crate) fn new(
+            child: &mut process::Child,
+            input: Option<Input>,
+            stdout_limit: Option<usize>,
+        ) -> io::Result<Self> {
+            let stdin = child.stdin.take();
+            let stdin = match (stdin, input) {
+                (Some(mut stdin), Some(input)) => {
+                    let (done, finished) = mpsc::channel();
+                    std::thread::spawn(move || {
+                        for (chunk, pause) in input.chunks() {
+                            if stdin.write_all(chunk).and_then(|()| stdin.flush()).is_err() {
+                                break;
+                            }
+                            std::thread::sleep(pause);
+                        }
+                        // Close stdin so the child sees EOF
+                        drop(stdin);
+                        let _ = done.send(());
+                    });
+                    Some(finished)
+                }
+                // Close stdin so the child sees EOF
+                (_, None) | (None, _) => None,
+            };
+
+            let (sender, outputs) = mpsc::channel();
+            let mut pending = 0;
+            if let Some(stdout) = child.stdout.take() {
+                read(stdout, Stream::Stdout, sender.clone(), stdout_limit);
+                pending += 1;
+            }
+            if let Some(stderr) = child.stderr.take() {
+                read(stderr, Stream::Stderr, sender, None);
+                pending += 1;
+            }
+            Ok(Self {
+                stdin,
+                outputs,
+                pending,
+                out: Vec::new(),
+                err: Vec::new(),
+            })
+        }
+
+        /// Write all of the input, returning `false` if `limit` was reached first.
+        pub(crate) fn write_stdin(&mut self, limit: Option<Instant>) -> io::Result<bool> {
+            let Some(finished) = &self.stdin else {
+                return Ok(true);
+            };
+            let finished = match limit {
+                Some(limit) => !matches!(
+                    finished.recv_timeout(limit.saturating_duration_since(Instant::now())),
+                    Err(mpsc::RecvTimeoutError::Timeout)
+                ),
+                None => {
+                    let _ = finished.recv();
+                    true
+                }
+            };
+            if finished {
+                self.stdin = None;
+            }
+            Ok(finished)
+        }
+
+        /// Read stdout and stderr to EOF, returning `false` if `limit` was reached first.
+        pub(crate) fn read_to_end(&mut self, limit: Option<Instant>) -> io::Result<bool> {
+            if !self.write_stdin(limit)? {
+                return Ok(false);
+            }
+            while 0 < self.pending {
+                let received = match limit {
+                    Some(limit) => {
+                        match self
+                            .outputs
+                            .recv_timeout(limit.saturating_duration_since(Instant::now()))
+                        {
+                            Ok(received) => Some(received),
+                            Err(mpsc::RecvTimeoutError::Timeout) => return Ok(false),
+                            Err(mpsc::RecvTimeoutError::Disconnected) => None,
+                        }
+                    }
+                    None => self.outputs.recv().ok(),
+                };
+                match received {
+                    Some((Stream::Stdout, data)) => self.out = data,
+                    Some((Stream::Stderr, data)) => self.err = data,
+                    None => break,
+                }
+                self.pending -= 1;
+            }
+            Ok(true)
+        }
+
+        /// Stop waiting on stdin.
+        pub(crate) fn close_stdin(&mut self) {
+            self.stdin = None;
+        }
+
+        pub(crate) fn finish(self) -> (Vec<u8>, Vec<u8>) {
+            (self.out, self.err)
+        }
+    }
+
+    fn read<R>(
+        pipe: R,
+        stream: Stream,
+        sender: mpsc::Sender<(Stream, Vec<u8>)>,
+        limit: Option<usize>,
+    ) where
+        R: Read + Send + 'static,
+    {
+        std::thread::spawn(move || {
+            let limit = limit.map(|l| l as u64).unwrap_or(u64::MAX);
+            let mut ret = Vec::new();
+            // Like `wait_with_output`, a failed read discards what was read
+            let ret = pipe
+                .take(limit)
+                .read_to_end(&mut ret)
+                .map(|_| ret)
+                .unwrap_or_default();
+            // Dropping the pipe, when `limit` is reached, breaks it for the child
+            let _ = sender.send((stream, ret));
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chunks() {
+        let pause = Duration::from_millis(10);
+        let input = Input::new(
+            Arc::new("ab".into()),
+            vec![(1, pause), (2, Duration::ZERO), (2, pause)],
+        );
+        assert_eq!(
+            input.chunks().collect::<Vec<_>>(),
+            [(b"a" as &[u8], pause), (b"b", Duration::ZERO), (b"", pause)]
+        );
+
+        let input = Input::new(Arc::new("ab".into()), Vec::new());
+        assert_eq!(
+            input.chunks().collect::<Vec<_>>(),
+            [(b"ab" as &[u8], Duration::ZERO)]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Keep secrets passed to commands out of failure output.
+//!
+//! Failure reports include the command line, its environment, and its output, so a token passed
+//! to the command under test can end up in CI logs.  Everything this crate reports is scrubbed of:
+//! - the values of environment variables with [registered][env_var] names, or with names that are
+//!   or end in `_`-separated words like `TOKEN`, `SECRET`, `PASSWORD`, or `API_KEY`, such as
+//!   `GITHUB_TOKEN` but not `TOKENIZERS_PARALLELISM` or `PASSWORD_STORE_DIR`
+//! - [registered secrets][secret]
+#![cfg_attr(
+    feature = "regex",
+    doc = "- matches of [registered patterns][pattern], with the `regex` feature"
+)]
+//!
+//! Names may also be given, comma-separated, with the `ASSERT_CMD_REDACT` environment variable.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::redact;
+//!
+//! redact::env_var("DEPLOY_KEY");
+//! # #[cfg(feature = "regex")]
+//! redact::pattern(regex::Regex::new(r"ghp_[A-Za-z0-9]+").unwrap());
+//!
+//! Command::cargo_bin("my-tool")
+//!     .unwrap()
+//!     .env("DEPLOY_KEY", std::env::var("DEPLOY_KEY").unwrap())
+//!     .assert()
+//!     .success();
+//! ```
+
+use std::borrow::Cow;
+use std::env;
+use std::ffi;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+/// Environment variable with comma-separated names of variables to redact, in addition to those
+/// [registered][env_var].
+pub const REDACT_ENV: &str = "ASSERT_CMD_REDACT";
+
+/// What redacted text is replaced with.
+pub const PLACEHOLDER: &str = "[redacted]";
+
+/// Case-insensitive endings, after a `_`, of variable names that are always redacted.
+const SENSITIVE_SUFFIXES: [&str; 10] = [
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "CREDENTIALS",
+    "API_KEY",
+    "PRIVATE_KEY",
+    "SECRET_KEY",
+    "ACCESS_KEY",
+];
+
+/// Shorter values aren't searched for in output, as they would hide unrelated text.
+const MIN_SECRET_LEN: usize = 8;
+
+struct Registry {
+    names: Vec<String>,
+    secrets: Vec<String>,
+    #[cfg(feature = "regex")]
+    patterns: Vec<regex::Regex>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    names: Vec::new(),
+    secrets: Vec::new(),
+    #[cfg(feature = "regex")]
+    patterns: Vec::new(),
+});
+
+/// Redact the value of the environment variable `name`, both where the command's environment is
+/// shown and wherever this process's value of it appears.
+pub fn env_var(name: impl Into<String>) {
+    registry().names.push(name.into());
+}
+
+/// Redact `value` wherever it appears.
+pub fn secret(value: impl Into<String>) {
+    registry().secrets.push(value.into());
+}
+
+/// Redact every match of `pattern`.
+#[cfg(feature = "regex")]
+pub fn pattern(pattern: regex::Regex) {
+    registry().patterns.push(pattern);
+}
+
+/// Forget everything registered, leaving the built-in names and [`REDACT_ENV`].
+pub fn clear() {
+    let mut registry = registry();
+    registry.names.clear();
+    registry.secrets.clear();
+    #[cfg(feature = "regex")]
+    registry.patterns.clear();
+}
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Names registered directly or with [`REDACT_ENV`].
+fn names() -> Vec<String> {
+    let mut names = registry().names.clone();
+    if let Ok(listed) = env::var(REDACT_ENV) {
+        names.extend(
+            listed
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(ToOwned::to_owned),
+        );
+    }
+    names
+}
+
+/// Whether the value of the environment variable `name` should be hidden.
+pub fn is_sensitive(name: &ffi::OsStr) -> bool {
+    is_sensitive_in(name, &names())
+}
+
+/// [`is_sensitive`], given the registered `names`.
+fn is_sensitive_in(name: &ffi::OsStr, names: &[String]) -> bool {
+    let name = name.to_string_lossy().to_ascii_uppercase();
+    SENSITIVE_SUFFIXES.iter().any(|suffix| {
+        name.strip_suffix(suffix)
+            .map(|rest| rest.is_empty() || rest.ends_with('_'))
+            .unwrap_or(false)
+    }) || names
+        .iter()
+        .any(|registered| registered.eq_ignore_ascii_case(&name))
+}
+
+/// Replace every secret in `text` with [`PLACEHOLDER`].
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let names = names();
+    let mut secrets = registry().secrets.clone();
+    secrets.extend(
+        env::vars_os()
+            .filter(|(name, _)| is_sensitive_in(name, &names))
+            .filter_map(|(_, value)| value.into_string().ok()),
+    );
+    secrets.retain(|secret| MIN_SECRET_LEN <= secret.len());
+    // Longest first, so a secret containing another is replaced whole
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+
+    let mut text = Cow::Borrowed(text);
+    for secret in &secrets {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), PLACEHOLDER));
+        }
+    }
+    #[cfg(feature = "regex")]
+    for pattern in &registry().patterns {
+        if let Cow::Owned(replaced) = pattern.replace_all(&text, regex::NoExpand(PLACEHOLDER)) {
+            text = Cow::Owned(replaced);
+        }
+    }
+    text
+}
+
+/// Write `report` with secrets [redacted][redact].
+pub(crate) fn write(f: &mut fmt::Formatter<'_>, report: impl fmt::Display) -> fmt::Result {
+    f.write_str(&redact(&report.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert!(is_sensitive("GITHUB_TOKEN".as_ref()));
+        assert!(is_sensitive("TOKEN".as_ref()));
+        assert!(is_sensitive("db_password".as_ref()));
+        assert!(is_sensitive("AWS_SECRET_ACCESS_KEY".as_ref()));
+        assert!(!is_sensitive("PATH".as_ref()));
+        assert!(!is_sensitive("TOKENIZERS_PARALLELISM".as_ref()));
+        assert!(!is_sensitive("GITLAB_TOKEN_URL".as_ref()));
+        assert!(!is_sensitive("PASSWORD_STORE_DIR".as_ref()));
+        assert!(!is_sensitive("NOTOKEN".as_ref()));
+    }
+
+    #[test]
+    fn short_secrets() {
+        secret("short");
+        assert_eq!(redact("a short note"), "a short note");
+    }
+
+    #[test]
+    fn secrets() {
+        secret("hunter2-unit-test");
+        assert_eq!(redact("login hunter2-unit-test ok"), "login [redacted] ok");
+        assert!(matches!(redact("nothing here"), Cow::Borrowed(_)));
+    }
+}
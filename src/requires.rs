@@ -0,0 +1,135 @@
+//! Skip tests whose host prerequisites are missing, rather than fail them confusingly.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::requires;
+//!
+//! if requires::binary("docker").and(requires::port_free(8080)).skip() {
+//!     return;
+//! }
+//!
+//! Command::cargo_bin("my-tool")
+//!     .unwrap()
+//!     .args(["deploy", "--port", "8080"])
+//!     .assert()
+//!     .success();
+//! ```
+
+use std::env;
+use std::fmt;
+use std::net;
+use std::path;
+
+/// A host prerequisite, and why it isn't met, if it isn't.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct Requirement {
+    unmet: Vec<String>,
+}
+
+impl Requirement {
+    fn met() -> Self {
+        Self { unmet: Vec::new() }
+    }
+
+    fn unmet(reason: String) -> Self {
+        Self {
+            unmet: vec![reason],
+        }
+    }
+
+    /// Also require `other`.
+    pub fn and(mut self, other: Self) -> Self {
+        self.unmet.extend(other.unmet);
+        self
+    }
+
+    /// Whether the prerequisite is met.
+    pub fn is_met(&self) -> bool {
+        self.unmet.is_empty()
+    }
+
+    /// Why the prerequisite isn't met, if it isn't.
+    pub fn reason(&self) -> Option<String> {
+        (!self.is_met()).then(|| self.unmet.join(", "))
+    }
+
+    /// Whether to skip the test, [reporting][crate::skip::report] why when it should be.
+    ///
+    /// See also [`skip_unless!`][crate::skip_unless].
+    pub fn skip(&self) -> bool {
+        let Some(reason) = self.reason() else {
+            return false;
+        };
+        crate::skip::report(reason);
+        true
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason() {
+            Some(reason) => f.write_str(&reason),
+            None => f.write_str("met"),
+        }
+    }
+}
+
+/// Require `name` to be on the `PATH`, or to exist if it is a path.
+pub fn binary(name: &str) -> Requirement {
+    if find_binary(name).is_some() {
+        Requirement::met()
+    } else {
+        Requirement::unmet(format!("`{name}` is not installed"))
+    }
+}
+
+fn find_binary(name: &str) -> Option<path::PathBuf> {
+    let exe = format!("{name}{}", env::consts::EXE_SUFFIX);
+    if path::Path::new(name).components().count() > 1 {
+        return [name, exe.as_str()]
+            .into_iter()
+            .map(path::PathBuf::from)
+            .find(|path| path.is_file());
+    }
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .flat_map(|dir| [dir.join(name), dir.join(&exe)])
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &path::Path) -> bool {
+    path.is_file()
+}
+
+/// Require nothing to be listening on `port` on the loopback interface.
+pub fn port_free(port: u16) -> Requirement {
+    match net::TcpListener::bind((net::Ipv4Addr::LOCALHOST, port)) {
+        Ok(_) => Requirement::met(),
+        Err(err) => Requirement::unmet(format!("port {port} is unavailable: {err}")),
+    }
+}
+
+/// Require the tests to be running as `root`.
+pub fn root() -> Requirement {
+    #[cfg(unix)]
+    {
+        // SAFETY: `geteuid` has no preconditions
+        if unsafe { libc::geteuid() } == 0 {
+            return Requirement::met();
+        }
+    }
+    Requirement::unmet("not running as root".to_owned())
+}
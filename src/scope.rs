@@ -0,0 +1,174 @@
+//! Clean up background processes and temporary directories at the end of a test, even if it
+//! panics.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::scope::Scope;
+//!
+//! let mut scope = Scope::new();
+//! let dir = scope.temp_dir().unwrap();
+//! scope
+//!     .spawn(Command::cargo_bin("my-server").unwrap().arg("--data").arg(&dir))
+//!     .unwrap();
+//!
+//! Command::cargo_bin("my-client")
+//!     .unwrap()
+//!     .assert()
+//!     .success();
+//! // `my-server` is killed and `dir` removed when `scope` is dropped
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path;
+use std::process;
+
+use crate::Command;
+
+/// Resources owned by a test, cleaned up when dropped.
+///
+/// Anything still running or left behind when the scope is dropped is reported on `stderr`.
+#[derive(Debug, Default)]
+pub struct Scope {
+    children: Vec<(String, process::Child)>,
+    dirs: Vec<path::PathBuf>,
+    #[cfg(unix)]
+    shims: Vec<crate::shim::ShimDir>,
+}
+
+impl Scope {
+    /// Create an empty scope.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `cmd` in the background, with its `stdin`, `stdout`, and `stderr` discarded.
+    ///
+    /// The command is killed when the scope is dropped, if it is still running.
+    pub fn spawn(&mut self, cmd: &mut Command) -> io::Result<&mut process::Child> {
+        let child = cmd.spawn_background()?;
+        Ok(self.track(format!("{:?}", cmd.get_program()), child))
+    }
+
+    /// Kill `child` when the scope is dropped, if it is still running.
+    ///
+    /// `name` identifies it in the cleanup report.
+    pub fn track(&mut self, name: impl Into<String>, child: process::Child) -> &mut process::Child {
+        self.children.push((name.into(), child));
+        &mut self.children.last_mut().expect("just pushed").1
+    }
+
+    /// Create an empty temporary directory, removed when the scope is dropped.
+    ///
+    /// On Unix, only the current user may access it.
+    pub fn temp_dir(&mut self) -> io::Result<path::PathBuf> {
+        let dir = crate::temp::create_dir("scope")?;
+        self.dirs.push(dir.clone());
+        Ok(dir)
+    }
+
+    /// Remove `path`, a file or directory, when the scope is dropped.
+    pub fn remove_on_drop(&mut self, path: impl Into<path::PathBuf>) -> &mut Self {
+        self.dirs.push(path.into());
+        self
+    }
+
+    /// Create a [`ShimDir`][crate::shim::ShimDir], removed when the scope is dropped.
+    #[cfg(unix)]
+    pub fn shims(&mut self) -> io::Result<&mut crate::shim::ShimDir> {
+        self.shims.push(crate::shim::ShimDir::new()?);
+        Ok(self.shims.last_mut().expect("just pushed"))
+    }
+
+    /// Clean up now, reporting what had to be killed or couldn't be removed.
+    pub fn cleanup(&mut self) -> Cleanup {
+        let mut cleanup = Cleanup::default();
+        for (name, mut child) in self.children.drain(..) {
+            if let Ok(None) = child.try_wait() {
+                let _ = child.kill();
+                cleanup.killed.push(format!("{name} (pid {})", child.id()));
+            }
+            // Reap it, so it doesn't linger as a zombie
+            let _ = child.wait();
+        }
+        for path in self.dirs.drain(..) {
+            let removed = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            match removed {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => cleanup.failed.push(format!("{}: {err}", path.display())),
+            }
+        }
+        #[cfg(unix)]
+        self.shims.clear();
+        cleanup
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let cleanup = self.cleanup();
+        if !cleanup.is_clean() {
+            // A single `eprint!` holds the `stderr` lock for the whole report
+            #[allow(clippy::print_stderr)]
+            {
+                eprint!("{cleanup}");
+            }
+        }
+    }
+}
+
+/// What [`Scope::cleanup`] had to do.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cleanup {
+    killed: Vec<String>,
+    failed: Vec<String>,
+}
+
+impl Cleanup {
+    /// Whether everything had already finished and was removed.
+    pub fn is_clean(&self) -> bool {
+        self.killed.is_empty() && self.failed.is_empty()
+    }
+
+    /// The processes that were still running and had to be killed.
+    pub fn killed(&self) -> &[String] {
+        &self.killed
+    }
+
+    /// The paths that couldn't be removed, with why.
+    pub fn failed(&self) -> &[String] {
+        &self.failed
+    }
+}
+
+impl fmt::Display for Cleanup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::redact::write(f, Report(self))
+    }
+}
+
+struct Report<'a>(&'a Cleanup);
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Report(cleanup) = self;
+        for killed in &cleanup.killed {
+            writeln!(
+                f,
+                "assert_cmd: killed {killed}, still running at end of scope"
+            )?;
+        }
+        for failed in &cleanup.failed {
+            writeln!(f, "assert_cmd: failed to remove {failed}")?;
+        }
+        Ok(())
+    }
+}
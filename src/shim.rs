@@ -0,0 +1,285 @@
+//! Replace external tools, like `git` or `docker`, with scripted shims on `PATH`.
+//!
+//! Shims record each call's arguments and `stdin` so they can be asserted on afterwards.
+//!
+//! Shims are `sh` scripts, so this is only available on Unix.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::shim::ShimDir;
+//! use assert_cmd::shim::ShimResponse;
+//!
+//! let mut shims = ShimDir::new().unwrap();
+//! shims
+//!     .add("git", ShimResponse::new().stdout("main\n"))
+//!     .unwrap();
+//!
+//! let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+//! shims.apply(&mut cmd).unwrap();
+//! cmd.assert().success();
+//!
+//! shims.not_called("git");
+//! ```
+
+use std::env;
+use std::error::Error;
+use std::ffi;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path;
+
+use crate::Command;
+
+/// A temporary directory of shims.
+///
+/// The directory is removed when this is dropped.
+#[derive(Debug)]
+pub struct ShimDir {
+    root: path::PathBuf,
+}
+
+impl ShimDir {
+    /// Create an empty directory of shims.
+    pub fn new() -> io::Result<Self> {
+        // The scripts run first on `PATH`, so no one else may replace them
+        let root = crate::temp::create_dir("shims")?;
+        fs::create_dir_all(root.join("bin"))?;
+        fs::create_dir_all(root.join("calls"))?;
+        Ok(Self { root })
+    }
+
+    /// Add a shim for the program `name` that responds with `response`.
+    pub fn add(&mut self, name: &str, response: ShimResponse) -> io::Result<&mut Self> {
+        if name.is_empty() || name.contains(['/', '\0']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid shim name `{name}`"),
+            ));
+        }
+        let responses = self.root.join("responses");
+        fs::create_dir_all(&responses)?;
+        let stdout = responses.join(format!("{name}.stdout"));
+        fs::write(&stdout, &response.stdout)?;
+        let stderr = responses.join(format!("{name}.stderr"));
+        fs::write(&stderr, &response.stderr)?;
+
+        // `mkdir` is atomic, so concurrent calls each get their own, ordered, record
+        let calls = self.root.join("calls");
+        let script = format!(
+            r#"#!/bin/sh
+i=0
+while ! mkdir {calls}/{name}-$i 2>/dev/null; do i=$((i + 1)); done
+record={calls}/{name}-$i
+# `printf` runs its format once even without arguments
+if [ $# -gt 0 ]; then printf '%s\0' "$@"; fi > "$record/args"
+cat > "$record/stdin"
+cat {stdout}
+cat {stderr} >&2
+exit {code}
+"#,
+            calls = sh_quote(calls.as_os_str()),
+            name = sh_quote(name.as_ref()),
+            stdout = sh_quote(stdout.as_os_str()),
+            stderr = sh_quote(stderr.as_os_str()),
+            code = response.code,
+        );
+        let path = self.root.join("bin").join(name);
+        fs::write(&path, script)?;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+        Ok(self)
+    }
+
+    /// Put the shims first on `cmd`'s `PATH`.
+    pub fn apply(&self, cmd: &mut Command) -> io::Result<()> {
+        let path = cmd
+            .get_envs()
+            .find(|(key, _)| *key == "PATH")
+            .map(|(_, value)| value.map(ToOwned::to_owned))
+            .unwrap_or_else(|| env::var_os("PATH"));
+        let mut paths = vec![self.root.join("bin")];
+        paths.extend(path.iter().flat_map(env::split_paths));
+        let path = env::join_paths(paths)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        cmd.env("PATH", path);
+        Ok(())
+    }
+
+    /// The calls made to the shim `name`, in order.
+    pub fn calls(&self, name: &str) -> io::Result<Vec<ShimCall>> {
+        let mut calls = Vec::new();
+        for index in 0.. {
+            let record = self.root.join("calls").join(format!("{name}-{index}"));
+            if !record.exists() {
+                break;
+            }
+            let args = fs::read(record.join("args"))?;
+            // Each argument is `\0`-terminated
+            let args = match args.strip_suffix(b"\0") {
+                Some(args) => args
+                    .split(|b| *b == b'\0')
+                    .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                    .collect(),
+                None => Vec::new(),
+            };
+            // The call may still be running
+            let stdin = fs::read(record.join("stdin")).unwrap_or_default();
+            calls.push(ShimCall { args, stdin });
+        }
+        Ok(calls)
+    }
+
+    /// Ensure the shim `name` was called.
+    #[track_caller]
+    pub fn called(&self, name: &str) -> &Self {
+        self.try_called(name).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`ShimDir::called`].
+    pub fn try_called(&self, name: &str) -> Result<&Self, ShimError> {
+        if self.calls(name)?.is_empty() {
+            return Err(ShimError::new(format!("`{name}` was not called")));
+        }
+        Ok(self)
+    }
+
+    /// Ensure the shim `name` was called with exactly `args`.
+    #[track_caller]
+    pub fn called_with(&self, name: &str, args: &[&str]) -> &Self {
+        self.try_called_with(name, args)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`ShimDir::called_with`].
+    pub fn try_called_with(&self, name: &str, args: &[&str]) -> Result<&Self, ShimError> {
+        let calls = self.calls(name)?;
+        if calls.iter().any(|call| call.args == args) {
+            return Ok(self);
+        }
+        let actual: Vec<_> = calls.iter().map(ShimCall::args).collect();
+        Err(ShimError::new(format!(
+            "`{name}` was not called with {args:?}, calls: {actual:?}"
+        )))
+    }
+
+    /// Ensure the shim `name` was not called.
+    #[track_caller]
+    pub fn not_called(&self, name: &str) -> &Self {
+        self.try_not_called(name)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`ShimDir::not_called`].
+    pub fn try_not_called(&self, name: &str) -> Result<&Self, ShimError> {
+        let calls = self.calls(name)?;
+        if let Some(call) = calls.first() {
+            return Err(ShimError::new(format!(
+                "`{name}` was unexpectedly called with {:?}",
+                call.args
+            )));
+        }
+        Ok(self)
+    }
+}
+
+impl Drop for ShimDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// How a shim responds to being called.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShimResponse {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    code: u8,
+}
+
+impl ShimResponse {
+    /// Succeed without writing anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write `stdout` to `stdout`.
+    pub fn stdout(mut self, stdout: impl Into<Vec<u8>>) -> Self {
+        self.stdout = stdout.into();
+        self
+    }
+
+    /// Write `stderr` to `stderr`.
+    pub fn stderr(mut self, stderr: impl Into<Vec<u8>>) -> Self {
+        self.stderr = stderr.into();
+        self
+    }
+
+    /// Exit with `code`.
+    pub fn code(mut self, code: u8) -> Self {
+        self.code = code;
+        self
+    }
+}
+
+/// A call recorded by a shim.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShimCall {
+    args: Vec<String>,
+    stdin: Vec<u8>,
+}
+
+impl ShimCall {
+    /// The arguments, excluding the program name.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// What was written to the shim's `stdin`.
+    pub fn stdin(&self) -> &[u8] {
+        &self.stdin
+    }
+}
+
+/// [`ShimDir`] assertion failure.
+#[derive(Debug)]
+pub struct ShimError {
+    message: String,
+}
+
+impl ShimError {
+    fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl From<io::Error> for ShimError {
+    fn from(err: io::Error) -> Self {
+        Self::new(format!("failed to read shim calls: {err}"))
+    }
+}
+
+impl Error for ShimError {}
+
+impl fmt::Display for ShimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::redact::write(f, &self.message)?;
+        writeln!(f)
+    }
+}
+
+fn sh_quote(value: &ffi::OsStr) -> String {
+    format!("'{}'", value.to_string_lossy().replace('\'', r#"'\''"#))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quote() {
+        assert_eq!(sh_quote("it's".as_ref()), r#"'it'\''s'"#);
+    }
+}
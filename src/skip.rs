@@ -0,0 +1,113 @@
+//! Skip tests whose prerequisites are missing, in a way tools can tell apart from a pass.
+//!
+//! The test harness has no way for a test to skip itself once running, so a skipped test passes.
+//! To tell skips apart, each one prints a line like `SKIPPED: <test>: <reason>` to stderr and,
+//! when the `ASSERT_CMD_SKIP_LOG` environment variable is set, appends a
+//! [JSON Lines](https://jsonlines.org/) entry to that file:
+//! ```json
+//! {"test":"deploy::uses_docker","reason":"`docker` is not installed"}
+//! ```
+//!
+//! Skips from [`requires`][crate::requires], [`platform::skip_on`][crate::platform::skip_on],
+//! and others in this crate are reported the same way.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::requires;
+//!
+//! assert_cmd::skip_unless!(requires::binary("docker"));
+//! if std::env::var_os("CI").is_none() {
+//!     assert_cmd::skip!("only run in CI");
+//! }
+//!
+//! Command::cargo_bin("my-tool")
+//!     .unwrap()
+//!     .arg("deploy")
+//!     .assert()
+//!     .success();
+//! ```
+
+use std::env;
+use std::ffi;
+use std::fmt;
+use std::fs;
+use std::io::Write as _;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+/// Environment variable for a file to append skipped tests to.
+pub const SKIP_LOG_ENV: &str = "ASSERT_CMD_SKIP_LOG";
+
+/// Start of the line printed for each skip.
+pub const PREFIX: &str = "SKIPPED";
+
+static SKIPPED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Skip the rest of the current test, reporting `reason` like [`report`], and return.
+///
+/// Takes the same arguments as [`format!`].  This returns `()`, so it can only be used in
+/// functions returning `()`, like most tests.
+#[macro_export]
+macro_rules! skip {
+    ($($reason:tt)+) => {{
+        $crate::skip::report(::std::format_args!($($reason)+));
+        return;
+    }};
+}
+
+/// Skip the rest of the current test, like [`skip!`], unless a
+/// [`Requirement`][crate::requires::Requirement] is met.
+#[macro_export]
+macro_rules! skip_unless {
+    ($requirement:expr) => {
+        if let ::std::option::Option::Some(reason) = $requirement.reason() {
+            $crate::skip!("{}", reason);
+        }
+    };
+}
+
+/// Report that the current test is skipped because of `reason`.
+///
+/// The test is named after the current thread, as the test harness names each test's thread
+/// after the test.  The line on stderr is left out when [quiet][crate::assert::set_quiet], but
+/// the skip is still [recorded][skipped] and logged.
+pub fn report(reason: impl fmt::Display) {
+    let test = std::thread::current()
+        .name()
+        .filter(|name| *name != "main")
+        .unwrap_or("<unknown>")
+        .to_owned();
+    let reason = reason.to_string();
+    if !crate::assert::is_quiet() {
+        // `eprintln!` is captured by the test harness, unlike writes to `io::stderr()`
+        #[allow(clippy::print_stderr)]
+        {
+            eprintln!("{PREFIX}: {test}: {reason}");
+        }
+    }
+    if let Some(path) = env::var_os(SKIP_LOG_ENV).filter(|p| !p.is_empty()) {
+        let mut entry = String::from("{\"test\":");
+        crate::journal::push_str(&mut entry, ffi::OsStr::new(&test));
+        entry.push_str(",\"reason\":");
+        crate::journal::push_str(&mut entry, ffi::OsStr::new(&reason));
+        entry.push_str("}\n");
+        if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(path) {
+            // A single write so concurrent tests don't interleave entries
+            let _ = file.write_all(entry.as_bytes());
+        }
+    }
+    SKIPPED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((test, reason));
+}
+
+/// The tests skipped so far in this process, with why.
+pub fn skipped() -> Vec<(String, String)> {
+    SKIPPED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
@@ -0,0 +1,630 @@
+//! Assert on the system calls a [`Command`] makes, using [`strace`].
+//!
+//! This requires the `strace` feature, Linux, and `strace` being installed.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use assert_cmd::Command;
+//! use assert_cmd::strace::Strace;
+//!
+//! let strace = Strace::new().unwrap();
+//! let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+//! strace.wrap(&mut cmd).assert().success();
+//! strace
+//!     .syscalls()
+//!     .unwrap()
+//!     .no_network_syscalls()
+//!     .not_opened_file("/etc/passwd");
+//! ```
+//!
+//! [`Command`]: crate::Command
+//! [`strace`]: https://strace.io/
+
+use std::error::Error;
+use std::ffi;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt as _;
+use std::path;
+
+use crate::Command;
+
+/// Syscalls that open a file, with the path as the first string argument.
+const OPEN_SYSCALLS: &[&str] = &["open", "openat", "openat2", "creat"];
+
+/// Syscalls that execute a program, with the path as the first string argument.
+const EXEC_SYSCALLS: &[&str] = &["execve", "execveat"];
+
+/// Syscalls that use a socket.
+const NETWORK_SYSCALLS: &[&str] = &[
+    "socket", "connect", "bind", "listen", "accept", "accept4", "sendto", "recvfrom", "sendmsg",
+    "recvmsg",
+];
+
+/// Trace the system calls of a [`Command`] to a temporary log.
+///
+/// The log is removed when this is dropped.
+#[derive(Debug)]
+pub struct Strace {
+    dir: path::PathBuf,
+    log: path::PathBuf,
+}
+
+impl Strace {
+    /// Create a tracer with a fresh log file.
+    ///
+    /// The log is in a directory only the current user may access, so the syscalls asserted on
+    /// can't be faked by planting a log.
+    pub fn new() -> io::Result<Self> {
+        let dir = crate::temp::create_dir("strace")?;
+        let log = dir.join("syscalls.log");
+        Ok(Self { dir, log })
+    }
+
+    /// Run `cmd` under `strace`, following forks.
+    ///
+    /// See [`Command::wrapped_by`].
+    pub fn wrap<'c>(&self, cmd: &'c mut Command) -> &'c mut Command {
+        let mut wrapper = Command::new("strace");
+        // `-s` so paths aren't truncated
+        wrapper
+            .args(["-f", "-qq", "-s", "4096", "-o"])
+            .arg(&self.log)
+            .arg("--");
+        cmd.wrapped_by(wrapper)
+    }
+
+    /// Read the system calls made by the command.
+    pub fn syscalls(&self) -> io::Result<Syscalls> {
+        let log = fs::read_to_string(&self.log)?;
+        Ok(Syscalls::parse(&log))
+    }
+}
+
+impl Drop for Strace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// System calls recorded by [`Strace`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Syscalls {
+    calls: Vec<Syscall>,
+}
+
+impl Syscalls {
+    /// Parse a log written with `-f`, joining calls that were interrupted by another process's
+    /// with where they resumed.
+    fn parse(log: &str) -> Self {
+        let mut calls = Vec::new();
+        // Each process's interrupted call, and where it is in `calls`
+        let mut unfinished: Vec<(&str, String, usize)> = Vec::new();
+        for line in log.lines() {
+            let call = line.trim_start_matches(|c: char| c.is_ascii_digit());
+            let pid = &line[..line.len() - call.len()];
+            if let Some(start) = line.strip_suffix(" <unfinished ...>") {
+                if let Some(parsed) = Syscall::parse(start) {
+                    unfinished.retain(|(p, _, _)| *p != pid);
+                    unfinished.push((pid, start.to_owned(), calls.len()));
+                    calls.push(parsed);
+                }
+            } else if let Some((_, rest)) = call
+                .trim_start()
+                .strip_prefix("<... ")
+                .and_then(|resumed| resumed.split_once(" resumed>"))
+            {
+                if let Some(i) = unfinished.iter().position(|(p, _, _)| *p == pid) {
+                    let (_, start, index) = unfinished.swap_remove(i);
+                    if let Some(parsed) = Syscall::parse(&format!("{start}{rest}")) {
+                        calls[index] = parsed;
+                    }
+                }
+            } else if let Some(parsed) = Syscall::parse(line) {
+                calls.push(parsed);
+            }
+        }
+        Self { calls }
+    }
+
+    /// Iterate over the recorded system calls.
+    pub fn iter(&self) -> impl Iterator<Item = &Syscall> {
+        self.calls.iter()
+    }
+
+    /// The processes the command executed, excluding the command itself.
+    pub fn subprocesses(&self) -> Vec<Subprocess> {
+        self.iter().filter_map(Syscall::executed).skip(1).collect()
+    }
+
+    /// Ensure the command didn't execute any other programs.
+    #[track_caller]
+    pub fn spawned_no_subprocesses(&self) -> &Self {
+        self.try_spawned_no_subprocesses()
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`Syscalls::spawned_no_subprocesses`].
+    pub fn try_spawned_no_subprocesses(&self) -> Result<&Self, SyscallError> {
+        let executed = self.iter().filter(|call| call.executed().is_some()).nth(1);
+        match executed {
+            Some(call) => Err(SyscallError::new("Unexpected subprocess", call)),
+            None => Ok(self),
+        }
+    }
+
+    /// Ensure the command executed a program whose command line contains `pattern`, like
+    /// `git fetch`.
+    #[track_caller]
+    pub fn spawned_matching(&self, pattern: &str) -> &Self {
+        self.try_spawned_matching(pattern)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`Syscalls::spawned_matching`].
+    pub fn try_spawned_matching(&self, pattern: &str) -> Result<&Self, SyscallError> {
+        let subprocesses = self.subprocesses();
+        if subprocesses
+            .iter()
+            .any(|p| p.command_line().contains(pattern))
+        {
+            Ok(self)
+        } else {
+            let spawned: Vec<_> = subprocesses.iter().map(Subprocess::command_line).collect();
+            Err(SyscallError {
+                message: format!("No subprocess matched `{pattern}`, spawned: {spawned:?}"),
+                call: None,
+            })
+        }
+    }
+
+    /// Ensure no IPv4 or IPv6 sockets were used.
+    ///
+    /// Unix domain sockets are allowed.
+    #[track_caller]
+    pub fn no_network_syscalls(&self) -> &Self {
+        self.try_no_network_syscalls()
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`Syscalls::no_network_syscalls`].
+    pub fn try_no_network_syscalls(&self) -> Result<&Self, SyscallError> {
+        match self.iter().find(|call| call.is_network()) {
+            Some(call) => Err(SyscallError::new("Unexpected network syscall", call)),
+            None => Ok(self),
+        }
+    }
+
+    /// Ensure `path` was opened.
+    #[track_caller]
+    pub fn opened_file(&self, path: impl AsRef<path::Path>) -> &Self {
+        self.try_opened_file(path)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`Syscalls::opened_file`].
+    pub fn try_opened_file(&self, path: impl AsRef<path::Path>) -> Result<&Self, SyscallError> {
+        let path = path.as_ref();
+        if self.iter().any(|call| call.opened() == Some(path)) {
+            Ok(self)
+        } else {
+            Err(SyscallError {
+                message: format!("`{}` was not opened", path.display()),
+                call: None,
+            })
+        }
+    }
+
+    /// Ensure `path` was not opened.
+    #[track_caller]
+    pub fn not_opened_file(&self, path: impl AsRef<path::Path>) -> &Self {
+        self.try_not_opened_file(path)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// `try_` variant of [`Syscalls::not_opened_file`].
+    pub fn try_not_opened_file(&self, path: impl AsRef<path::Path>) -> Result<&Self, SyscallError> {
+        let path = path.as_ref();
+        match self.iter().find(|call| call.opened() == Some(path)) {
+            Some(call) => Err(SyscallError::new(
+                &format!("`{}` was unexpectedly opened", path.display()),
+                call,
+            )),
+            None => Ok(self),
+        }
+    }
+}
+
+/// A system call recorded by [`Strace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Syscall {
+    name: String,
+    line: String,
+    args: Vec<Value>,
+    /// What follows ` = `, or [`None`] if the call never finished.
+    result: Option<String>,
+}
+
+impl Syscall {
+    /// Parse a line like `1234 openat(AT_FDCWD, "/etc/passwd", O_RDONLY) = 3`.
+    fn parse(line: &str) -> Option<Self> {
+        let call = line.trim_start_matches(|c: char| c.is_ascii_digit()).trim();
+        let (name, rest) = call.split_once('(')?;
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+        let mut rest = rest;
+        let args = parse_list(&mut rest, ')');
+        let result = args
+            .as_ref()
+            .and_then(|_| rest.trim_start().strip_prefix('='))
+            .map(|result| result.trim().to_owned());
+        Some(Self {
+            name: name.to_owned(),
+            line: line.to_owned(),
+            args: args.unwrap_or_default(),
+            result,
+        })
+    }
+
+    /// The name of the system call, like `openat`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The line `strace` recorded for this call.
+    ///
+    /// A call interrupted by another process's is joined with where it resumed.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    fn is_network(&self) -> bool {
+        NETWORK_SYSCALLS.contains(&self.name.as_str()) && self.line.contains("AF_INET")
+    }
+
+    /// The first string argument, which is the path for the calls this looks at.
+    fn path(&self) -> Option<&path::Path> {
+        self.args.iter().find_map(|arg| match arg {
+            Value::Str(bytes) => Some(path::Path::new(ffi::OsStr::from_bytes(bytes))),
+            _ => None,
+        })
+    }
+
+    /// Parse `execve("/usr/bin/git", ["git", "fetch"], ...) = 0`, skipping failed attempts, like
+    /// while searching `PATH`, and calls that never finished.
+    fn executed(&self) -> Option<Subprocess> {
+        if !EXEC_SYSCALLS.contains(&self.name.as_str()) {
+            return None;
+        }
+        let result = self.result.as_deref()?;
+        if result.starts_with('-') || result.starts_with('?') {
+            return None;
+        }
+        let args = self.args.iter().find_map(|arg| match arg {
+            Value::Array(args) => Some(args),
+            _ => None,
+        })?;
+        let args = args
+            .iter()
+            .filter_map(|arg| match arg {
+                Value::Str(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+                _ => None,
+            })
+            .collect();
+        Some(Subprocess {
+            program: self.path()?.to_owned(),
+            args,
+        })
+    }
+
+    fn opened(&self) -> Option<&path::Path> {
+        if !OPEN_SYSCALLS.contains(&self.name.as_str()) {
+            return None;
+        }
+        self.path()
+    }
+}
+
+/// An argument as `strace` prints it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    /// A quoted string, unescaped.
+    Str(Vec<u8>),
+    /// A `[...]` array.
+    Array(Vec<Value>),
+    /// Anything else, like flags, numbers, or structures, as written.
+    Other(String),
+}
+
+/// Parse comma-separated values up to `close`, leaving `input` after it.
+///
+/// Returns [`None`] if `input` ends first, like for an unfinished call.
+fn parse_list(input: &mut &str, close: char) -> Option<Vec<Value>> {
+    let mut values = Vec::new();
+    loop {
+        *input = input.trim_start();
+        if let Some(rest) = input.strip_prefix(close) {
+            *input = rest;
+            return Some(values);
+        }
+        let value = if let Some(rest) = input.strip_prefix('"') {
+            *input = rest;
+            let value = Value::Str(parse_string(input)?);
+            // Truncated strings are followed by `...`
+            *input = input.strip_prefix("...").unwrap_or(input);
+            value
+        } else if let Some(rest) = input.strip_prefix('[') {
+            *input = rest;
+            Value::Array(parse_list(input, ']')?)
+        } else {
+            Value::Other(parse_other(input, close)?)
+        };
+        values.push(value);
+        *input = input.trim_start();
+        if let Some(rest) = input.strip_prefix(',') {
+            *input = rest;
+        } else if !input.starts_with(close) {
+            return None;
+        }
+    }
+}
+
+/// Parse the rest of a quoted string, leaving `input` after the closing quote.
+fn parse_string(input: &mut &str) -> Option<Vec<u8>> {
+    let bytes = input.as_bytes();
+    let mut value = Vec::new();
+    let mut i = 0;
+    loop {
+        match *bytes.get(i)? {
+            b'"' => break,
+            b'\\' => {
+                i += 1;
+                let escaped = *bytes.get(i)?;
+                let byte = match escaped {
+                    b'n' => b'\n',
+                    b't' => b'\t',
+                    b'r' => b'\r',
+                    b'v' => 0x0b,
+                    b'f' => 0x0c,
+                    b'x' => {
+                        let hex = input.get(i + 1..i + 3)?;
+                        i += 2;
+                        u8::from_str_radix(hex, 16).ok()?
+                    }
+                    b'0'..=b'7' => {
+                        let digits = bytes[i..]
+                            .iter()
+                            .take(3)
+                            .take_while(|b| (b'0'..=b'7').contains(*b))
+                            .count();
+                        let octal = u16::from_str_radix(&input[i..i + digits], 8).ok()?;
+                        i += digits - 1;
+                        u8::try_from(octal).ok()?
+                    }
+                    other => other,
+                };
+                value.push(byte);
+            }
+            byte => value.push(byte),
+        }
+        i += 1;
+    }
+    *input = &input[i + 1..];
+    Some(value)
+}
+
+/// Parse an unquoted value, up to a `,` or `close` that isn't nested, leaving `input` there.
+fn parse_other(input: &mut &str, close: char) -> Option<String> {
+    let mut value = String::new();
+    let mut depth = 0_usize;
+    loop {
+        let c = input.chars().next()?;
+        if depth == 0 && (c == ',' || c == close) {
+            break;
+        }
+        if c == '"' {
+            *input = &input[1..];
+            let string = parse_string(input)?;
+            value.push('"');
+            value.push_str(&String::from_utf8_lossy(&string));
+            value.push('"');
+            continue;
+        }
+        if let Some(rest) = input.strip_prefix("/*") {
+            let (comment, rest) = rest.split_once("*/")?;
+            value.push_str("/*");
+            value.push_str(comment);
+            value.push_str("*/");
+            *input = rest;
+            continue;
+        }
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+        value.push(c);
+        *input = &input[c.len_utf8()..];
+    }
+    Some(value.trim_end().to_owned())
+}
+
+impl fmt::Display for Syscall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.line.fmt(f)
+    }
+}
+
+/// A program executed by the command, as recorded by [`Strace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Subprocess {
+    program: path::PathBuf,
+    args: Vec<String>,
+}
+
+impl Subprocess {
+    /// The path of the program executed.
+    pub fn program(&self) -> &path::Path {
+        &self.program
+    }
+
+    /// The arguments, including `argv[0]`.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// The arguments joined by spaces.
+    pub fn command_line(&self) -> String {
+        self.args.join(" ")
+    }
+}
+
+/// [`Syscalls`] assertion failure.
+#[derive(Debug)]
+pub struct SyscallError {
+    message: String,
+    call: Option<Syscall>,
+}
+
+impl SyscallError {
+    fn new(message: &str, call: &Syscall) -> Self {
+        Self {
+            message: message.to_owned(),
+            call: Some(call.clone()),
+        }
+    }
+}
+
+impl Error for SyscallError {}
+
+impl fmt::Display for SyscallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        crate::redact::write(f, Report(self))
+    }
+}
+
+struct Report<'a>(&'a SyscallError);
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Report(error) = self;
+        let palette = crate::Palette::color();
+        writeln!(f, "{}", error.message)?;
+        if let Some(call) = &error.call {
+            writeln!(
+                f,
+                "{:#}=`{:#}`",
+                palette.key("syscall"),
+                palette.value(call)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LOG: &str = r#"1234 execve("/bin/true", ["true"], 0x7ffd /* 20 vars */) = 0
+1234 openat(AT_FDCWD, "/etc/ld.so.cache", O_RDONLY|O_CLOEXEC) = 3
+1234 socket(AF_UNIX, SOCK_STREAM|SOCK_CLOEXEC|SOCK_NONBLOCK, 0) = 4
+1235 read(3,  <unfinished ...>
+1235 <... read resumed>"", 4096) = 0
+1234 +++ exited with 0 +++
+"#;
+
+    #[test]
+    fn parse() {
+        let syscalls = Syscalls::parse(LOG);
+        let names: Vec<_> = syscalls.iter().map(Syscall::name).collect();
+        assert_eq!(names, ["execve", "openat", "socket", "read"]);
+    }
+
+    #[test]
+    fn opened() {
+        let syscalls = Syscalls::parse(LOG);
+        syscalls
+            .opened_file("/etc/ld.so.cache")
+            .not_opened_file("/etc/passwd");
+        assert!(syscalls.try_opened_file("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn subprocesses() {
+        let log = r#"1 execve("/bin/tool", ["tool"], 0x7ffd /* 20 vars */) = 0
+2 execve("/usr/local/bin/git", ["git", "fetch"], 0x7ffd /* 20 vars */) = -1 ENOENT (No such file or directory)
+2 execve("/usr/bin/git", ["git", "fetch", "origin"], 0x7ffd /* 20 vars */) = 0
+"#;
+        let syscalls = Syscalls::parse(log);
+        let subprocesses = syscalls.subprocesses();
+        assert_eq!(subprocesses.len(), 1);
+        assert_eq!(subprocesses[0].program(), path::Path::new("/usr/bin/git"));
+        assert_eq!(subprocesses[0].command_line(), "git fetch origin");
+        syscalls.spawned_matching("git fetch");
+        assert!(syscalls.try_spawned_no_subprocesses().is_err());
+        Syscalls::parse(LOG).spawned_no_subprocesses();
+    }
+
+    #[test]
+    fn quoted_arguments() {
+        let log = r#"1 execve("/bin/tool", ["tool"], 0x7ffd /* 20 vars */) = 0
+2 execve("/bin/sh", ["sh", "-c", "[ -f x ]", "a\", b", "say \"hi\"\n", "\303\251"], 0x7ffd /* 20 vars */) = 0
+2 openat(AT_FDCWD, "/tmp/a \"quoted\" file", O_RDONLY) = 3
+"#;
+        let syscalls = Syscalls::parse(log);
+        let subprocesses = syscalls.subprocesses();
+        assert_eq!(
+            subprocesses[0].args(),
+            ["sh", "-c", "[ -f x ]", "a\", b", "say \"hi\"\n", "é"]
+        );
+        syscalls.opened_file("/tmp/a \"quoted\" file");
+    }
+
+    #[test]
+    fn resumed() {
+        let log = r#"1 execve("/bin/tool", ["tool"], 0x7ffd /* 20 vars */) = 0
+2 execve("/usr/local/bin/git", ["git", "fetch"], 0x7ffd /* 20 vars */ <unfinished ...>
+1 wait4(-1,  <unfinished ...>
+2 <... execve resumed>) = -1 ENOENT (No such file or directory)
+2 execve("/usr/bin/git", ["git", "fetch"], 0x7ffd /* 20 vars */ <unfinished ...>
+2 <... execve resumed>) = 0
+"#;
+        let syscalls = Syscalls::parse(log);
+        let subprocesses = syscalls.subprocesses();
+        assert_eq!(subprocesses.len(), 1);
+        assert_eq!(subprocesses[0].program(), path::Path::new("/usr/bin/git"));
+        let names: Vec<_> = syscalls.iter().map(Syscall::name).collect();
+        assert_eq!(names, ["execve", "execve", "wait4", "execve"]);
+
+        let unfinished = Syscalls::parse(
+            r#"1 execve("/bin/tool", ["tool"], 0x7ffd /* 20 vars */) = 0
+2 execve("/usr/bin/git", ["git"], 0x7ffd /* 20 vars */ <unfinished ...>
+"#,
+        );
+        assert!(unfinished.subprocesses().is_empty());
+    }
+
+    #[test]
+    fn redacted() {
+        crate::redact::secret("strace-unit-s3cr3t");
+        let log = r#"1 execve("/bin/sh", ["sh"], 0x7ffd /* 20 vars */) = 0
+2 execve("/usr/bin/curl", ["curl", "strace-unit-s3cr3t"], 0x7ffd /* 20 vars */) = 0
+"#;
+        let err = Syscalls::parse(log)
+            .try_spawned_no_subprocesses()
+            .unwrap_err()
+            .to_string();
+        assert!(!err.contains("strace-unit-s3cr3t"), "{err}");
+    }
+
+    #[test]
+    fn network() {
+        Syscalls::parse(LOG).no_network_syscalls();
+        let syscalls = Syscalls::parse("1 socket(AF_INET6, SOCK_DGRAM, IPPROTO_IP) = 3\n");
+        assert!(syscalls.try_no_network_syscalls().is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Run the same invocation many times concurrently, see [`Command::stress`].
+//!
+//! This catches races that only show up under contention, like two runs taking the same lock
+//! file or port.
+//!
+//! [`Command::stress`]: crate::Command::stress
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use crate::assert::Assert;
+use crate::assert::AssertError;
+use crate::assert::AssertResult;
+use crate::Command;
+
+/// How many failures are shown in full.
+const SHOWN: usize = 3;
+
+/// Run each of `runs` with up to `parallelism` at a time, passing each [`Assert`] to `check`.
+pub(crate) fn run<F>(runs: Vec<Command>, parallelism: usize, check: F) -> Result<(), StressError>
+where
+    F: Fn(Assert) -> AssertResult + Sync,
+{
+    let iterations = runs.len();
+    let queue = Mutex::new(runs.into_iter().enumerate());
+    let failures = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..parallelism.clamp(1, iterations.max(1)) {
+            scope.spawn(|| loop {
+                let next = queue.lock().unwrap_or_else(PoisonError::into_inner).next();
+                let Some((iteration, mut cmd)) = next else {
+                    break;
+                };
+                let result = cmd
+                    .try_assert()
+                    .and_then(|assert| check(assert.append_context("iteration", iteration)));
+                if let Err(err) = result {
+                    failures
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push((iteration, err));
+                }
+            });
+        }
+    });
+    let mut failures = failures
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner);
+    if failures.is_empty() {
+        return Ok(());
+    }
+    failures.sort_by_key(|(iteration, _)| *iteration);
+    Err(StressError::new(iterations, failures))
+}
+
+/// Why a failure happened, to group failures with the same cause.
+fn cause(err: &AssertError) -> String {
+    let message = err.to_string();
+    let first_line = message.lines().next().unwrap_or_default().to_owned();
+    match err.output().and_then(|output| output.status.code()) {
+        Some(code) => format!("{first_line} (code {code})"),
+        None => first_line,
+    }
+}
+
+/// [`Command::stress`][crate::Command::stress] failure.
+#[derive(Debug)]
+pub struct StressError {
+    iterations: usize,
+    causes: Vec<(String, usize)>,
+    failures: Vec<(usize, AssertError)>,
+}
+
+impl StressError {
+    fn new(iterations: usize, failures: Vec<(usize, AssertError)>) -> Self {
+        let mut causes: Vec<(String, usize)> = Vec::new();
+        for (_, err) in &failures {
+            let cause = cause(err);
+            match causes.iter_mut().find(|(seen, _)| *seen == cause) {
+                Some((_, count)) => *count += 1,
+                None => causes.push((cause, 1)),
+            }
+        }
+        Self {
+            iterations,
+            causes,
+            failures,
+        }
+    }
+
+    /// How many runs failed.
+    pub fn failed(&self) -> usize {
+        self.failures.len()
+    }
+
+    /// Each distinct cause of failure, with how many runs it failed.
+    pub fn causes(&self) -> &[(String, usize)] {
+        &self.causes
+    }
+
+    /// Each failed run, by iteration.
+    pub fn failures(&self) -> &[(usize, AssertError)] {
+        &self.failures
+    }
+}
+
+impl Error for StressError {}
+
+impl fmt::Display for StressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} runs failed",
+            self.failures.len(),
+            self.iterations
+        )?;
+        for (cause, count) in &self.causes {
+            writeln!(f, "  {count}x {cause}")?;
+        }
+        for (iteration, err) in self.failures.iter().take(SHOWN) {
+            writeln!(f, "\nrun {iteration}:\n{err}")?;
+        }
+        if SHOWN < self.failures.len() {
+            writeln!(f, "\n{} more failures", self.failures.len() - SHOWN)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+//! Temporary directories private to this user.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path;
+use std::process;
+use std::sync::atomic;
+
+/// Give up after this many names are already taken, rather than loop forever.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// Create an empty directory in the temporary directory, named after `purpose`.
+///
+/// Names are predictable, so the directory is created exclusively, never reusing one planted by
+/// another user, and on Unix only its owner may access it.
+pub(crate) fn create_dir(purpose: &str) -> io::Result<path::PathBuf> {
+    static COUNTER: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    for _ in 0..MAX_ATTEMPTS {
+        let id = COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("assert_cmd-{purpose}-{}-{id}", process::id()));
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("no free name for a temporary `{purpose}` directory"),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn private() {
+        let dir = create_dir("private").unwrap();
+        let other = create_dir("private").unwrap();
+        assert_ne!(dir, other);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            let mode = fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        fs::remove_dir(dir).unwrap();
+        fs::remove_dir(other).unwrap();
+    }
+}
@@ -154,3 +154,666 @@ fn stderr_example() {
         .assert()
         .stderr("world\n");
 }
+
+#[test]
+fn output_result_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .output()
+        .assert()
+        .success()
+        .stdout("hello\n");
+}
+
+#[test]
+#[should_panic(expected = "Failed to spawn command")]
+fn output_result_spawn_failure() {
+    Command::new("non-existent-assert-cmd-binary")
+        .output()
+        .assert();
+}
+
+#[test]
+fn output_result_spawn_failure_message() {
+    let panic = std::panic::catch_unwind(|| {
+        Command::new("non-existent-assert-cmd-binary")
+            .output()
+            .assert();
+    })
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    // Only the error is known, not the command that failed to spawn
+    let path = std::env::var("PATH").unwrap_or_else(|_| "<unset>".to_owned());
+    let lines: Vec<_> = message.lines().collect();
+    assert_eq!(lines.len(), 3, "{message}");
+    assert_eq!(lines[0], "Failed to spawn command");
+    assert_eq!(lines[1], format!("PATH=`{path}`"));
+    assert!(lines[2].starts_with("cause="), "{message}");
+}
+
+#[test]
+#[should_panic(expected = "PATH=`/no/such/dir`")]
+fn spawn_failure_context() {
+    Command::new("non-existent-assert-cmd-binary")
+        .env("PATH", "/no/such/dir")
+        .assert();
+}
+
+#[test]
+fn error_kind_example() {
+    use assert_cmd::assert::AssertErrorKind;
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "42")
+        .assert()
+        .try_code(2)
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::WrongCode);
+    assert!(err.predicate().is_some());
+    assert_eq!(err.output().unwrap().status.code(), Some(42));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .try_stdout("world\n")
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::StdoutMismatch);
+}
+
+#[test]
+fn bytes_case_tree_example() {
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .try_stdout(b"world\n" as &[u8])
+        .unwrap_err();
+    let predicate = err.predicate().unwrap();
+    assert!(predicate.contains(r#"var == "world\n""#), "{predicate}");
+    assert!(predicate.contains(r#"var: "hello\n""#), "{predicate}");
+}
+
+#[test]
+fn test_name_context_example() {
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .try_failure()
+        .unwrap_err();
+    // Styled with the `color` feature
+    let message = strip_sgr(&err.to_string());
+    assert!(
+        message.contains("test=`test_name_context_example`"),
+        "{message}"
+    );
+}
+
+/// Remove the SGR escape sequences the `color` feature styles reports with.
+fn strip_sgr(styled: &str) -> String {
+    let mut plain = String::with_capacity(styled.len());
+    let mut rest = styled;
+    while let Some(start) = rest.find("\x1b[") {
+        plain.push_str(&rest[..start]);
+        rest = &rest[start..];
+        rest = rest
+            .find('m')
+            .map(|end| &rest[end + 1..])
+            .unwrap_or_default();
+    }
+    plain.push_str(rest);
+    plain
+}
+
+#[test]
+fn map_stdout_example() {
+    let (assert, id) = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "id=42")
+        .env("stderr", "warning: slow")
+        .assert()
+        .success()
+        .map_stdout(|stdout| stdout.strip_prefix(b"id=").map(<[u8]>::to_vec));
+    assert_eq!(id.as_deref(), Some(&b"42\n"[..]));
+
+    let (assert, lines) = assert.map_stderr(|stderr| stderr.split(|b| *b == b'\n').count());
+    assert_eq!(lines, 2);
+    assert.stdout("id=42\n");
+}
+
+#[test]
+fn eq_trimmed_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .assert()
+        .stdout_eq_trimmed("hello")
+        .stdout_eq_trimmed("hello\n")
+        .stderr_eq_trimmed("world");
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello\n")
+        .assert()
+        .try_stdout_eq_trimmed("hello")
+        .unwrap_err();
+}
+
+#[test]
+#[cfg(unix)]
+fn jsonrpc_example() {
+    let response = String::from_utf8(assert_cmd::jsonrpc::frame(r#"{"id":1}"#)).unwrap();
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", response)
+        .assert();
+    // `bin_fixture` appends a newline, which isn't a valid frame
+    let err = assert.try_stdout_jsonrpc("{}").unwrap_err();
+    assert!(err.to_string().contains("message 1 is malformed"), "{err}");
+
+    let assert = assert_cmd::Command::new("cat")
+        .write_stdin_jsonrpc([r#"{"id":1}"#, r#"{"id":2}"#])
+        .assert();
+    let assert = assert.stdout_jsonrpc(r#"{"id":2}"#);
+    let err = assert.try_stdout_jsonrpc(r#"{"id":3}"#).unwrap_err();
+    assert!(
+        err.to_string().contains("none of 2 messages matched"),
+        "{err}"
+    );
+}
+
+#[test]
+fn shared_output_example() {
+    use std::sync::Arc;
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert();
+    let output = assert.shared_output();
+    let err = assert.try_stdout("world\n").unwrap_err();
+    assert!(Arc::ptr_eq(&output, &err.shared_output().unwrap()));
+
+    let err = assert_cmd::output::OutputError::from_shared(Arc::clone(&output));
+    assert!(Arc::ptr_eq(&output, &err.shared_output().unwrap()));
+}
+
+#[test]
+fn stdout_each_line_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello\nworld")
+        .assert()
+        .stdout_each_line(predicate::function(|line: &str| {
+            line.chars().all(|c| c.is_ascii_lowercase())
+        }));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello\nWorld")
+        .assert()
+        .try_stdout_each_line(predicate::function(|line: &str| {
+            line.chars().all(|c| c.is_ascii_lowercase())
+        }))
+        .unwrap_err();
+    assert!(err.to_string().contains("line 2: \"World\""), "{err}");
+}
+
+#[test]
+fn empty_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "world")
+        .assert()
+        .stdout_empty();
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "thread 'main' panicked at src/main.rs:1:1")
+        .assert()
+        .try_stderr_empty()
+        .unwrap_err();
+    assert!(err.to_string().contains("looks like a panic"), "{err}");
+}
+
+#[test]
+fn indoc_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "Usage:\n  bin_fixture [OPTIONS]")
+        .assert()
+        .stdout_indoc(
+            r#"
+            Usage:
+              bin_fixture [OPTIONS]
+            "#,
+        );
+}
+
+#[test]
+fn visible_whitespace_example() {
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello ")
+        .assert()
+        .try_stdout("hello\n")
+        .unwrap_err();
+    assert!(err.to_string().contains("hello·↵"), "{err}");
+}
+
+#[test]
+fn normalize_paths_example() {
+    use assert_cmd::normalize::NormalizePaths;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", r"C:\Users\me\project\src\main.rs")
+        .assert()
+        .normalize_paths(NormalizePaths::new().strip_drive_letters(true))
+        .stdout("/Users/me/project/src/main.rs\n");
+}
+
+#[test]
+fn outputs_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "error: no input")
+        .env("exit", "2")
+        .assert()
+        .outputs(
+            predicate::str::is_empty(),
+            predicate::str::contains("no input"),
+            2,
+        );
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("stderr", "world")
+        .assert()
+        .try_outputs("", "world\n", 1)
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        assert_cmd::assert::AssertErrorKind::StdoutMismatch
+    );
+    let message = err.to_string();
+    assert!(message.contains("stdout: failed"), "{message}");
+    assert!(message.contains("stderr: passed"), "{message}");
+    assert!(message.contains("code: failed"), "{message}");
+}
+
+#[test]
+#[cfg(feature = "ansi")]
+fn styled_example() {
+    use assert_cmd::ansi::contains;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1;31merror\x1b[0m: no input")
+        .assert()
+        .stdout_styled(contains("error").in_red().bold())
+        .stdout_styled(contains("no input").plain());
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[33merror\x1b[0m: no input")
+        .assert()
+        .try_stdout_styled(contains("error").in_red())
+        .unwrap_err();
+    assert!(err.to_string().contains("found: yellow"), "{err}");
+}
+
+#[test]
+#[cfg(feature = "encoding")]
+fn decoded_example() {
+    let output = std::process::Output {
+        status: Command::cargo_bin("bin_fixture").unwrap().status().unwrap(),
+        stdout: b"caf\xe9\n".to_vec(),
+        stderr: b"\xff\xfee\x00r\x00r\x00".to_vec(),
+    };
+    output
+        .clone()
+        .assert()
+        .stdout_decoded(encoding_rs::WINDOWS_1252, "café\n")
+        .stderr_decoded(encoding_rs::UTF_16LE, "err");
+
+    let err = output
+        .assert()
+        .try_stdout_decoded(encoding_rs::UTF_8, "café\n")
+        .unwrap_err();
+    assert!(err.to_string().contains("UTF-8"), "{err}");
+}
+
+#[test]
+fn normalize_text_example() {
+    use assert_cmd::normalize::NormalizeText;
+
+    let output = std::process::Output {
+        status: Command::cargo_bin("bin_fixture").unwrap().status().unwrap(),
+        stdout: b"\xef\xbb\xbfline 1\r\nline 2\r\n".to_vec(),
+        stderr: Vec::new(),
+    };
+    let err = output
+        .assert()
+        .normalize_text(NormalizeText::new())
+        .stdout("line 1\nline 2\n")
+        .try_stdout("line 1\n")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("stdout (UTF-8 BOM, CRLF)"),
+        "{err}"
+    );
+}
+
+#[test]
+fn attach_file_example() {
+    use assert_cmd::assert::AssertErrorKind;
+
+    let log = std::env::temp_dir().join(format!("assert_cmd-attach-{}.log", std::process::id()));
+    std::fs::write(&log, "listening on 8080\n").unwrap();
+
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .attach_file(&log)
+        .attached_file(&log, predicate::str::contains("listening"));
+    let err = assert.try_failure().unwrap_err();
+    assert!(err.to_string().contains("listening on 8080"), "{err}");
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .try_attached_file(&log, predicate::str::contains("error"))
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::FileMismatch);
+    std::fs::remove_file(&log).unwrap();
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .try_attached_file(&log, predicate::str::contains("listening"))
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::FileMismatch);
+    assert!(err.to_string().contains("Failed to read"), "{err}");
+}
+
+#[test]
+#[cfg(unix)]
+fn help_and_version_example() {
+    use assert_cmd::assert::HelpRules;
+
+    Command::new("sh")
+        .args(["-c", "printf 'Does things\\n\\nUsage: tool [OPTIONS]\\n'"])
+        .assert()
+        .help_like_with(&HelpRules::new().bin_name("tool").max_width(80));
+
+    let err = Command::new("sh")
+        .args(["-c", "printf 'Usage: other\\n'"])
+        .assert()
+        .try_help_like_with(&HelpRules::new().bin_name("tool"))
+        .unwrap_err();
+    assert!(err.to_string().contains("name \"tool\""), "{err}");
+
+    Command::new("sh")
+        .args(["-c", "echo tool v$CARGO_PKG_VERSION"])
+        .assert()
+        .version_matches_crate();
+
+    Command::new("sh")
+        .args(["-c", "echo tool 1.2.30"])
+        .assert()
+        .try_version_matches("1.2.3")
+        .unwrap_err();
+}
+
+#[test]
+#[cfg(unix)]
+fn completion_script_example() {
+    use assert_cmd::completions::Shell;
+
+    Command::new("echo")
+        .arg("complete -F _tool tool")
+        .assert()
+        .completion_script(Shell::Bash);
+
+    Command::new("echo")
+        .arg("_tool() { :; }")
+        .assert()
+        .try_completion_script(Shell::Zsh)
+        .unwrap_err();
+
+    // Without `bash`, only the `complete` marker is checked
+    if assert_cmd::requires::binary("bash").skip() {
+        return;
+    }
+    Command::new("echo")
+        .arg("complete -F _tool tool; if")
+        .assert()
+        .try_completion_script(Shell::Bash)
+        .unwrap_err();
+}
+
+#[test]
+fn per_platform_example() {
+    use assert_cmd::platform::skip_on;
+    use assert_cmd::platform::Platform;
+
+    let current = Platform::os(std::env::consts::OS).and_arch(std::env::consts::ARCH);
+    assert!(!skip_on(Platform::os("not-an-os")));
+    assert!(skip_on(current.clone()));
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .stdout_per_platform(&[(Platform::os("not-an-os"), "bye\n"), (current, "hello\n")]);
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .try_stdout_per_platform(&[(Platform::arch("not-an-arch"), "")])
+        .unwrap_err();
+}
+
+#[test]
+fn code_per_platform_example() {
+    use assert_cmd::assert::AssertErrorKind;
+    use assert_cmd::platform::Platform;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "3")
+        .assert()
+        .code_per_platform(&[(Platform::os("not-an-os"), 0), (Platform::any(), 3)]);
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .try_code_per_platform(&[(Platform::os("not-an-os"), 0)])
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::WrongCode);
+    let message = err.to_string();
+    assert!(message.contains("no expectation"), "{message}");
+}
+
+#[test]
+fn ignore_case_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "Ärger")
+        .env("stderr", "Error")
+        .assert()
+        .stdout_eq_ignore_case("ÄRGER\n")
+        .stderr_eq_ignore_case("error\n")
+        .try_stdout_eq_ignore_case("ARGER\n")
+        .unwrap_err();
+}
+
+#[test]
+#[cfg(feature = "unicode")]
+fn unicode_normalization_example() {
+    use assert_cmd::normalize::NormalizeText;
+    use assert_cmd::normalize::UnicodeForm;
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "cafe\u{301}")
+        .assert()
+        .normalize_text(NormalizeText::new().unicode(Some(UnicodeForm::Nfc)))
+        .stdout("caf\u{e9}\n");
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn json_example() {
+    use assert_cmd::json::JsonRules;
+
+    let stdout = r#"{"time": 1700000000, "items": [{"id": 7, "name": "a"}]}"#;
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", stdout)
+        .assert()
+        .stdout_json_with(
+            r#"{"items": [{"name": "a", "id": 0}], "time": 0}"#,
+            &JsonRules::new().ignore("$.time").ignore("$.items[*].id"),
+        );
+
+    let err = assert
+        .try_stdout_json(r#"{"items": [{"name": "a", "id": 0}], "time": 0}"#)
+        .unwrap_err();
+    assert!(err.to_string().contains("$.items[0].id"), "{err}");
+}
+
+#[test]
+fn named_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .named("first run")
+        .success();
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "1")
+        .assert()
+        .named("second run")
+        .try_success()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("[second run] Unexpected failure"),
+        "{err}"
+    );
+}
+
+#[test]
+fn section_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env(
+            "stdout",
+            "noise\n== SUMMARY ==\n3 passed\n== END ==\nmore noise",
+        )
+        .env("stderr", "warning: slow\n-- begin --\nok\n-- end --\n")
+        .assert()
+        .stdout_section(
+            "== SUMMARY ==",
+            "== END ==",
+            predicate::str::diff("3 passed"),
+        )
+        .stderr_section("-- begin --", "-- end --", predicate::str::diff("ok"));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "== SUMMARY ==\n3 passed\n")
+        .assert()
+        .try_stdout_section("== SUMMARY ==", "== END ==", predicate::always())
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains(r#"end marker "== END ==" not found after line 1"#),
+        "{err}"
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn nothing_asserted_example() {
+    // Only warns, unless in strict mode
+    Command::cargo_bin("bin_fixture").unwrap().assert();
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "1")
+        .assert()
+        .try_success()
+        .unwrap_err();
+    drop(err.assert());
+}
+
+#[test]
+fn decode_example() {
+    let reverse = |bytes: &[u8]| -> Result<Vec<u8>, String> {
+        let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        Ok(text
+            .trim_end()
+            .chars()
+            .rev()
+            .collect::<String>()
+            .into_bytes())
+    };
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "olleh")
+        .env("stderr", "dlrow")
+        .assert()
+        .decode_stdout(reverse)
+        .decode_stderr(reverse)
+        .stdout("hello")
+        .stderr("world");
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .try_decode_stdout(|_| Err("not gzip"))
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        assert_cmd::assert::AssertErrorKind::StdoutMismatch
+    );
+    assert!(
+        err.to_string()
+            .contains("Failed to decode stdout: not gzip"),
+        "{err}"
+    );
+}
+
+#[test]
+fn plain_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1;31merror\x1b[0m: no input")
+        .env("stderr", "\x1b[33mwarning\x1b[0m")
+        .assert()
+        .stdout_plain("error: no input\n")
+        .stdout(predicate::str::contains("\x1b[1;31m"))
+        .stderr_plain("warning\n");
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "\x1b[1;31merror\x1b[0m")
+        .assert()
+        .try_stdout_plain("warning\n")
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains("Unexpected stdout with ANSI escapes stripped"),
+        "{message}"
+    );
+    assert!(message.contains("plain stdout"), "{message}");
+}
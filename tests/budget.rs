@@ -0,0 +1,33 @@
+//! The budget is process-wide, so this runs in its own test binary.
+
+use std::time::Duration;
+
+use assert_cmd::assert::AssertErrorKind;
+use assert_cmd::Command;
+
+#[test]
+#[cfg(unix)]
+fn budget_fails_fast_once_exceeded() {
+    assert_cmd::budget::set_budget(Some(Duration::from_millis(300)));
+
+    let err = Command::new("sleep")
+        .arg("10")
+        .timeout(Duration::from_secs(5))
+        .assert()
+        .try_success()
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::Timeout);
+    assert!(err.to_string().contains("budget"), "{err}");
+    assert!(assert_cmd::budget::spent() < Duration::from_secs(5));
+
+    let err = Command::new("true").try_assert().unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::Timeout);
+    assert!(err.to_string().contains("budget"), "{err}");
+    assert_eq!(assert_cmd::budget::remaining(), Some(Duration::ZERO));
+
+    let output = Command::new("sleep").arg("10").output().unwrap();
+    assert!(!output.status.success());
+
+    assert_cmd::budget::set_budget(Some(Duration::from_secs(3600)));
+    Command::new("true").assert().success();
+}
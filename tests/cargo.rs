@@ -60,3 +60,34 @@ fn cargo_bin_example_2() {
     let output = cmd.unwrap();
     println!("{output:?}");
 }
+
+#[test]
+fn missing_binary_lists_tried() {
+    use std::error::Error as _;
+
+    let err = Command::cargo_bin("no_such_bin").unwrap_err();
+    let not_found = err
+        .source()
+        .and_then(|cause| cause.downcast_ref::<assert_cmd::cargo::NotFoundError>())
+        .unwrap();
+    assert!(!not_found.tried().is_empty());
+    let file = format!("no_such_bin{}", std::env::consts::EXE_SUFFIX);
+    assert!(not_found.tried().iter().all(|path| path.ends_with(&file)));
+    assert!(err.to_string().contains("Cargo command not found"), "{err}");
+}
+
+#[test]
+fn failure_identifies_binary() {
+    let err = assert_cmd::Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "1")
+        .assert()
+        .try_success()
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("binary"), "{message}");
+    if cfg!(feature = "sha2") {
+        assert!(message.contains("sha256 "), "{message}");
+    }
+    assert!(message.contains(" bytes"), "{message}");
+}
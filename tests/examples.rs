@@ -26,3 +26,858 @@ fn timeout_example() {
         .assert();
     assert.failure();
 }
+
+#[test]
+fn timeout_error_kind() {
+    use assert_cmd::assert::AssertErrorKind;
+    use assert_cmd::Command;
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .timeout(std::time::Duration::from_secs(1))
+        .env("sleep", "100")
+        .assert()
+        .try_success()
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::Timeout);
+}
+
+#[test]
+fn timeout_output_is_ok() {
+    use assert_cmd::Command;
+
+    let output = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .timeout(std::time::Duration::from_secs(1))
+        .env("stdout", "hello")
+        .env("sleep", "100")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert_eq!(output.stdout, b"hello\n");
+}
+
+#[test]
+fn env_matrix_example() {
+    use assert_cmd::matrix::EnvMatrix;
+    use assert_cmd::Command;
+
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    let mut runs = 0;
+    EnvMatrix::new()
+        .var("stdout", ["hello", "world"])
+        .var("exit", [None, Some("0")])
+        .run(&mut cmd, |cell, assert| {
+            runs += 1;
+            let expected = format!("{}\n", cell.get("stdout").unwrap().to_str().unwrap());
+            assert.success().stdout(expected);
+        });
+    assert_eq!(runs, 4);
+}
+
+#[test]
+#[cfg(unix)]
+fn arg_matrix_example() {
+    use assert_cmd::matrix::ArgMatrix;
+    use assert_cmd::Command;
+
+    let mut runs = 0;
+    ArgMatrix::new().args([["-q"], ["-v"]]).run(
+        || Command::new("echo"),
+        |cell, assert| {
+            runs += 1;
+            let expected = format!("{}\n", cell.args()[0].to_str().unwrap());
+            assert.success().stdout(expected);
+        },
+    );
+    assert_eq!(runs, 2);
+}
+
+#[test]
+fn locale_example() {
+    use assert_cmd::Command;
+
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    let Some(cmd) = cmd.locale("C") else {
+        panic!("`C` is always available");
+    };
+    let envs: Vec<_> = cmd.get_envs().collect();
+    assert!(envs.contains(&("LC_ALL".as_ref(), Some("C".as_ref()))));
+    cmd.assert().success();
+
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    assert!(cmd.locale("xx_NOT_A_LOCALE.UTF-8").is_none() || cfg!(windows));
+}
+
+#[test]
+fn stdin_timeout_example() {
+    use assert_cmd::assert::AssertErrorKind;
+    use assert_cmd::Command;
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .stdin_timeout(std::time::Duration::from_secs(1))
+        .timeout(std::time::Duration::from_secs(60))
+        .env("sleep", "100")
+        .write_stdin(vec![b'a'; 10_000_000])
+        .assert()
+        .try_success()
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::Timeout);
+    assert!(
+        err.to_string().contains("Timed out writing to stdin"),
+        "{err}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn shell_example() {
+    Command::shell("printf 'a\\nb\\n' | wc -l")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("2"));
+}
+
+#[test]
+#[cfg(unix)]
+fn wrapped_by_example() {
+    let mut wrapper = Command::new("sh");
+    wrapper.args(["-c", r#""$@"; exit $(($? + 100))"#, "wrapper"]);
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("exit", "3")
+        .wrapped_by(wrapper)
+        .wrapper_exit_code(|code| code - 100)
+        .assert()
+        .code(3)
+        .stdout("hello\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn wrapped_by_keeps_env_clear() {
+    use predicates::prelude::*;
+
+    let mut wrapper = Command::new("/usr/bin/env");
+    wrapper.env("wrapper", "set");
+
+    let assert = Command::new("/usr/bin/env")
+        .env_clear()
+        .env("inner", "set")
+        .wrapped_by(wrapper)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("inner=set"))
+        .stdout(predicates::str::contains("wrapper=set"))
+        .stdout(predicates::str::contains("CARGO").not());
+    assert!(assert.to_string().contains("env -i"), "{assert}");
+}
+
+#[test]
+#[cfg(unix)]
+fn assert_within_example() {
+    let marker = std::env::temp_dir().join(format!("assert_within-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+    let script = format!(
+        "test -e {0} && echo ready || {{ touch {0}; echo starting; }}",
+        marker.display()
+    );
+    Command::shell(script).assert_within(std::time::Duration::from_secs(5), |assert| {
+        assert.try_stdout("ready\n")
+    });
+    std::fs::remove_file(&marker).unwrap();
+
+    let err = Command::shell("echo starting")
+        .try_assert_within(std::time::Duration::from_millis(250), |assert| {
+            assert.try_stdout("ready\n")
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("attempts"), "{err}");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn inherit_listener_example() {
+    let first = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let second = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let output = Command::shell(
+        r#"echo "$LISTEN_FDS $LISTEN_PID $$"; readlink /proc/$$/fd/3 /proc/$$/fd/4"#,
+    )
+    .inherit_listener(first)
+    .unwrap()
+    .inherit_listener(second)
+    .unwrap()
+    .assert()
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let output = String::from_utf8(output).unwrap();
+    let mut lines = output.lines();
+    let fields: Vec<_> = lines.next().unwrap().split(' ').collect();
+    assert_eq!(fields[0], "2", "{output}");
+    assert_eq!(fields[1], fields[2], "{output}");
+    assert!(lines.all(|line| line.starts_with("socket:")), "{output}");
+}
+
+#[test]
+#[cfg(unix)]
+fn inherit_listener_keeps_pre_exec() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    Command::new("cat")
+        .env_clear()
+        .env("PATH", "/bin:/usr/bin")
+        .stdin_closed()
+        .inherit_listener(listener)
+        .unwrap()
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Bad file descriptor"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn inherit_listener_after_first_run() {
+    let mut cmd = Command::shell(r#"echo "$LISTEN_FDS"; readlink /proc/$$/fd/4; cat"#);
+    cmd.inherit_listener(std::net::TcpListener::bind("127.0.0.1:0").unwrap())
+        .unwrap();
+    cmd.assert().success();
+
+    cmd.inherit_listener(std::net::TcpListener::bind("127.0.0.1:0").unwrap())
+        .unwrap()
+        .stdin_closed()
+        .assert()
+        .failure()
+        .stdout(predicates::str::starts_with("2\nsocket:"))
+        .stderr(predicates::str::contains("Bad file descriptor"));
+}
+
+#[test]
+#[cfg(unix)]
+fn inherit_fd_conflicting_with_listener() {
+    use std::os::unix::io::AsRawFd;
+
+    let file = std::fs::File::open("Cargo.toml").unwrap();
+    let fd = usize::try_from(file.as_raw_fd()).unwrap();
+    let mut cmd = Command::new("true");
+    cmd.inherit_fd(file).unwrap();
+    let mut listeners = 0;
+    let err = loop {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        match cmd.inherit_listener(listener) {
+            Ok(_) => listeners += 1,
+            Err(err) => break err,
+        }
+    };
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(3 + listeners, fd, "{err}");
+}
+
+#[test]
+#[cfg(unix)]
+fn shim_example() {
+    use assert_cmd::shim::{ShimDir, ShimResponse};
+
+    let mut shims = ShimDir::new().unwrap();
+    shims
+        .add("git", ShimResponse::new().stdout("main\n").code(3))
+        .unwrap();
+    let mut cmd = Command::shell("echo input | git branch --show-current; echo code=$?");
+    shims.apply(&mut cmd).unwrap();
+    cmd.assert().success().stdout("main\ncode=3\n");
+
+    shims
+        .called("git")
+        .called_with("git", &["branch", "--show-current"])
+        .not_called("docker");
+    let calls = shims.calls("git").unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].stdin(), b"input\n");
+    assert!(shims.try_called_with("git", &["status"]).is_err());
+}
+
+#[test]
+fn assert_status_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "a lot of output")
+        .assert_status()
+        .success()
+        .stdout_empty();
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "42")
+        .try_assert_status()
+        .unwrap()
+        .try_success()
+        .unwrap_err();
+    assert!(err.to_string().contains("not captured"), "{err}");
+}
+
+#[test]
+fn assert_to_files_example() {
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "a lot of output")
+        .env("stderr", "world")
+        .assert_to_files()
+        .success()
+        .stdout_contains("a lot")
+        .stdout_each_line("a lot of output")
+        .stderr_contains(b"world" as &[u8]);
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert_to_files()
+        .try_stdout_contains("world")
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        assert_cmd::assert::AssertErrorKind::StdoutMismatch
+    );
+    let message = err.to_string();
+    assert!(message.contains("stdout content"), "{message}");
+    assert!(message.contains("command"), "{message}");
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("sleep", "10")
+        .timeout(std::time::Duration::from_millis(200))
+        .assert_to_files()
+        .try_success()
+        .unwrap_err();
+    assert_eq!(err.kind(), assert_cmd::assert::AssertErrorKind::Timeout);
+}
+
+#[test]
+fn pid_example() {
+    let assert = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .success();
+    assert_ne!(assert.pid().unwrap(), std::process::id());
+    assert!(assert.started_at().unwrap() <= assert.finished_at().unwrap());
+
+    let output = Command::cargo_bin("bin_fixture").unwrap().output().unwrap();
+    assert_eq!(assert_cmd::assert::Assert::new(output).pid(), None);
+}
+
+#[test]
+#[cfg(unix)]
+fn timing_example() {
+    let assert = Command::new("sleep").arg("0.2").assert().success();
+    assert!(std::time::Duration::from_millis(200) <= assert.execution_time().unwrap());
+    let total = assert
+        .finished_at()
+        .unwrap()
+        .duration_since(assert.started_at().unwrap())
+        .unwrap();
+    let parts = assert.spawn_latency().unwrap()
+        + assert.execution_time().unwrap()
+        + assert.teardown_time().unwrap();
+    assert!(
+        parts <= total + std::time::Duration::from_millis(50),
+        "{parts:?} > {total:?}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn warn_after_example() {
+    let err = Command::new("sleep")
+        .arg("0.1")
+        .warn_after(std::time::Duration::from_millis(10))
+        .assert()
+        .try_failure()
+        .unwrap_err();
+    assert!(err.to_string().contains("slow"), "{err}");
+
+    let err = Command::new("true")
+        .warn_after(std::time::Duration::from_secs(60))
+        .assert()
+        .try_failure()
+        .unwrap_err();
+    assert!(!err.to_string().contains("slow"), "{err}");
+}
+
+#[test]
+#[cfg(unix)]
+fn unix_passthrough_example() {
+    Command::shell("echo $0")
+        .arg0("renamed")
+        .process_group(0)
+        .assert()
+        .success()
+        .stdout("renamed\n");
+
+    let mut cmd = Command::cargo_bin("bin_fixture").unwrap();
+    // SAFETY: the hook doesn't allocate or take locks
+    unsafe {
+        cmd.pre_exec(|| Err(std::io::ErrorKind::PermissionDenied.into()));
+    }
+    cmd.try_assert().unwrap_err();
+}
+
+#[test]
+#[cfg(unix)]
+fn write_stdin_chunks_example() {
+    let start = std::time::Instant::now();
+    Command::shell("read first; echo \"$first\"; read second; echo \"$second\"")
+        .write_stdin_chunks([
+            ("line1\n", std::time::Duration::from_millis(300)),
+            ("line2\n", std::time::Duration::ZERO),
+        ])
+        .assert()
+        .success()
+        .stdout("line1\nline2\n");
+    assert!(start.elapsed() >= std::time::Duration::from_millis(300));
+}
+
+#[test]
+#[cfg(unix)]
+fn stdin_failures_example() {
+    Command::new("cat")
+        .write_stdin("truncated")
+        .close_stdin_after(5)
+        .assert()
+        .success()
+        .stdout("trunc");
+
+    Command::new("cat")
+        .stdin_closed()
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Bad file descriptor"));
+
+    Command::new("cat")
+        .stdin_read_error()
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("directory"));
+}
+
+#[test]
+#[cfg(unix)]
+fn close_stdout_after_example() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let assert = Command::new("yes")
+        .close_stdout_after(10)
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout("y\ny\ny\ny\ny\n");
+    assert_eq!(assert.get_output().status.signal(), Some(13));
+}
+
+#[test]
+#[cfg(unix)]
+fn deterministic_example() {
+    let err = Command::new("sh")
+        .args(["-c", "echo $SOURCE_DATE_EPOCH $ASSERT_CMD_SEED $TZ"])
+        .deterministic(42)
+        .assert()
+        .stdout("946684800 42 UTC\n")
+        .try_failure()
+        .unwrap_err();
+    assert!(err.to_string().contains("seed"), "{err}");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn scheduling_example() {
+    // Pin to a core this test is allowed on, which may not include core 0
+    // SAFETY: `cpu_set_t` is plain data, for which all zeroes is the empty set
+    let mut allowed: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    // `size_of_val` is only in the prelude since Rust 1.80
+    #[allow(unused_qualifications)]
+    let size = std::mem::size_of_val(&allowed);
+    // SAFETY: `allowed` is a `cpu_set_t` of `size`
+    let res = unsafe { libc::sched_getaffinity(0, size, &mut allowed) };
+    assert_eq!(res, 0, "{}", std::io::Error::last_os_error());
+    let cpu = (0..libc::CPU_SETSIZE as usize)
+        // SAFETY: `cpu` is within the set
+        .find(|cpu| unsafe { libc::CPU_ISSET(*cpu, &allowed) })
+        .unwrap();
+    Command::new("sh")
+        .args(["-c", "grep Cpus_allowed_list /proc/self/status"])
+        .cpu_affinity([cpu])
+        .assert()
+        .success()
+        .stdout(predicates::str::ends_with(format!(":\t{cpu}\n")));
+
+    let err = Command::new("true")
+        .cpu_affinity([])
+        .try_assert()
+        .unwrap_err();
+    assert_eq!(err.kind(), assert_cmd::assert::AssertErrorKind::Spawn);
+}
+
+#[test]
+#[cfg(unix)]
+fn fds_example() {
+    use std::os::unix::io::AsRawFd;
+
+    let path = std::env::temp_dir().join(format!("fds_example-{}", std::process::id()));
+    let log = std::fs::File::create(&path).unwrap();
+    let fd = log.as_raw_fd();
+    let assert = Command::new("sh")
+        .args(["-c", &format!("echo logged >&{fd}")])
+        .close_fds(true)
+        .inherit_fd(log)
+        .unwrap()
+        .report_fds(true)
+        .assert()
+        .success()
+        .only_fds(&[0, 1, 2, fd]);
+    assert_eq!(assert.visible_fds(), Some(&[0, 1, 2, fd][..]));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "logged\n");
+    std::fs::remove_file(&path).unwrap();
+
+    let err = Command::new("true")
+        .report_fds(true)
+        .assert()
+        .success()
+        .try_only_fds(&[0])
+        .unwrap_err();
+    assert_eq!(err.kind(), assert_cmd::assert::AssertErrorKind::FdMismatch);
+
+    let err = Command::new("true")
+        .assert()
+        .try_only_fds(&[0, 1, 2])
+        .unwrap_err();
+    assert_eq!(err.kind(), assert_cmd::assert::AssertErrorKind::FdMismatch);
+}
+
+#[test]
+#[cfg(unix)]
+fn scope_example() {
+    use assert_cmd::scope::Scope;
+
+    let mut scope = Scope::new();
+    let dir = scope.temp_dir().unwrap();
+    std::fs::write(dir.join("file"), "").unwrap();
+    scope.spawn(Command::new("sleep").arg("60")).unwrap();
+    scope
+        .spawn(&mut Command::new("true"))
+        .unwrap()
+        .wait()
+        .unwrap();
+
+    let cleanup = scope.cleanup();
+    assert_eq!(cleanup.killed().len(), 1, "{cleanup}");
+    assert!(cleanup.killed()[0].contains("sleep"), "{cleanup}");
+    assert!(!dir.exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn assert_idempotent_example() {
+    use assert_cmd::assert::AssertErrorKind;
+    use assert_cmd::scope::Scope;
+
+    let mut scope = Scope::new();
+    let dir = scope.temp_dir().unwrap();
+
+    Command::new("sh")
+        .args(["-c", "touch formatted; echo done"])
+        .current_dir(&dir)
+        .assert_idempotent()
+        .success();
+
+    let err = Command::new("sh")
+        .args(["-c", "echo line >> log; wc -l < log"])
+        .current_dir(&dir)
+        .try_assert_idempotent()
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::NotIdempotent);
+    assert!(err.to_string().contains("first stdout"), "{err}");
+
+    Command::new("sh")
+        .args([
+            "-c",
+            "[ -e migrated ] || { touch migrated; echo migrated; }",
+        ])
+        .current_dir(&dir)
+        .assert_idempotent_by(|_first, second| second.stdout.is_empty())
+        .success();
+}
+
+#[test]
+#[cfg(unix)]
+fn compat_example() {
+    use assert_cmd::compat::Compat;
+    use assert_cmd::compat::Part;
+
+    Compat::new("/bin/sh", "/bin/sh").case("same", |cmd| {
+        cmd.args(["-c", "echo same"]);
+    });
+
+    let compat = Compat::new("/bin/echo", "/bin/sh").waive("waived", Part::Stdout);
+    compat.case("waived", |cmd| {
+        cmd.args(["-c", ""]);
+    });
+    let err = compat
+        .try_case("differs", |cmd| {
+            cmd.arg("-c");
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("stdout differs"), "{err}");
+}
+
+#[test]
+#[cfg(unix)]
+fn hooks_example() {
+    let err = Command::new("sh")
+        .args(["-c", "echo $HOOKED"])
+        .before_spawn(|cmd| {
+            cmd.env("HOOKED", "yes");
+        })
+        .after_wait(|assert| assert.append_context("collected", "artifacts"))
+        .assert()
+        .stdout("yes\n")
+        .try_failure()
+        .unwrap_err();
+    assert!(err.to_string().contains("artifacts"), "{err}");
+}
+
+#[test]
+fn requires_example() {
+    use assert_cmd::requires;
+
+    let missing = requires::binary("assert-cmd-no-such-binary");
+    assert!(!missing.is_met());
+    assert!(missing.reason().unwrap().contains("not installed"));
+    assert!(!requires::binary("cargo").skip());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let busy = requires::port_free(port).and(missing);
+    assert!(busy.reason().unwrap().contains(&port.to_string()));
+    drop(listener);
+}
+
+#[test]
+#[cfg(unix)]
+fn stress_example() {
+    Command::new("true").stress(4, 20);
+
+    let err = Command::new("false").try_stress(2, 5).unwrap_err();
+    assert_eq!(err.failed(), 5);
+    assert_eq!(err.causes().len(), 1);
+    assert_eq!(err.causes()[0].1, 5);
+    let message = err.to_string();
+    assert!(message.starts_with("5 of 5 runs failed"), "{message}");
+    assert!(message.contains("2 more failures"), "{message}");
+
+    let err = Command::new("sh")
+        .args(["-c", "echo hello"])
+        .try_stress_by(2, 4, |assert| assert.try_stdout("goodbye\n"))
+        .unwrap_err();
+    assert_eq!(err.failed(), 4);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn stress_copies_process_settings() {
+    Command::shell("echo $0; cat")
+        .arg0("renamed")
+        .stdin_closed()
+        .stress_by(2, 4, |assert| {
+            assert
+                .try_failure()?
+                .try_stdout("renamed\n")?
+                .try_stderr(predicates::str::contains("Bad file descriptor"))
+        });
+
+    Command::shell(r#"echo "$LISTEN_FDS"; readlink /proc/$$/fd/3"#)
+        .inherit_listener(std::net::TcpListener::bind("127.0.0.1:0").unwrap())
+        .unwrap()
+        .stress_by(2, 4, |assert| {
+            assert
+                .try_success()?
+                .try_stdout(predicates::str::starts_with("1\nsocket:"))
+        });
+}
+
+#[test]
+#[cfg(unix)]
+fn pred_example() {
+    use assert_cmd::pred;
+
+    let output = "Compiling\n--- summary ---\n3 passed\n--- end ---\n";
+    Command::new("printf")
+        .arg(output)
+        .assert()
+        .stdout(pred::contains("3 passed"))
+        .stdout(pred::starts_with("Compiling"))
+        .stdout(pred::line(3, predicates::str::ends_with("passed")))
+        .stdout(pred::between_markers(
+            "--- summary ---",
+            "--- end ---",
+            predicates::str::diff("3 passed"),
+        ));
+
+    let err = Command::new("printf")
+        .arg(output)
+        .assert()
+        .try_stdout(pred::line(3, predicates::str::ends_with("failed")))
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("> 3 | 3 passed"), "{message}");
+}
+
+#[test]
+#[cfg(unix)]
+fn expected_failure_example() {
+    Command::new("false")
+        .expected_failure("tracking issue #123")
+        .assert()
+        .success()
+        .code(0);
+
+    let panic = std::panic::catch_unwind(|| {
+        Command::new("true")
+            .expected_failure("tracking issue #123")
+            .assert()
+            .success();
+    })
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains("Unexpectedly passed, expected to fail: tracking issue #123"),
+        "{message}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn failure_facts_example() {
+    let err = Command::new("sh")
+        .args(["-c", "echo hello"])
+        .assert()
+        .try_failure()
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("started"), "{message}");
+    assert!(message.contains("finished"), "{message}");
+    assert!(message.contains("(spawn "), "{message}");
+    assert!(message.contains("stdout 6 B, stderr 0 B"), "{message}");
+}
+
+#[test]
+#[cfg(unix)]
+fn env_changes_example() {
+    let err = Command::new("false")
+        .env("ASSERT_CMD_EXAMPLE", "1")
+        .env_remove("PATH")
+        .env("ASSERT_CMD_EXAMPLE_SECRET", "hunter2")
+        .assert()
+        .try_success()
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(message.contains("+ASSERT_CMD_EXAMPLE=1"), "{message}");
+    assert!(message.contains("-PATH"), "{message}");
+    assert!(
+        message.contains("+ASSERT_CMD_EXAMPLE_SECRET=[redacted]"),
+        "{message}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+#[cfg(feature = "fs")]
+fn temp_dir_example() {
+    use assert_fs::prelude::*;
+
+    let (mut cmd, temp) = Command::new("sh").with_temp_dir().unwrap();
+    temp.child("in.txt").write_str("hello").unwrap();
+    let assert = cmd.args(["-c", "cp in.txt out.txt"]).assert().success();
+    assert_eq!(assert.current_dir(), Some(temp.path()));
+    assert.child_path("out.txt").assert("hello");
+    temp.close().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn signal_cleanup_example() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("signal_cleanup");
+    std::fs::create_dir_all(&dir).unwrap();
+    let script = r#"trap 'rm -f partial.tmp; exit 130' INT; touch partial.tmp; while :; do sleep 0.05; done"#;
+    Command::new("sh")
+        .args(["-c", script])
+        .current_dir(&dir)
+        .send_signal_after(
+            assert_cmd::cmd::Signal::INT,
+            std::time::Duration::from_millis(200),
+        )
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .code(130)
+        .file_absent("partial.tmp");
+
+    let err = Command::new("sh")
+        .args(["-c", "touch partial.tmp"])
+        .current_dir(&dir)
+        .assert()
+        .success()
+        .try_file_absent("partial.tmp")
+        .unwrap_err();
+    assert_eq!(
+        err.kind(),
+        assert_cmd::assert::AssertErrorKind::FileMismatch
+    );
+    std::fs::remove_file(dir.join("partial.tmp")).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn random_stdin_example() {
+    let first = Command::new("cat")
+        .write_stdin_random(1000, 7)
+        .assert()
+        .success();
+    let stdout = first.get_output().stdout.clone();
+    assert_eq!(stdout.len(), 1000);
+    Command::new("cat")
+        .write_stdin_random(1000, 7)
+        .assert()
+        .stdout(stdout);
+
+    let assert = Command::new("cat")
+        .write_stdin_random_lines(50, 7)
+        .assert()
+        .success();
+    let lines = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert_eq!(lines.lines().count(), 50);
+    assert!(lines.lines().all(|line| line.len() <= 80));
+
+    let err = Command::new("cat")
+        .write_stdin_random(10, 1234)
+        .assert()
+        .try_stdout("")
+        .unwrap_err();
+    assert!(err.to_string().contains("1234"), "{err}");
+}
+
+#[test]
+#[cfg(unix)]
+fn stream_for_example() {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", "echo line 1; echo line 2; exec sleep 30"]);
+    let assert = cmd
+        .assert_stream_for(std::time::Duration::from_millis(500))
+        .stdout("line 1\nline 2\n");
+    assert!(assert.try_success().is_err());
+
+    Command::new("sh")
+        .args(["-c", "echo done"])
+        .assert_stream_for(std::time::Duration::from_secs(10))
+        .success()
+        .stdout("done\n");
+}
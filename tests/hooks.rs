@@ -0,0 +1,81 @@
+//! Installed hooks are process-wide, so these run in their own test binary.
+
+use assert_cmd::hooks::Hooks;
+use assert_cmd::Command;
+
+/// Serialize tests installing hooks.
+static INSTALLED: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+#[cfg(unix)]
+fn installed_hooks_run_before_local_ones() {
+    let _installed = INSTALLED
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    Hooks::new()
+        .before_spawn(|cmd| {
+            cmd.env("HOOKED", "installed");
+        })
+        .after_wait(|assert| assert.append_context("hook", "installed"))
+        .install();
+    let err = Command::new("sh")
+        .args(["-c", "echo $HOOKED"])
+        .before_spawn(|cmd| {
+            cmd.env("HOOKED", "local");
+        })
+        .assert()
+        .stdout("local\n")
+        .try_failure()
+        .unwrap_err();
+    Hooks::uninstall();
+
+    let message = err.to_string();
+    assert!(message.contains("hook"), "{message}");
+    assert!(message.contains("installed"), "{message}");
+}
+
+#[test]
+fn progress_counts_commands() {
+    let _installed = INSTALLED
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let started = std::sync::Arc::clone(&events);
+    let finished = std::sync::Arc::clone(&events);
+    Hooks::new()
+        .on_command_start(move |progress| {
+            if progress.command().contains("progress_counts_commands") {
+                started.lock().unwrap().push(("start", progress.clone()));
+            }
+        })
+        .on_command_finish(move |progress| {
+            if progress.command().contains("progress_counts_commands") {
+                finished.lock().unwrap().push(("finish", progress.clone()));
+            }
+        })
+        .install();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "progress_counts_commands")
+        .assert()
+        .success();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "progress_counts_commands")
+        .env("exit", "1")
+        .assert()
+        .failure();
+    Hooks::uninstall();
+
+    let events = events.lock().unwrap();
+    let kinds: Vec<_> = events.iter().map(|(kind, _)| *kind).collect();
+    assert_eq!(kinds, ["start", "finish", "start", "finish"]);
+    let (_, first) = &events[1];
+    let (_, second) = &events[3];
+    assert!(first.status().unwrap().success());
+    assert!(first.elapsed().is_some());
+    assert!(first.started() < second.started());
+    assert!(first.finished() < second.finished());
+    assert!(first.failed() < second.failed());
+    assert!(events[0].1.elapsed().is_none());
+}
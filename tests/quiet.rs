@@ -0,0 +1,22 @@
+//! Quiet mode is process-wide, so this runs in its own test binary.
+
+use assert_cmd::assert::set_quiet;
+use assert_cmd::Command;
+
+#[test]
+fn panic_message_is_first_line() {
+    set_quiet(true);
+    let panic = std::panic::catch_unwind(|| {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("stdout", "hello")
+            .assert()
+            .stdout("world\n");
+    })
+    .unwrap_err();
+    set_quiet(false);
+
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("Unexpected stdout"), "{message}");
+    assert!(!message.contains('\n'), "{message}");
+}
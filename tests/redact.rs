@@ -0,0 +1,105 @@
+//! Redactions are registered process-wide, so these run in their own test binary.
+#![cfg(unix)]
+
+use assert_cmd::redact;
+use assert_cmd::Command;
+
+#[test]
+fn secrets_are_redacted() {
+    redact::env_var("ASSERT_CMD_TEST_DEPLOY");
+    redact::secret("s3cr3t-literal");
+
+    let err = Command::new("sh")
+        .args(["-c", "echo s3cr3t-literal; exit 1"])
+        .env("ASSERT_CMD_TEST_DEPLOY", "deploy-value")
+        .env("ASSERT_CMD_TEST_GITHUB_TOKEN", "token-value")
+        .assert()
+        .try_success()
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(!message.contains("s3cr3t-literal"), "{message}");
+    assert!(!message.contains("deploy-value"), "{message}");
+    assert!(!message.contains("token-value"), "{message}");
+    assert!(
+        message.contains("ASSERT_CMD_TEST_DEPLOY=[redacted]"),
+        "{message}"
+    );
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn patterns_are_redacted() {
+    redact::pattern(regex::Regex::new(r"ghp_[A-Za-z0-9]+").unwrap());
+
+    let err = Command::new("sh")
+        .args(["-c", "echo pushing with ghp_abc123XYZ; exit 1"])
+        .assert()
+        .try_success()
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(!message.contains("ghp_abc123XYZ"), "{message}");
+    assert!(message.contains("pushing with [redacted]"), "{message}");
+}
+
+#[test]
+fn shim_errors_are_redacted() {
+    use assert_cmd::shim::{ShimDir, ShimResponse};
+
+    redact::secret("shim-s3cr3t");
+
+    let mut shims = ShimDir::new().unwrap();
+    shims.add("deploy", ShimResponse::new()).unwrap();
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", "deploy --token shim-s3cr3t"]);
+    shims.apply(&mut cmd).unwrap();
+    cmd.assert().success();
+
+    let message = shims
+        .try_called_with("deploy", &["--token", "other"])
+        .unwrap_err()
+        .to_string();
+    assert!(!message.contains("shim-s3cr3t"), "{message}");
+    assert!(message.contains("[redacted]"), "{message}");
+}
+
+#[test]
+fn scope_cleanup_is_redacted() {
+    use assert_cmd::scope::Scope;
+
+    redact::secret("scope-s3cr3t");
+
+    let mut scope = Scope::new();
+    let child = std::process::Command::new("sleep")
+        .arg("10")
+        .spawn()
+        .unwrap();
+    scope.track("server --token scope-s3cr3t", child);
+    let message = scope.cleanup().to_string();
+    assert!(!message.contains("scope-s3cr3t"), "{message}");
+    assert!(message.contains("server --token [redacted]"), "{message}");
+}
+
+#[test]
+#[cfg(all(feature = "strace", target_os = "linux"))]
+fn syscall_errors_are_redacted() {
+    use assert_cmd::requires;
+    use assert_cmd::strace::Strace;
+
+    if requires::binary("strace").skip() {
+        return;
+    }
+    redact::secret("strace-s3cr3t");
+
+    let strace = Strace::new().unwrap();
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", "/bin/true strace-s3cr3t; true"]);
+    strace.wrap(&mut cmd).assert().success();
+
+    let message = strace
+        .syscalls()
+        .unwrap()
+        .try_spawned_matching("git")
+        .unwrap_err()
+        .to_string();
+    assert!(!message.contains("strace-s3cr3t"), "{message}");
+}
@@ -0,0 +1,28 @@
+//! The stderr policy is process-wide, so this runs in its own test binary.
+
+use assert_cmd::assert::AssertErrorKind;
+use assert_cmd::assert::StderrPolicy;
+use assert_cmd::Command;
+
+#[test]
+fn stderr_policy_example() {
+    StderrPolicy::new()
+        .deny("policy-denied")
+        .allow("policy-denied but allowed")
+        .install();
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "policy-denied but allowed")
+        .assert()
+        .success();
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "policy-denied")
+        .try_assert()
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::StderrMismatch);
+
+    StderrPolicy::uninstall();
+}
@@ -0,0 +1,62 @@
+//! Strict mode is process-wide, so this runs in its own test binary.
+
+use std::panic;
+
+use assert_cmd::assert::{set_strict, Strict};
+use assert_cmd::Command;
+
+#[test]
+fn strict_fails_unasserted_streams() {
+    set_strict(Strict::Stderr);
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .success();
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "warning: deprecated")
+        .assert()
+        .success()
+        .stderr("warning: deprecated\n");
+
+    let panic = panic::catch_unwind(|| {
+        Command::cargo_bin("bin_fixture")
+            .unwrap()
+            .env("stderr", "warning: deprecated")
+            .assert()
+            .success();
+    })
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(
+        message.contains("Unasserted stderr, required by strict mode"),
+        "{message}"
+    );
+    assert!(message.contains("warning: deprecated"), "{message}");
+
+    set_strict(Strict::All);
+    let output = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .get_output()
+        .clone();
+    assert_eq!(output.stdout, b"hello\n");
+
+    if cfg!(debug_assertions) {
+        let panic = panic::catch_unwind(|| {
+            Command::cargo_bin("bin_fixture").unwrap().assert();
+        })
+        .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(
+            message.contains("Nothing was asserted on this run"),
+            "{message}"
+        );
+    }
+
+    set_strict(Strict::Off);
+}
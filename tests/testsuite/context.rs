@@ -0,0 +1,18 @@
+use assert_cmd::assert::add_context_provider;
+use assert_cmd::assert::clear_context_providers;
+use assert_cmd::Command;
+
+#[test]
+fn provided_context_is_in_failures() {
+    add_context_provider(|| vec![("seed", "42".to_owned())]);
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .assert()
+        .try_failure()
+        .unwrap_err();
+    clear_context_providers();
+
+    let message = err.to_string();
+    assert!(message.contains("seed"), "{message}");
+    assert!(message.contains("42"), "{message}");
+}
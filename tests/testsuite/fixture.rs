@@ -0,0 +1,40 @@
+use assert_cmd::fixture::CommandFixture;
+use predicates::prelude::*;
+
+#[test]
+fn stamps_out_commands() {
+    let fixture = CommandFixture::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .env("exit", "3");
+    fixture.command().assert().code(3).stdout("hello\n");
+    fixture.command().env("exit", "0").assert().success();
+
+    fixture.install();
+    CommandFixture::installed()
+        .unwrap()
+        .command()
+        .assert()
+        .code(3);
+    CommandFixture::uninstall();
+    assert_eq!(CommandFixture::installed(), None);
+}
+
+#[test]
+fn always_checks_every_run() {
+    let fixture = CommandFixture::cargo_bin("bin_fixture")
+        .unwrap()
+        .always(|assert| assert.stderr(predicate::str::contains("panicked at").not()));
+    assert_eq!(fixture.clone(), fixture);
+    fixture.command().env("stderr", "ok").assert().success();
+
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        fixture
+            .command()
+            .env("stderr", "thread 'main' panicked at src/main.rs")
+            .assert();
+    }))
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("Unexpected stderr"), "{message}");
+}
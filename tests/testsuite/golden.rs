@@ -0,0 +1,145 @@
+use assert_cmd::assert::AssertErrorKind;
+use assert_cmd::Command;
+
+/// Serialize tests changing process-wide settings.
+static SETTINGS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[test]
+fn failed_comparison_writes_applicable_patch() {
+    let _settings = SETTINGS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let expected = dir.join("hello.txt");
+    std::fs::write(&expected, "hello\nworld\n").unwrap();
+    let patches = dir.join("patches");
+    assert_cmd::golden::set_patch_dir(Some(patches.clone()));
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello\nthere")
+        .assert()
+        .try_stdout_matches_path(&expected)
+        .unwrap_err();
+    assert_cmd::golden::set_patch_dir(None);
+    assert_eq!(err.kind(), AssertErrorKind::StdoutMismatch);
+    let message = err.to_string();
+    assert!(message.contains("-world"), "{message}");
+    assert!(message.contains("+there"), "{message}");
+
+    let patch = std::fs::read_dir(&patches)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    assert!(message.contains(&patch.display().to_string()), "{message}");
+    if assert_cmd::requires::binary("git").skip() {
+        return;
+    }
+    let applied = std::process::Command::new("git")
+        .args(["apply", "--unsafe-paths"])
+        .arg(&patch)
+        .status()
+        .unwrap();
+    assert!(applied.success(), "`git apply {}` failed", patch.display());
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello\nthere")
+        .assert()
+        .stdout_matches_path(&expected);
+}
+
+#[test]
+fn blessing_updates_expectations() {
+    let _settings = SETTINGS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("bless");
+    let _ = std::fs::remove_dir_all(&dir);
+    let stale = dir.join("stale.txt");
+    let missing = dir.join("new").join("missing.txt");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(&stale, "old\n").unwrap();
+
+    assert_cmd::golden::set_bless(true);
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "new")
+        .env("stderr", "err")
+        .assert()
+        .stdout_matches_path(&stale)
+        .stderr_matches_path(&missing);
+    assert_cmd::golden::set_bless(false);
+
+    assert_eq!(std::fs::read_to_string(&stale).unwrap(), "new\n");
+    assert_eq!(std::fs::read_to_string(&missing).unwrap(), "err\n");
+    let updated = assert_cmd::golden::updated();
+    assert!(updated.contains(&stale), "{updated:?}");
+    assert!(updated.contains(&missing), "{updated:?}");
+}
+
+#[test]
+fn ratchet_only_lowers() {
+    let _settings = SETTINGS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("ratchet");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let baseline = dir.join("deprecations.txt");
+    std::fs::write(&baseline, "2\n").unwrap();
+    let warnings = "warning: `foo` is deprecated\nwarning: `bar` is deprecated";
+
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", format!("{warnings}\nwarning: `baz` is deprecated"))
+        .assert()
+        .try_stderr_ratchet("deprecated", &baseline)
+        .unwrap_err();
+    assert_eq!(err.kind(), AssertErrorKind::StderrMismatch);
+    let message = err.to_string();
+    assert!(message.contains("3 lines contain"), "{message}");
+    assert!(message.contains("up from 2"), "{message}");
+
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", warnings)
+        .assert()
+        .stderr_ratchet("deprecated", &baseline);
+
+    assert_cmd::golden::set_bless(true);
+    let raised = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", format!("{warnings}\nwarning: `baz` is deprecated"))
+        .assert()
+        .try_stderr_ratchet("deprecated", &baseline);
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", "warning: `foo` is deprecated")
+        .assert()
+        .stderr_ratchet("deprecated", &baseline);
+    assert_cmd::golden::set_bless(false);
+
+    assert!(raised.is_err());
+    assert_eq!(std::fs::read_to_string(&baseline).unwrap(), "1\n");
+
+    let missing = dir.join("new").join("warnings.txt");
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", warnings)
+        .assert()
+        .try_stderr_ratchet("deprecated", &missing)
+        .unwrap_err();
+    assert!(err.to_string().contains("up from 0"), "{err}");
+    assert_cmd::golden::set_bless(true);
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stderr", warnings)
+        .assert()
+        .stderr_ratchet("deprecated", &missing);
+    assert_cmd::golden::set_bless(false);
+    assert_eq!(std::fs::read_to_string(&missing).unwrap(), "2\n");
+}
@@ -0,0 +1,24 @@
+use assert_cmd::journal::Journal;
+use assert_cmd::Command;
+
+#[test]
+fn records_runs() {
+    let path = std::env::temp_dir().join(format!("assert_cmd-journal-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    Journal::new(&path).install();
+    Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("exit", "3")
+        .assert()
+        .code(3);
+    Journal::uninstall();
+
+    let journal = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    // Other tests in this binary may run while the journal is installed
+    let entry = journal
+        .lines()
+        .find(|line| line.contains(r#""env":{"exit":"3"}"#))
+        .unwrap();
+    assert!(entry.ends_with(r#""code":3}"#), "{entry}");
+}
@@ -0,0 +1,21 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use assert_cmd::Command;
+
+// The child fills `stderr` before reading any of `stdin`, then echoes `stdin`, so each side
+// blocks on a full pipe unless all three are pumped together.
+#[test]
+fn chatty_child_does_not_block() {
+    let input = vec![b'x'; 1024 * 1024];
+    let output = Command::shell("head -c 262144 /dev/zero >&2; cat")
+        .write_stdin(input.clone())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    assert_eq!(output.stderr.len(), 256 * 1024);
+    assert_eq!(output.stdout, input);
+}
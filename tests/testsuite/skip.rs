@@ -0,0 +1,29 @@
+use assert_cmd::requires;
+
+fn skipped_by_macro() {
+    assert_cmd::skip!("needs {}", "a reason");
+}
+
+fn skipped_unless_met() {
+    assert_cmd::skip_unless!(requires::binary("assert_cmd-no-such-binary"));
+    unreachable!("skip_unless! returns");
+}
+
+#[test]
+fn skips_are_recorded() {
+    skipped_by_macro();
+    skipped_unless_met();
+    let skipped = assert_cmd::skip::skipped();
+    let reasons: Vec<_> = skipped
+        .iter()
+        .filter(|(test, _)| test.ends_with("skips_are_recorded"))
+        .map(|(_, reason)| reason.as_str())
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            "needs a reason",
+            "`assert_cmd-no-such-binary` is not installed"
+        ]
+    );
+}
@@ -0,0 +1,18 @@
+//! The theme is process-wide, so this runs in its own test binary.
+
+use assert_cmd::color::Theme;
+use assert_cmd::Command;
+
+#[test]
+fn theme_example() {
+    Theme::plain().install();
+    let err = Command::cargo_bin("bin_fixture")
+        .unwrap()
+        .env("stdout", "hello")
+        .assert()
+        .try_stdout("world\n")
+        .unwrap_err();
+    let message = err.to_string();
+    Theme::uninstall();
+    assert!(!message.contains('\x1b'), "{message}");
+}